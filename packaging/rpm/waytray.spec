@@ -13,8 +13,7 @@ BuildRequires:  gtk4-devel
 BuildRequires:  gtk4-layer-shell-devel
 BuildRequires:  gstreamer1-devel
 BuildRequires:  pkgconfig
-
-Requires:       pulseaudio-utils
+BuildRequires:  systemd-rpm-macros
 
 %description
 WayTray is a compositor-agnostic Linux system tray with a daemon + client
@@ -31,12 +30,24 @@ cargo build --release
 %install
 install -Dm755 target/release/waytray %{buildroot}%{_bindir}/waytray
 install -Dm755 target/release/waytray-daemon %{buildroot}%{_bindir}/waytray-daemon
+install -Dm755 target/release/waytray-ctl %{buildroot}%{_bindir}/waytray-ctl
+install -Dm644 packaging/systemd/waytray.service %{buildroot}%{_userunitdir}/waytray.service
+install -Dm644 packaging/dbus/org.waytray.Daemon.service %{buildroot}%{_datadir}/dbus-1/services/org.waytray.Daemon.service
+
+%post
+%systemd_user_post waytray.service
+
+%preun
+%systemd_user_preun waytray.service
 
 %files
 %license LICENSE
 %doc README.md
 %{_bindir}/waytray
 %{_bindir}/waytray-daemon
+%{_bindir}/waytray-ctl
+%{_userunitdir}/waytray.service
+%{_datadir}/dbus-1/services/org.waytray.Daemon.service
 
 %changelog
 * Tue Jan 06 2026 Harley Richardson <hrichardson2004@hotmail.com> - 1.0.5-1
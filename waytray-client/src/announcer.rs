@@ -0,0 +1,166 @@
+//! Screen reader announcements for important item changes
+//!
+//! The window compares each item with its previous state and announces the
+//! changes enabled in `[client.announcements]`, so they are heard even when
+//! focus is on another item.
+
+use waytray_daemon::config::{AnnouncementsConfig, Config, Politeness};
+use waytray_daemon::{ItemStatus, ModuleItem, ValueKind};
+
+/// A message for the screen reader
+#[derive(Debug, Clone, PartialEq)]
+pub struct Announcement {
+    pub message: String,
+    pub politeness: Politeness,
+}
+
+/// Decides which item changes are announced
+pub struct Announcer {
+    config: AnnouncementsConfig,
+    low_threshold: f64,
+    critical_threshold: f64,
+}
+
+impl Announcer {
+    pub fn new(config: &Config) -> Self {
+        let battery = config.modules.battery.clone().unwrap_or_default();
+        Self {
+            config: config.client.announcements.clone(),
+            low_threshold: battery.low_threshold as f64,
+            critical_threshold: battery.critical_threshold as f64,
+        }
+    }
+
+    /// Announcement for an item that changed from `old`, or appeared if None
+    pub fn announcement(&self, old: Option<&ModuleItem>, new: &ModuleItem) -> Option<Announcement> {
+        if !self.config.enabled {
+            return None;
+        }
+        let politeness = self.config.politeness(&new.module);
+        if politeness == Politeness::Off {
+            return None;
+        }
+
+        let message = self.message(old, new)?;
+        Some(Announcement { message, politeness })
+    }
+
+    fn message(&self, old: Option<&ModuleItem>, new: &ModuleItem) -> Option<String> {
+        let was_attention = old.is_some_and(|old| old.status == ItemStatus::NeedsAttention);
+        if self.config.attention && new.status == ItemStatus::NeedsAttention && !was_attention {
+            return Some(format!("{} needs attention", item_name(new)));
+        }
+
+        let old = old?;
+
+        if self.config.battery_thresholds && new.module == "battery" {
+            if let (Some(before), Some(after)) = (percentage(old), percentage(new)) {
+                // Only drops onto or below a threshold are announced
+                let crossed = |threshold: f64| before > threshold && after <= threshold;
+                if crossed(self.critical_threshold) {
+                    return Some(format!("Battery critical, {}", item_name(new)));
+                }
+                if crossed(self.low_threshold) {
+                    return Some(format!("Battery low, {}", item_name(new)));
+                }
+            }
+        }
+
+        if old.label != new.label
+            && !new.label.is_empty()
+            && self.config.label_modules.contains(&new.module)
+        {
+            return Some(new.label.clone());
+        }
+
+        None
+    }
+}
+
+/// The item's value if it is a percentage
+fn percentage(item: &ModuleItem) -> Option<f64> {
+    item.value
+        .as_ref()
+        .filter(|value| value.kind == ValueKind::Percentage)
+        .map(|value| value.value)
+}
+
+/// Name to speak for an item: its label, else the first tooltip line, else the module
+fn item_name(item: &ModuleItem) -> &str {
+    if !item.label.is_empty() {
+        return &item.label;
+    }
+    item.tooltip
+        .as_deref()
+        .and_then(|tooltip| tooltip.lines().next())
+        .filter(|line| !line.is_empty())
+        .unwrap_or(&item.module)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waytray_daemon::ItemValue;
+
+    fn battery(percent: f64) -> ModuleItem {
+        let mut item = ModuleItem::new("battery", "BAT0", &format!("{}%", percent));
+        item.value = Some(ItemValue::percentage(percent));
+        item
+    }
+
+    #[test]
+    fn test_attention_is_announced_once() {
+        let announcer = Announcer::new(&Config::default());
+        let calm = ModuleItem::new("tray", "chat", "Chat");
+        let mut alerting = calm.clone();
+        alerting.status = ItemStatus::NeedsAttention;
+
+        assert_eq!(
+            announcer.message(Some(&calm), &alerting).as_deref(),
+            Some("Chat needs attention")
+        );
+        assert_eq!(
+            announcer.message(None, &alerting).as_deref(),
+            Some("Chat needs attention")
+        );
+        assert_eq!(announcer.message(Some(&alerting), &alerting), None);
+        assert_eq!(announcer.message(Some(&alerting), &calm), None);
+    }
+
+    #[test]
+    fn test_battery_thresholds_only_downwards() {
+        let announcer = Announcer::new(&Config::default());
+
+        assert_eq!(
+            announcer.message(Some(&battery(21.0)), &battery(20.0)).as_deref(),
+            Some("Battery low, 20%")
+        );
+        assert_eq!(
+            announcer.message(Some(&battery(25.0)), &battery(9.0)).as_deref(),
+            Some("Battery critical, 9%")
+        );
+        assert_eq!(announcer.message(Some(&battery(20.0)), &battery(19.0)), None);
+        assert_eq!(announcer.message(Some(&battery(9.0)), &battery(21.0)), None);
+        assert_eq!(announcer.message(None, &battery(5.0)), None);
+    }
+
+    #[test]
+    fn test_label_changes_of_listed_modules() {
+        let mut config = Config::default();
+        config.client.announcements.label_modules = vec!["scripts".to_string()];
+        let announcer = Announcer::new(&config);
+
+        let before = ModuleItem::new("scripts", "vpn", "VPN off");
+        let after = ModuleItem::new("scripts", "vpn", "VPN on");
+        assert_eq!(announcer.message(Some(&before), &after).as_deref(), Some("VPN on"));
+        assert_eq!(announcer.message(Some(&after), &after), None);
+        assert_eq!(
+            announcer.message(Some(&before), &ModuleItem::new("scripts", "vpn", "")),
+            None
+        );
+
+        let cpu = ModuleItem::new("system", "cpu", "CPU 10%");
+        let busy = ModuleItem::new("system", "cpu", "CPU 90%");
+        assert_eq!(announcer.message(Some(&cpu), &busy), None);
+    }
+}
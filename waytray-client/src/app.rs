@@ -1,4 +1,9 @@
 //! GTK4 Application setup
+//!
+//! The application is single-instance: running `waytray` again forwards its
+//! command line to the running client, which toggles the window or focuses
+//! an item. The same commands are exported as application actions
+//! (`toggle`, `focus-item` and `open-menu`) on the session bus.
 
 use gtk4::prelude::*;
 use gtk4::{gio, glib};
@@ -14,19 +19,71 @@ impl WayTrayApp {
     pub fn new() -> Self {
         let app = gtk4::Application::builder()
             .application_id("org.waytray.Client")
-            .flags(gio::ApplicationFlags::FLAGS_NONE)
+            .flags(gio::ApplicationFlags::HANDLES_COMMAND_LINE)
             .build();
 
-        app.connect_activate(|app| {
-            // Check if we already have a window
-            if let Some(window) = app.active_window() {
-                window.present();
-                return;
+        app.add_main_option(
+            "focus",
+            glib::Char::from(b'f'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Focus the item with this id, e.g. pipewire:volume",
+            Some("ITEM_ID"),
+        );
+        app.add_main_option(
+            "menu",
+            glib::Char::from(b'm'),
+            glib::OptionFlags::NONE,
+            glib::OptionArg::String,
+            "Open the context menu of the item with this id",
+            Some("ITEM_ID"),
+        );
+
+        let toggle = gio::ActionEntry::builder("toggle")
+            .activate(|app: &gtk4::Application, _, _| {
+                let (window, created) = main_window(app);
+                if created {
+                    window.present();
+                } else {
+                    window.toggle();
+                }
+            })
+            .build();
+        let focus_item = gio::ActionEntry::builder("focus-item")
+            .parameter_type(Some(glib::VariantTy::STRING))
+            .activate(|app: &gtk4::Application, _, parameter| {
+                if let Some(item_id) = parameter.and_then(|p| p.get::<String>()) {
+                    main_window(app).0.focus_item(&item_id, false);
+                }
+            })
+            .build();
+        let open_menu = gio::ActionEntry::builder("open-menu")
+            .parameter_type(Some(glib::VariantTy::STRING))
+            .activate(|app: &gtk4::Application, _, parameter| {
+                if let Some(item_id) = parameter.and_then(|p| p.get::<String>()) {
+                    main_window(app).0.focus_item(&item_id, true);
+                }
+            })
+            .build();
+        app.add_action_entries([toggle, focus_item, open_menu]);
+
+        // Runs in the primary instance, also for later invocations
+        app.connect_command_line(|app, command_line| {
+            let options = command_line.options_dict();
+            let option = |name: &str| options.lookup::<String>(name).ok().flatten();
+
+            if let Some(item_id) = option("menu") {
+                app.activate_action("open-menu", Some(&item_id.to_variant()));
+            } else if let Some(item_id) = option("focus") {
+                app.activate_action("focus-item", Some(&item_id.to_variant()));
+            } else {
+                app.activate_action("toggle", None);
             }
+            glib::ExitCode::SUCCESS
+        });
 
-            // Create the main window
-            let window = WayTrayWindow::new(app);
-            window.present();
+        app.connect_activate(|app| {
+            main_window(app).0.present();
         });
 
         Self { app }
@@ -42,3 +99,15 @@ impl Default for WayTrayApp {
         Self::new()
     }
 }
+
+/// The client window, created if there is none yet. Returns whether it was created.
+fn main_window(app: &gtk4::Application) -> (WayTrayWindow, bool) {
+    let existing = app
+        .windows()
+        .into_iter()
+        .find_map(|window| window.downcast::<WayTrayWindow>().ok());
+    match existing {
+        Some(window) => (window, false),
+        None => (WayTrayWindow::new(app), true),
+    }
+}
@@ -11,7 +11,7 @@ use zbus::Connection;
     default_service = "org.waytray.Daemon",
     default_path = "/org/waytray/Daemon"
 )]
-trait WayTrayDaemon {
+pub trait WayTrayDaemon {
     // =========================================================================
     // Legacy API (backwards compatible)
     // =========================================================================
@@ -63,6 +63,9 @@ trait WayTrayDaemon {
         y: i32,
     ) -> zbus::Result<()>;
 
+    /// Reload the daemon config file
+    fn reload_config(&self) -> zbus::Result<()>;
+
     /// Signal emitted when module items change
     #[zbus(signal)]
     fn module_items_changed(&self, module_name: String) -> zbus::Result<()>;
@@ -74,8 +77,15 @@ trait WayTrayDaemon {
     /// Get menu items for a tray item via DBusMenu
     fn get_item_menu(&self, item_id: &str) -> zbus::Result<Vec<MenuItemDto>>;
 
+    /// Get the menu of an item that is already open, without AboutToShow
+    fn get_current_item_menu(&self, item_id: &str) -> zbus::Result<Vec<MenuItemDto>>;
+
     /// Activate a menu item by sending a "clicked" event
     fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> zbus::Result<()>;
+
+    /// Signal emitted when the menu of an item changes
+    #[zbus(signal)]
+    fn menu_changed(&self, item_id: String) -> zbus::Result<()>;
 }
 
 /// Client for communicating with the WayTray daemon
@@ -186,9 +196,24 @@ impl DaemonClient {
         Ok(items)
     }
 
+    /// Get the menu of an item that is already open, e.g. after it changed
+    pub async fn get_current_item_menu(&self, item_id: &str) -> anyhow::Result<Vec<MenuItemDto>> {
+        let items = self.proxy.get_current_item_menu(item_id).await?;
+        Ok(items)
+    }
+
     /// Activate a menu item by sending a "clicked" event
     pub async fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> anyhow::Result<()> {
         self.proxy.activate_menu_item(item_id, menu_item_id).await?;
         Ok(())
     }
+
+    /// Subscribe to changes of the menu of one item
+    pub async fn menu_changes(&self, item_id: &str) -> anyhow::Result<MenuChangedStream> {
+        let stream = self
+            .proxy
+            .receive_menu_changed_with_args(&[(0, item_id)])
+            .await?;
+        Ok(stream)
+    }
 }
@@ -3,6 +3,7 @@
 //! GTK4 client for displaying items from the WayTray daemon in an accessible window.
 //! Uses a horizontal FlowBox for left/right arrow navigation like KDE's system tray.
 
+mod announcer;
 mod app;
 mod daemon_proxy;
 mod menu_popover;
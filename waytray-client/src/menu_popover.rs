@@ -6,7 +6,7 @@
 use gtk4::prelude::*;
 use gtk4::subclass::prelude::*;
 use gtk4::{gdk, glib};
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::sync::Arc;
 
 use waytray_daemon::dbus_service::MenuItemDto;
@@ -25,6 +25,14 @@ mod imp {
         pub client: RefCell<Option<Arc<DaemonClient>>>,
         /// For submenus, stores the parent button to return focus to on Left arrow
         pub parent_button: RefCell<Option<gtk4::Button>>,
+        /// ID of the menu item whose children are shown (0 for the top level)
+        pub root_id: Cell<i32>,
+        /// Buttons by menu item ID, used to restore focus after a refresh
+        pub buttons: RefCell<Vec<(i32, gtk4::Button)>>,
+        /// Currently open submenu, if any
+        pub open_submenu: RefCell<Option<super::MenuPopover>>,
+        /// A menu change arrived while a submenu was open
+        pub refresh_pending: Cell<bool>,
     }
 
     #[glib::object_subclass]
@@ -39,6 +47,10 @@ mod imp {
                 item_id: RefCell::new(String::new()),
                 client: RefCell::new(None),
                 parent_button: RefCell::new(None),
+                root_id: Cell::new(0),
+                buttons: RefCell::new(Vec::new()),
+                open_submenu: RefCell::new(None),
+                refresh_pending: Cell::new(false),
             }
         }
     }
@@ -113,18 +125,34 @@ impl MenuPopover {
         *self.imp().parent_button.borrow_mut() = Some(button.clone());
     }
 
+    /// Set the menu item whose children this popover shows (for submenus)
+    pub fn set_root_id(&self, root_id: i32) {
+        self.imp().root_id.set(root_id);
+    }
+
     /// Populate the menu with items (flat list with parent_id relationships)
     pub fn set_menu_items(&self, items: &[MenuItemDto]) {
+        self.rebuild(items);
+
+        // Focus the first item when menu is shown
+        if let Some(first) = self.imp().content_box.first_child() {
+            first.grab_focus();
+        }
+    }
+
+    /// Rebuild the menu content from a flat item list
+    fn rebuild(&self, items: &[MenuItemDto]) {
         let imp = self.imp();
 
         // Clear existing children
         while let Some(child) = imp.content_box.first_child() {
             imp.content_box.remove(&child);
         }
+        imp.buttons.borrow_mut().clear();
 
-        // Only show top-level items (parent_id == 0)
-        // Submenus could be expanded inline or as nested popovers
-        let top_level: Vec<_> = items.iter().filter(|i| i.parent_id == 0).collect();
+        // Only show items directly below our root; submenus are nested popovers
+        let root_id = imp.root_id.get();
+        let top_level: Vec<_> = items.iter().filter(|i| i.parent_id == root_id).collect();
 
         if top_level.is_empty() {
             let label = gtk4::Label::new(Some("No menu items"));
@@ -140,10 +168,118 @@ impl MenuPopover {
             let widget = self.create_menu_item_widget(item, items);
             imp.content_box.append(&widget);
         }
+    }
 
-        // Focus the first item when menu is shown
-        if let Some(first) = imp.content_box.first_child() {
-            first.grab_focus();
+    /// Refresh the menu whenever the daemon reports a change, until closed
+    pub fn watch_for_changes(&self) {
+        let imp = self.imp();
+        let Some(client) = imp.client.borrow().clone() else {
+            return;
+        };
+        let item_id = imp.item_id.borrow().clone();
+
+        // Closing the channel wakes the listener so it stops with the popover
+        let (stop_tx, stop_rx) = async_channel::bounded::<()>(1);
+        self.connect_closed(move |_| {
+            stop_tx.close();
+        });
+
+        let popover = self.downgrade();
+        glib::spawn_future_local(async move {
+            use futures::StreamExt;
+
+            // One subscription for the popover's lifetime, so changes that
+            // arrive while a refresh is running are queued rather than lost
+            let mut changes = match client.menu_changes(&item_id).await {
+                Ok(changes) => changes,
+                Err(e) => {
+                    tracing::debug!("Failed to watch menu of {}: {}", item_id, e);
+                    return;
+                }
+            };
+
+            loop {
+                let changed = changes.next();
+                let stopped = stop_rx.recv();
+                futures::pin_mut!(stopped);
+
+                match futures::future::select(changed, stopped).await {
+                    futures::future::Either::Left((Some(_), _)) => {
+                        let Some(popover) = popover.upgrade() else {
+                            break;
+                        };
+                        popover.refresh().await;
+                    }
+                    futures::future::Either::Left((None, _)) => {
+                        tracing::debug!("Stopped watching menu of {}: signal stream ended", item_id);
+                        break;
+                    }
+                    futures::future::Either::Right(_) => break,
+                }
+            }
+        });
+    }
+
+    /// Refetch the menu and rebuild it, keeping focus on the same entry
+    async fn refresh(&self) {
+        let imp = self.imp();
+
+        // Rebuilding would close an open submenu; wait until it is dismissed
+        let submenu_open = imp
+            .open_submenu
+            .borrow()
+            .as_ref()
+            .map(|submenu| submenu.is_visible())
+            .unwrap_or(false);
+        if submenu_open {
+            imp.refresh_pending.set(true);
+            return;
+        }
+        imp.refresh_pending.set(false);
+
+        let Some(client) = imp.client.borrow().clone() else {
+            return;
+        };
+        let item_id = imp.item_id.borrow().clone();
+
+        let items = match client.get_current_item_menu(&item_id).await {
+            Ok(items) => items,
+            Err(e) => {
+                tracing::debug!("Failed to refresh menu of {}: {}", item_id, e);
+                return;
+            }
+        };
+
+        if !self.is_visible() {
+            return;
+        }
+
+        // Close submenus whose parent entry went away
+        let root_id = imp.root_id.get();
+        if root_id != 0 && !items.iter().any(|i| i.id == root_id) {
+            self.popdown();
+            return;
+        }
+
+        let focused_id = self.get_focused_button().and_then(|button| {
+            imp.buttons
+                .borrow()
+                .iter()
+                .find(|(_, b)| *b == button)
+                .map(|(id, _)| *id)
+        });
+
+        self.rebuild(&items);
+
+        let focus_target = focused_id.and_then(|id| {
+            imp.buttons
+                .borrow()
+                .iter()
+                .find(|(button_id, _)| *button_id == id)
+                .map(|(_, button)| button.clone().upcast::<gtk4::Widget>())
+        });
+        if let Some(widget) = focus_target.or_else(|| imp.content_box.first_child()) {
+            widget.grab_focus();
         }
     }
 
@@ -234,16 +370,13 @@ impl MenuPopover {
         let has_submenu = item.has_submenu;
         let popover = self.clone();
 
-        // Get submenu items for this item
-        let submenu_items: Vec<MenuItemDto> = if has_submenu {
-            all_items
-                .iter()
-                .filter(|i| i.parent_id == menu_item_id)
-                .cloned()
-                .collect()
-        } else {
-            Vec::new()
-        };
+        // Keep the full list so the submenu can show its own children and refresh
+        let submenu_items: Vec<MenuItemDto> =
+            if has_submenu && all_items.iter().any(|i| i.parent_id == menu_item_id) {
+                all_items.to_vec()
+            } else {
+                Vec::new()
+            };
 
         button.connect_clicked(move |btn| {
             if has_submenu && !submenu_items.is_empty() {
@@ -260,12 +393,8 @@ impl MenuPopover {
                         .expect("Client not set"),
                 );
                 submenu_popover.set_item_id(&popover.imp().item_id.borrow());
-
-                // Build flat list for submenu (items where parent_id matches)
-                let mut submenu_flat = submenu_items.clone();
-                // Include nested items too
-                submenu_flat.iter_mut().for_each(|i| i.parent_id = 0);
-                submenu_popover.set_menu_items(&submenu_flat);
+                submenu_popover.set_root_id(menu_item_id);
+                submenu_popover.set_menu_items(&submenu_items);
 
                 // Forward activation signal
                 let parent_popover = popover.clone();
@@ -275,6 +404,20 @@ impl MenuPopover {
                     None
                 });
 
+                // Apply changes that arrived while the submenu was open
+                let parent_popover = popover.clone();
+                submenu_popover.connect_closed(move |_| {
+                    parent_popover.imp().open_submenu.replace(None);
+                    if parent_popover.imp().refresh_pending.get() {
+                        let parent_popover = parent_popover.clone();
+                        glib::spawn_future_local(async move {
+                            parent_popover.refresh().await;
+                        });
+                    }
+                });
+
+                popover.imp().open_submenu.replace(Some(submenu_popover.clone()));
+                submenu_popover.watch_for_changes();
                 submenu_popover.popup();
             } else {
                 // Activate this menu item
@@ -282,6 +425,8 @@ impl MenuPopover {
             }
         });
 
+        self.imp().buttons.borrow_mut().push((menu_item_id, button.clone()));
+
         button.upcast()
     }
 
@@ -7,7 +7,7 @@ use gtk4::{gdk, glib};
 use std::cell::RefCell;
 use std::sync::OnceLock;
 
-use waytray_daemon::ModuleItem;
+use waytray_daemon::{ItemStatus, ModuleItem};
 
 mod imp {
     use super::*;
@@ -18,7 +18,12 @@ mod imp {
     pub struct ModuleItemWidget {
         pub item_data: RefCell<Option<ModuleItem>>,
         pub icon: gtk4::Image,
+        /// Small badge drawn over the bottom-right corner of the icon
+        pub overlay_icon: gtk4::Image,
+        pub icon_overlay: gtk4::Overlay,
         pub label: gtk4::Label,
+        /// Shows the item's value when it has a range (volume, battery, CPU...)
+        pub level_bar: gtk4::LevelBar,
     }
 
     #[glib::object_subclass]
@@ -27,11 +32,23 @@ mod imp {
         type Type = super::ModuleItemWidget;
         type ParentType = gtk4::Box;
 
+        fn class_init(klass: &mut Self::Class) {
+            // Adjustable items override this with the slider role at construction
+            klass.set_accessible_role(gtk4::AccessibleRole::Button);
+        }
+
         fn new() -> Self {
             Self {
                 item_data: RefCell::new(None),
                 icon: gtk4::Image::new(),
+                overlay_icon: gtk4::Image::new(),
+                icon_overlay: gtk4::Overlay::new(),
                 label: gtk4::Label::new(None),
+                // The value is exposed on the item itself, so keep the bar
+                // out of the accessibility tree
+                level_bar: gtk4::LevelBar::builder()
+                    .accessible_role(gtk4::AccessibleRole::Presentation)
+                    .build(),
             }
         }
     }
@@ -45,6 +62,9 @@ mod imp {
                     Signal::builder("context-menu-item").build(),
                     Signal::builder("scroll-up").build(),
                     Signal::builder("scroll-down").build(),
+                    Signal::builder("adjust-item")
+                        .param_types([String::static_type()])
+                        .build(),
                 ]
             })
         }
@@ -65,19 +85,33 @@ mod imp {
             // Configure the icon
             self.icon.set_pixel_size(24);
 
+            // Configure the overlay badge
+            self.overlay_icon.set_pixel_size(12);
+            self.overlay_icon.set_halign(gtk4::Align::End);
+            self.overlay_icon.set_valign(gtk4::Align::End);
+            self.overlay_icon.set_visible(false);
+            self.icon_overlay.set_child(Some(&self.icon));
+            self.icon_overlay.add_overlay(&self.overlay_icon);
+
             // Configure the label
             self.label.set_xalign(0.0);
 
+            // Configure the level bar
+            self.level_bar.set_mode(gtk4::LevelBarMode::Continuous);
+            self.level_bar.set_width_request(64);
+            self.level_bar.set_valign(gtk4::Align::Center);
+            self.level_bar.set_focusable(false);
+            self.level_bar.set_can_target(false);
+            self.level_bar.set_visible(false);
+
             // Add children directly to this box
-            obj.append(&self.icon);
+            obj.append(&self.icon_overlay);
             obj.append(&self.label);
+            obj.append(&self.level_bar);
 
             // Make focusable
             obj.set_focusable(true);
 
-            // Set accessible role
-            obj.set_accessible_role(gtk4::AccessibleRole::Button);
-
             // Set up key event controller
             let key_controller = gtk4::EventControllerKey::new();
             key_controller.connect_key_pressed(glib::clone!(
@@ -107,6 +141,51 @@ impl ModuleItemWidget {
         glib::Object::new()
     }
 
+    /// Create a widget showing an item, with the slider role if it is adjustable
+    ///
+    /// The accessible role can't change after construction, so an item that
+    /// becomes (or stops being) adjustable needs a new widget.
+    pub fn for_item(item: &ModuleItem) -> Self {
+        let role = if is_adjustable(item) {
+            gtk4::AccessibleRole::Slider
+        } else {
+            gtk4::AccessibleRole::Button
+        };
+        let widget: Self = glib::Object::builder()
+            .property("accessible-role", role)
+            .build();
+        widget.set_item(item.clone());
+        widget
+    }
+
+    /// Use smaller spacing and icons and shorten long labels, for the panel
+    pub fn set_compact(&self, compact: bool) {
+        let imp = self.imp();
+        let (margin_x, margin_y) = if compact { (4, 2) } else { (8, 6) };
+        self.set_spacing(if compact { 4 } else { 8 });
+        self.set_margin_start(margin_x);
+        self.set_margin_end(margin_x);
+        self.set_margin_top(margin_y);
+        self.set_margin_bottom(margin_y);
+        imp.icon.set_pixel_size(if compact { 16 } else { 24 });
+        imp.overlay_icon.set_pixel_size(if compact { 8 } else { 12 });
+        imp.level_bar.set_width_request(if compact { 32 } else { 64 });
+
+        // The full label stays available as the accessible name and tooltip
+        if compact {
+            imp.label.set_ellipsize(gtk4::pango::EllipsizeMode::End);
+            imp.label.set_max_width_chars(16);
+        } else {
+            imp.label.set_ellipsize(gtk4::pango::EllipsizeMode::None);
+            imp.label.set_max_width_chars(-1);
+        }
+    }
+
+    /// Whether this widget was created with the slider role
+    pub fn is_adjustable(&self) -> bool {
+        self.accessible_role() == gtk4::AccessibleRole::Slider
+    }
+
     /// Set the module item data
     pub fn set_item(&self, item: ModuleItem) {
         let imp = self.imp();
@@ -118,11 +197,14 @@ impl ModuleItemWidget {
         self.update_icon(&item);
 
         // Set accessible properties
-        self.update_property(&[gtk4::accessible::Property::Label(&item.label)]);
+        self.update_property(&[gtk4::accessible::Property::Label(&accessible_label(&item))]);
+        self.update_value(&item);
 
         if let Some(tooltip) = &item.tooltip {
             self.set_tooltip_text(Some(tooltip));
-            self.update_property(&[gtk4::accessible::Property::Description(tooltip)]);
+        }
+        if let Some(description) = accessible_description(&item) {
+            self.update_property(&[gtk4::accessible::Property::Description(&description)]);
         }
 
         // Store the item data
@@ -143,6 +225,10 @@ impl ModuleItemWidget {
                         || current.icon_name != item.icon_name
                         || current.tooltip != item.tooltip
                         || current.icon_pixmap != item.icon_pixmap
+                        || current.attention_icon != item.attention_icon
+                        || current.overlay_icon != item.overlay_icon
+                        || current.status != item.status
+                        || current.value != item.value
                 }
                 None => true,
             }
@@ -150,14 +236,20 @@ impl ModuleItemWidget {
 
         if needs_update {
             // Only update the visual elements, not accessible properties
-            // unless the label actually changed
-            let label_changed = {
+            // unless the label or attention state actually changed
+            let (label_changed, value_changed) = {
                 let current = imp.item_data.borrow();
-                current.as_ref().map(|c| &c.label) != Some(&item.label)
+                (
+                    current.as_ref().map(accessible_label) != Some(accessible_label(item)),
+                    current.as_ref().map(|c| &c.value) != Some(&item.value),
+                )
             };
 
             imp.label.set_text(&item.label);
             self.update_icon(item);
+            if value_changed {
+                self.update_value(item);
+            }
 
             if let Some(tooltip) = &item.tooltip {
                 self.set_tooltip_text(Some(tooltip));
@@ -166,9 +258,9 @@ impl ModuleItemWidget {
             // Only update accessible properties if label changed
             // This prevents Orca from re-announcing unchanged items
             if label_changed {
-                self.update_property(&[gtk4::accessible::Property::Label(&item.label)]);
-                if let Some(tooltip) = &item.tooltip {
-                    self.update_property(&[gtk4::accessible::Property::Description(tooltip)]);
+                self.update_property(&[gtk4::accessible::Property::Label(&accessible_label(item))]);
+                if let Some(description) = accessible_description(item) {
+                    self.update_property(&[gtk4::accessible::Property::Description(&description)]);
                 }
             }
 
@@ -179,32 +271,115 @@ impl ModuleItemWidget {
         }
     }
 
+    /// Update the level bar and the accessible value properties
+    fn update_value(&self, item: &ModuleItem) {
+        let imp = self.imp();
+
+        match &item.value {
+            Some(value) => {
+                self.update_property(&[gtk4::accessible::Property::ValueText(&value.spoken())]);
+                if value.fraction().is_some() {
+                    imp.level_bar.set_min_value(value.min);
+                    imp.level_bar.set_max_value(value.max);
+                    imp.level_bar.set_value(value.value.clamp(value.min, value.max));
+                    imp.level_bar.set_visible(true);
+                    self.update_property(&[
+                        gtk4::accessible::Property::ValueMin(value.min),
+                        gtk4::accessible::Property::ValueMax(value.max),
+                        gtk4::accessible::Property::ValueNow(value.value),
+                    ]);
+                } else {
+                    // Rates without a known peak have no meaningful bar
+                    imp.level_bar.set_visible(false);
+                    self.update_property(&[gtk4::accessible::Property::ValueNow(value.value)]);
+                    self.reset_property(gtk4::AccessibleProperty::ValueMin);
+                    self.reset_property(gtk4::AccessibleProperty::ValueMax);
+                }
+            }
+            None => {
+                imp.level_bar.set_visible(false);
+                for property in [
+                    gtk4::AccessibleProperty::ValueNow,
+                    gtk4::AccessibleProperty::ValueMin,
+                    gtk4::AccessibleProperty::ValueMax,
+                    gtk4::AccessibleProperty::ValueText,
+                ] {
+                    self.reset_property(property);
+                }
+            }
+        }
+    }
+
     /// Update the icon from item data
     fn update_icon(&self, item: &ModuleItem) {
         let imp = self.imp();
 
+        // The attention icon replaces the normal icon while it is set
+        let shown = match &item.attention_icon {
+            Some(attention) => self.set_image(
+                &imp.icon,
+                attention.name.as_deref(),
+                attention.pixmap.as_deref(),
+                attention.width,
+                attention.height,
+            ),
+            None => false,
+        };
+
+        if !shown
+            && !self.set_image(
+                &imp.icon,
+                item.icon_name.as_deref(),
+                item.icon_pixmap.as_deref(),
+                item.icon_width,
+                item.icon_height,
+            )
+        {
+            // Default fallback icon
+            imp.icon.set_icon_name(Some("application-x-executable"));
+        }
+
+        let overlay_shown = match &item.overlay_icon {
+            Some(overlay) => self.set_image(
+                &imp.overlay_icon,
+                overlay.name.as_deref(),
+                overlay.pixmap.as_deref(),
+                overlay.width,
+                overlay.height,
+            ),
+            None => false,
+        };
+        imp.overlay_icon.set_visible(overlay_shown);
+    }
+
+    /// Show an icon by theme name or ARGB32 pixmap. Returns false if neither is usable.
+    fn set_image(
+        &self,
+        image: &gtk4::Image,
+        icon_name: Option<&str>,
+        pixmap_data: Option<&[u8]>,
+        width: u32,
+        height: u32,
+    ) -> bool {
         // Prefer icon name (from theme)
-        if let Some(icon_name) = &item.icon_name {
+        if let Some(icon_name) = icon_name {
             if !icon_name.is_empty() {
-                imp.icon.set_icon_name(Some(icon_name));
-                return;
+                image.set_icon_name(Some(icon_name));
+                return true;
             }
         }
 
         // Fall back to pixmap data
-        if let Some(pixmap_data) = &item.icon_pixmap {
-            if !pixmap_data.is_empty() && item.icon_width > 0 && item.icon_height > 0 {
-                if let Some(texture) =
-                    self.create_texture_from_argb32(pixmap_data, item.icon_width, item.icon_height)
-                {
-                    imp.icon.set_paintable(Some(&texture));
-                    return;
+        if let Some(pixmap_data) = pixmap_data {
+            if !pixmap_data.is_empty() && width > 0 && height > 0 {
+                if let Some(texture) = self.create_texture_from_argb32(pixmap_data, width, height) {
+                    image.set_paintable(Some(&texture));
+                    return true;
                 }
             }
         }
 
-        // Default fallback icon
-        imp.icon.set_icon_name(Some("application-x-executable"));
+        false
     }
 
     /// Create a GDK texture from ARGB32 pixmap data
@@ -253,6 +428,44 @@ impl ModuleItemWidget {
         Some(texture.upcast())
     }
 
+    /// Get a copy of the item currently shown
+    pub fn item(&self) -> Option<ModuleItem> {
+        self.imp().item_data.borrow().clone()
+    }
+
+    /// Name used for type-ahead: the label, or the first tooltip line for
+    /// items without one
+    pub fn display_name(&self) -> String {
+        let item_data = self.imp().item_data.borrow();
+        let Some(item) = item_data.as_ref() else {
+            return String::new();
+        };
+        if !item.label.is_empty() {
+            return item.label.clone();
+        }
+        item.tooltip
+            .as_deref()
+            .and_then(|tooltip| tooltip.lines().next())
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// Whether the label, module name or tooltip contains the lowercase filter text
+    pub fn matches_filter(&self, filter: &str) -> bool {
+        if filter.is_empty() {
+            return true;
+        }
+        let item_data = self.imp().item_data.borrow();
+        let Some(item) = item_data.as_ref() else {
+            return false;
+        };
+        let matches = [Some(item.label.as_str()), Some(item.module.as_str()), item.tooltip.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|text| text.to_lowercase().contains(filter));
+        matches
+    }
+
     /// Get the item ID
     pub fn item_id(&self) -> Option<String> {
         self.imp()
@@ -304,9 +517,11 @@ impl ModuleItemWidget {
             }
         }
 
+        // Page steps share the suffix but are bound to their own keys
+        let page_suffix = format!("_page{}", suffix);
         item.actions
             .iter()
-            .find(|a| a.id.ends_with(suffix))
+            .find(|a| a.id.ends_with(suffix) && !a.id.ends_with(&page_suffix))
             .map(|a| a.id.clone())
     }
 
@@ -318,6 +533,24 @@ impl ModuleItemWidget {
         self.action_id_by_preference(&["mic_volume_down", "volume_down"], "_down")
     }
 
+    /// Action sharing the step-up action's prefix, e.g. `volume_max` for `volume_up`
+    pub fn adjust_action_id(&self, suffix: &str) -> Option<String> {
+        let step_up = self.step_up_action_id()?;
+        let action_id = format!("{}_{}", step_up.strip_suffix("_up")?, suffix);
+        self.has_action(&action_id).then_some(action_id)
+    }
+
+    /// Emit adjust-item for the given adjust action, if the item has it
+    fn emit_adjust(&self, suffix: &str) -> glib::Propagation {
+        match self.adjust_action_id(suffix) {
+            Some(action_id) => {
+                self.emit_by_name::<()>("adjust-item", &[&action_id]);
+                glib::Propagation::Stop
+            }
+            None => glib::Propagation::Proceed,
+        }
+    }
+
     /// Handle key press events
     fn handle_key_press(&self, keyval: gdk::Key, state: gdk::ModifierType) -> glib::Propagation {
         match keyval {
@@ -357,6 +590,17 @@ impl ModuleItemWidget {
                 }
             }
 
+            // Page Up/Down: large steps, Home/End: minimum and maximum
+            gdk::Key::Page_Up | gdk::Key::KP_Page_Up => self.emit_adjust("page_up"),
+            gdk::Key::Page_Down | gdk::Key::KP_Page_Down => self.emit_adjust("page_down"),
+            // Ctrl+Home/End are left to the window, which moves to the first/last item
+            gdk::Key::Home | gdk::Key::KP_Home if !state.contains(gdk::ModifierType::CONTROL_MASK) => {
+                self.emit_adjust("min")
+            }
+            gdk::Key::End | gdk::Key::KP_End if !state.contains(gdk::ModifierType::CONTROL_MASK) => {
+                self.emit_adjust("max")
+            }
+
             _ => glib::Propagation::Proceed,
         }
     }
@@ -396,6 +640,42 @@ impl ModuleItemWidget {
             None
         })
     }
+
+    /// Connect to the adjust-item signal, which carries the action id to invoke
+    pub fn connect_adjust_item<F: Fn(&Self, &str) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_local("adjust-item", false, move |values| {
+            let obj = values[0].get::<ModuleItemWidget>().unwrap();
+            let action_id = values[1].get::<String>().unwrap();
+            f(&obj, &action_id);
+            None
+        })
+    }
+}
+
+/// Whether an item has a ranged value and step actions, and so acts as a slider
+pub fn is_adjustable(item: &ModuleItem) -> bool {
+    let has_action = |suffix: &str| item.actions.iter().any(|a| a.id.ends_with(suffix));
+    item.value.as_ref().and_then(|v| v.fraction()).is_some()
+        && has_action("_up")
+        && has_action("_down")
+}
+
+/// Accessible label for an item, mentioning when it needs attention
+fn accessible_label(item: &ModuleItem) -> String {
+    if item.status == ItemStatus::NeedsAttention {
+        format!("{}, needs attention", item.label)
+    } else {
+        item.label.clone()
+    }
+}
+
+/// Accessible description: the tooltip, plus the status when the item is passive
+fn accessible_description(item: &ModuleItem) -> Option<String> {
+    match (item.status, &item.tooltip) {
+        (ItemStatus::Passive, Some(tooltip)) => Some(format!("{}\nStatus: passive", tooltip)),
+        (ItemStatus::Passive, None) => Some("Status: passive".to_string()),
+        (_, tooltip) => tooltip.clone(),
+    }
 }
 
 impl Default for ModuleItemWidget {
@@ -1,6 +1,8 @@
 //! Main window for the WayTray client
 //!
-//! Uses a horizontal FlowBox for left/right arrow navigation like KDE's system tray.
+//! Items are shown in a row (left/right arrow navigation like KDE's system tray),
+//! a column or a grid, as set in `[client.window]`. With `group_by_module` each
+//! module gets a labelled section of its own, laid out the same way.
 
 use gtk4::prelude::*;
 use gtk4::subclass::prelude::*;
@@ -9,11 +11,25 @@ use gtk4_layer_shell::{Edge, Layer, LayerShell};
 use std::cell::{Cell, RefCell};
 use std::sync::Arc;
 
+use crate::announcer::{Announcement, Announcer};
 use crate::daemon_proxy::DaemonClient;
 use crate::menu_popover::MenuPopover;
-use crate::module_item::ModuleItemWidget;
+use crate::module_item::{is_adjustable, ModuleItemWidget};
+use waytray_daemon::config::{ClientMode, Config, ItemLayout, WindowConfig, WindowEdge, WindowLayer};
 use waytray_daemon::ModuleItem;
 
+/// A labelled section holding the items of one module
+#[derive(Clone)]
+pub struct ModuleGroup {
+    module: String,
+    /// The section: a heading above the items box
+    container: gtk4::Box,
+    items_box: gtk4::Box,
+}
+
+/// Pause after which typed letters start a new type-ahead search
+const TYPE_AHEAD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(1);
+
 mod imp {
     use super::*;
     use gtk4::subclass::application_window::ApplicationWindowImpl;
@@ -21,10 +37,31 @@ mod imp {
     use gtk4::subclass::window::WindowImpl;
 
     pub struct WayTrayWindow {
-        /// Horizontal box containing the items (replaces FlowBox for better a11y)
+        /// Horizontal box containing the items (replaces FlowBox for better a11y),
+        /// or the module groups when grouping
         pub items_box: gtk4::Box,
+        /// Module sections in display order, empty unless grouping by module
+        pub groups: RefCell<Vec<ModuleGroup>>,
         pub scrolled_window: gtk4::ScrolledWindow,
         pub status_label: gtk4::Label,
+        /// Shows announcements when GTK has no announce API (before 4.14)
+        #[cfg(not(feature = "v4_14"))]
+        pub announce_label: gtk4::Label,
+        #[cfg(not(feature = "v4_14"))]
+        pub announce_timeout: RefCell<Option<glib::SourceId>>,
+        pub announcer: RefCell<Option<Announcer>>,
+        pub window_config: RefCell<WindowConfig>,
+        /// Popup (closes on focus loss) or persistent panel
+        pub mode: Cell<ClientMode>,
+        /// Set once the first item list has been shown
+        pub items_loaded: Cell<bool>,
+        /// Item to focus (and whether to open its menu) once items are loaded
+        pub pending_focus: RefCell<Option<(String, bool)>>,
+        /// Narrows the shown items; revealed with Ctrl+F
+        pub filter_entry: gtk4::SearchEntry,
+        /// Letters typed for type-ahead navigation, and when the last one was typed
+        pub type_ahead: RefCell<String>,
+        pub type_ahead_time: Cell<Option<std::time::Instant>>,
         pub main_box: gtk4::Box,
         pub client: RefCell<Option<Arc<DaemonClient>>>,
         /// Track if window has ever been focused (for close-on-focus-loss behavior)
@@ -43,8 +80,23 @@ mod imp {
         fn default() -> Self {
             Self {
                 items_box: gtk4::Box::new(gtk4::Orientation::Horizontal, 0),
+                groups: RefCell::new(Vec::new()),
                 scrolled_window: gtk4::ScrolledWindow::new(),
                 status_label: gtk4::Label::new(Some("Connecting to daemon...")),
+                #[cfg(not(feature = "v4_14"))]
+                announce_label: gtk4::Label::builder()
+                    .accessible_role(gtk4::AccessibleRole::Status)
+                    .build(),
+                #[cfg(not(feature = "v4_14"))]
+                announce_timeout: RefCell::new(None),
+                announcer: RefCell::new(None),
+                window_config: RefCell::new(WindowConfig::default()),
+                mode: Cell::new(ClientMode::Popup),
+                items_loaded: Cell::new(false),
+                pending_focus: RefCell::new(None),
+                filter_entry: gtk4::SearchEntry::new(),
+                type_ahead: RefCell::new(String::new()),
+                type_ahead_time: Cell::new(None),
                 main_box: gtk4::Box::new(gtk4::Orientation::Vertical, 0),
                 client: RefCell::new(None),
                 has_been_focused: Cell::new(false),
@@ -69,43 +121,58 @@ mod imp {
 
             let obj = self.obj();
 
+            let config = Config::load().unwrap_or_else(|e| {
+                tracing::warn!("Failed to load config, using defaults: {}", e);
+                Config::default()
+            });
+            *self.announcer.borrow_mut() = Some(Announcer::new(&config));
+            let window_config = config.client.window;
+            self.mode.set(config.client.mode);
+
             // Initialize layer shell if available (must be called before window is realized)
             if gtk4_layer_shell::is_supported() {
-                tracing::info!("Layer shell supported, using overlay layer");
+                tracing::info!("Layer shell supported, using {:?} layer", window_config.layer);
                 obj.init_layer_shell();
-                obj.set_layer(Layer::Overlay);
-
-                // Anchor to top edge, stretch across full width
-                obj.set_anchor(Edge::Top, true);
-                obj.set_anchor(Edge::Left, true);
-                obj.set_anchor(Edge::Right, true);
-
-                // Allow keyboard interaction
-                obj.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::OnDemand);
+                obj.apply_layer_shell(&window_config, config.client.mode);
             } else {
                 tracing::info!("Layer shell not supported, using regular window");
             }
 
             // Configure window
             obj.set_title(Some("System Tray"));
-            obj.set_default_size(600, 60);
+            obj.set_default_size(window_config.width, window_config.height);
             obj.set_resizable(true);
 
-            // Configure the horizontal items box
-            self.items_box
-                .set_orientation(gtk4::Orientation::Horizontal);
-            self.items_box.set_spacing(4);
+            // Configure the items box; when grouping, it holds the sections
+            // side by side in the horizontal layout and stacked otherwise
+            if window_config.group_by_module {
+                self.items_box.set_spacing(12);
+                if window_config.layout != ItemLayout::Horizontal {
+                    self.items_box.set_orientation(gtk4::Orientation::Vertical);
+                }
+            } else {
+                configure_items_box(&self.items_box, window_config.layout);
 
-            // Set accessible role for the items container
-            self.items_box
-                .set_accessible_role(gtk4::AccessibleRole::List);
+                // Set accessible role for the items container
+                self.items_box
+                    .set_accessible_role(gtk4::AccessibleRole::List);
+            }
 
-            // Configure scrolled window for horizontal scrolling only
+            // Scroll along the direction the items are laid out in
             self.scrolled_window.set_child(Some(&self.items_box));
-            self.scrolled_window.set_vexpand(false);
-            self.scrolled_window.set_hexpand(true);
-            self.scrolled_window
-                .set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Never);
+            if window_config.layout == ItemLayout::Horizontal {
+                self.scrolled_window.set_vexpand(false);
+                self.scrolled_window.set_hexpand(true);
+                self.scrolled_window
+                    .set_policy(gtk4::PolicyType::Automatic, gtk4::PolicyType::Never);
+            } else {
+                self.scrolled_window.set_vexpand(true);
+                self.scrolled_window.set_hexpand(true);
+                self.scrolled_window
+                    .set_policy(gtk4::PolicyType::Never, gtk4::PolicyType::Automatic);
+                self.scrolled_window.set_propagate_natural_height(true);
+            }
+            *self.window_config.borrow_mut() = window_config;
 
             // Configure status label
             self.status_label.set_margin_top(12);
@@ -113,9 +180,42 @@ mod imp {
             self.status_label.set_margin_start(12);
             self.status_label.set_margin_end(12);
 
+            // Configure the announcement label
+            #[cfg(not(feature = "v4_14"))]
+            {
+                self.announce_label.set_margin_top(4);
+                self.announce_label.set_margin_bottom(4);
+                self.announce_label.set_visible(false);
+            }
+
+            // Configure the filter entry
+            self.filter_entry.set_placeholder_text(Some("Filter items"));
+            self.filter_entry.set_margin_start(8);
+            self.filter_entry.set_margin_end(8);
+            self.filter_entry.set_margin_top(4);
+            self.filter_entry.set_visible(false);
+            self.filter_entry.connect_search_changed(glib::clone!(
+                #[weak]
+                obj,
+                move |_| obj.apply_filter()
+            ));
+            self.filter_entry.connect_activate(glib::clone!(
+                #[weak]
+                obj,
+                move |_| obj.focus_edge_item(false)
+            ));
+            self.filter_entry.connect_stop_search(glib::clone!(
+                #[weak]
+                obj,
+                move |_| obj.toggle_filter()
+            ));
+
             // Build layout
+            self.main_box.append(&self.filter_entry);
             self.main_box.append(&self.status_label);
             self.main_box.append(&self.scrolled_window);
+            #[cfg(not(feature = "v4_14"))]
+            self.main_box.append(&self.announce_label);
 
             obj.set_child(Some(&self.main_box));
 
@@ -126,7 +226,7 @@ mod imp {
                 obj,
                 #[upgrade_or]
                 glib::Propagation::Proceed,
-                move |_, keyval, _keycode, _state| {
+                move |_, keyval, _keycode, state| {
                     let imp = obj.imp();
                     // Record keyboard interaction to prevent spurious close-on-focus-loss
                     imp.last_keyboard_interaction
@@ -138,9 +238,19 @@ mod imp {
 
                     match keyval {
                         gdk::Key::Escape => {
-                            obj.close();
+                            if imp.mode.get() == ClientMode::Panel {
+                                obj.release_panel_focus();
+                            } else {
+                                obj.close();
+                            }
                             glib::Propagation::Stop
                         }
+                        gdk::Key::Left if state.contains(gdk::ModifierType::CONTROL_MASK) => {
+                            obj.navigate_groups(-1)
+                        }
+                        gdk::Key::Right if state.contains(gdk::ModifierType::CONTROL_MASK) => {
+                            obj.navigate_groups(1)
+                        }
                         gdk::Key::Left => {
                             obj.navigate_items(-1);
                             glib::Propagation::Stop
@@ -149,12 +259,68 @@ mod imp {
                             obj.navigate_items(1);
                             glib::Propagation::Stop
                         }
-                        _ => glib::Propagation::Proceed,
+                        // Items that don't use Up/Down themselves let them through
+                        gdk::Key::Up => obj.navigate_rows(-1),
+                        gdk::Key::Down => obj.navigate_rows(1),
+                        // Keys a menu or the filter entry didn't use must not
+                        // move focus to an item behind them
+                        gdk::Key::Home | gdk::Key::KP_Home if obj.items_own_keys() => {
+                            obj.focus_edge_item(false);
+                            glib::Propagation::Stop
+                        }
+                        gdk::Key::End | gdk::Key::KP_End if obj.items_own_keys() => {
+                            obj.focus_edge_item(true);
+                            glib::Propagation::Stop
+                        }
+                        gdk::Key::f if state.contains(gdk::ModifierType::CONTROL_MASK) => {
+                            obj.toggle_filter();
+                            glib::Propagation::Stop
+                        }
+                        _ => {
+                            // Printable characters jump to matching items
+                            let modifiers = gdk::ModifierType::CONTROL_MASK
+                                | gdk::ModifierType::ALT_MASK
+                                | gdk::ModifierType::SUPER_MASK;
+                            match keyval.to_unicode() {
+                                Some(c)
+                                    if !c.is_control()
+                                        && !c.is_whitespace()
+                                        && !state.intersects(modifiers)
+                                        && obj.items_own_keys() =>
+                                {
+                                    obj.type_ahead(c);
+                                    glib::Propagation::Stop
+                                }
+                                _ => glib::Propagation::Proceed,
+                            }
+                        }
                     }
                 }
             ));
             obj.add_controller(key_controller);
 
+            // Tab moves between module sections. It is caught before GTK's
+            // own focus handling, which would step through every item, but
+            // only while an item has focus: menus and the filter entry keep
+            // their usual Tab behaviour.
+            if obj.imp().window_config.borrow().group_by_module {
+                let tab_controller = gtk4::EventControllerKey::new();
+                tab_controller.set_propagation_phase(gtk4::PropagationPhase::Capture);
+                tab_controller.connect_key_pressed(glib::clone!(
+                    #[weak]
+                    obj,
+                    #[upgrade_or]
+                    glib::Propagation::Proceed,
+                    move |_, keyval, _keycode, _state| match keyval {
+                        _ if !obj.items_own_keys() => glib::Propagation::Proceed,
+                        gdk::Key::Tab | gdk::Key::KP_Tab => obj.navigate_groups(1),
+                        gdk::Key::ISO_Left_Tab => obj.navigate_groups(-1),
+                        _ => glib::Propagation::Proceed,
+                    }
+                ));
+                obj.add_controller(tab_controller);
+            }
+
             // Close window when focus leaves (but not when using menus/popovers)
             // Only close after the window has been focused at least once (handles
             // compositors like Niri where fullscreen windows prevent initial focus)
@@ -165,6 +331,12 @@ mod imp {
                     imp.has_been_focused.set(true);
                     // Cancel any pending close since we're active again
                     imp.pending_close_id.set(0);
+                } else if imp.mode.get() == ClientMode::Panel {
+                    // The panel stays open, but hands the keyboard back once
+                    // another window is focused (menus don't count)
+                    if imp.open_popover_count.get() == 0 {
+                        window.release_panel_focus();
+                    }
                 } else if imp.has_been_focused.get() && imp.open_popover_count.get() == 0 {
                     // Check if close is temporarily suppressed (popover just closed)
                     if let Some(until) = imp.suppress_close_until.get() {
@@ -219,6 +391,112 @@ impl WayTrayWindow {
         window
     }
 
+    /// Set the layer, anchors, margins and output of the layer-shell surface
+    fn apply_layer_shell(&self, config: &WindowConfig, mode: ClientMode) {
+        self.set_layer(match config.layer {
+            WindowLayer::Background => Layer::Background,
+            WindowLayer::Bottom => Layer::Bottom,
+            WindowLayer::Top => Layer::Top,
+            WindowLayer::Overlay => Layer::Overlay,
+        });
+
+        // Anchor to the edge, stretched along it
+        let anchors: &[Edge] = match config.edge {
+            WindowEdge::Top => &[Edge::Top, Edge::Left, Edge::Right],
+            WindowEdge::Bottom => &[Edge::Bottom, Edge::Left, Edge::Right],
+            WindowEdge::Left => &[Edge::Left, Edge::Top, Edge::Bottom],
+            WindowEdge::Right => &[Edge::Right, Edge::Top, Edge::Bottom],
+            WindowEdge::Center => &[],
+        };
+        for edge in anchors {
+            self.set_anchor(*edge, true);
+        }
+        self.set_margin(Edge::Top, config.margin_top);
+        self.set_margin(Edge::Bottom, config.margin_bottom);
+        self.set_margin(Edge::Left, config.margin_left);
+        self.set_margin(Edge::Right, config.margin_right);
+
+        if !config.output.is_empty() {
+            match find_monitor(&config.output) {
+                Some(monitor) => self.set_monitor(Some(&monitor)),
+                None => tracing::warn!("Output {} not found, using the default", config.output),
+            }
+        }
+
+        // A panel always reserves its space
+        let exclusive = config.exclusive_zone || mode == ClientMode::Panel;
+        if exclusive && config.edge != WindowEdge::Center {
+            self.auto_exclusive_zone_enable();
+        }
+
+        // The panel only takes keyboard focus when toggled
+        self.set_keyboard_mode(match mode {
+            ClientMode::Popup => gtk4_layer_shell::KeyboardMode::OnDemand,
+            ClientMode::Panel => gtk4_layer_shell::KeyboardMode::None,
+        });
+    }
+
+    /// Handle another launch of the client: close the popup if it is focused
+    /// (else bring it up), or move keyboard focus into or out of the panel
+    pub fn toggle(&self) {
+        let has_focus = match self.imp().mode.get() {
+            ClientMode::Popup => self.is_active(),
+            ClientMode::Panel => {
+                !gtk4_layer_shell::is_supported()
+                    || self.keyboard_mode() != gtk4_layer_shell::KeyboardMode::None
+            }
+        };
+
+        if !has_focus {
+            self.take_keyboard_focus();
+            if self.focused_index().is_none() {
+                self.focus_edge_item(false);
+            }
+        } else if self.imp().mode.get() == ClientMode::Panel {
+            self.release_panel_focus();
+        } else {
+            self.close();
+        }
+    }
+
+    /// Focus an item by id and optionally open its context menu, waiting for
+    /// the first item list if it hasn't arrived yet
+    pub fn focus_item(&self, item_id: &str, open_menu: bool) {
+        let imp = self.imp();
+        self.take_keyboard_focus();
+
+        match self.find_item_widget(item_id) {
+            Some(widget) => {
+                widget.grab_focus();
+                if open_menu {
+                    self.show_context_menu(&widget);
+                }
+            }
+            None if !imp.items_loaded.get() => {
+                imp.pending_focus.replace(Some((item_id.to_string(), open_menu)));
+            }
+            None => tracing::warn!("No item with id {}", item_id),
+        }
+    }
+
+    /// Bring the window up with keyboard focus
+    ///
+    /// The panel asks for focus on demand rather than exclusively, so clicking
+    /// another window still moves the keyboard there.
+    fn take_keyboard_focus(&self) {
+        if self.imp().mode.get() == ClientMode::Panel && gtk4_layer_shell::is_supported() {
+            self.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::OnDemand);
+        }
+        self.present();
+    }
+
+    /// Give keyboard focus back to the previously focused window
+    fn release_panel_focus(&self) {
+        if gtk4_layer_shell::is_supported() {
+            self.set_keyboard_mode(gtk4_layer_shell::KeyboardMode::None);
+        }
+    }
+
     /// Connect to the WayTray daemon and start listening for updates
     fn connect_to_daemon(&self) {
         let window = self.clone();
@@ -276,72 +554,72 @@ impl WayTrayWindow {
             items.iter().map(|i| i.id.as_str()).collect();
 
         // Remove widgets for items that no longer exist
-        let mut to_remove = Vec::new();
-        let mut child = imp.items_box.first_child();
-        while let Some(widget) = child {
-            let next = widget.next_sibling();
-            if let Some(item_widget) = widget.downcast_ref::<ModuleItemWidget>() {
-                if let Some(id) = item_widget.item_id() {
-                    if !new_ids.contains(id.as_str()) {
-                        to_remove.push(id);
-                    }
-                }
-            }
-            child = next;
-        }
+        let to_remove: Vec<String> = self
+            .item_widgets()
+            .iter()
+            .filter_map(|item_widget| item_widget.item_id())
+            .filter(|id| !new_ids.contains(id.as_str()))
+            .collect();
 
         for id in &to_remove {
             if let Some(widget) = self.find_item_widget(id) {
-                imp.items_box.remove(&widget);
+                if let Some(parent) = widget.parent().and_downcast::<gtk4::Box>() {
+                    parent.remove(&widget);
+                }
             }
         }
+        self.remove_empty_groups();
+
+        // Changes to announce; nothing is announced for the initial load
+        let mut announcements = Vec::new();
+        let announcer = imp.announcer.borrow();
+        let mut check_announcement = |old: Option<&ModuleItem>, item: &ModuleItem| {
+            if let Some(announcer) = announcer.as_ref().filter(|_| !was_empty) {
+                announcements.extend(announcer.announcement(old, item));
+            }
+        };
 
         // Update existing items or add new ones in order
         for item in items {
             if let Some(existing_widget) = self.find_item_widget(&item.id) {
-                // Update existing widget only if data changed
-                existing_widget.update_item_if_changed(item);
-            } else {
-                // Create new widget
-                let widget = ModuleItemWidget::new();
-                widget.set_item(item.clone());
-
-                // Connect signals
-                let window = self.clone();
-                widget.connect_activate_item(move |widget| {
-                    window.activate_item(widget);
-                });
-
-                let window = self.clone();
-                widget.connect_context_menu_item(move |widget| {
-                    window.show_context_menu(widget);
-                });
-
-                let window = self.clone();
-                widget.connect_scroll_up(move |widget| {
-                    if let Some(action_id) = widget.step_up_action_id() {
-                        window.invoke_item_action(widget, &action_id);
-                    }
-                });
+                check_announcement(existing_widget.item().as_ref(), item);
 
-                let window = self.clone();
-                widget.connect_scroll_down(move |widget| {
-                    if let Some(action_id) = widget.step_down_action_id() {
-                        window.invoke_item_action(widget, &action_id);
+                if existing_widget.is_adjustable() == is_adjustable(item) {
+                    // Update existing widget only if data changed
+                    existing_widget.update_item_if_changed(item);
+                } else {
+                    // The accessible role changed, so replace the widget in place
+                    let widget = self.create_item_widget(item);
+                    let Some(parent) = existing_widget.parent().and_downcast::<gtk4::Box>() else {
+                        continue;
+                    };
+                    parent.insert_child_after(&widget, existing_widget.prev_sibling().as_ref());
+                    let had_focus = existing_widget.has_focus();
+                    parent.remove(&existing_widget);
+                    if had_focus {
+                        widget.grab_focus();
                     }
-                });
-
-                imp.items_box.append(&widget);
+                }
+            } else {
+                check_announcement(None, item);
+                let widget = self.create_item_widget(item);
+                self.items_box_for(&item.module).append(&widget);
             }
         }
+        drop(announcer);
+
+        for announcement in &announcements {
+            self.announce_change(announcement);
+        }
 
-        // Update status label visibility
+        // Update status label visibility, then hide items not matching the filter
         if items.is_empty() {
             imp.status_label.set_text("No items");
             imp.status_label.set_visible(true);
         } else {
             imp.status_label.set_visible(false);
         }
+        self.apply_filter();
 
         // Only grab focus on initial load, or if the focused item was removed
         let focused_item_removed = focused_item_id
@@ -350,61 +628,263 @@ impl WayTrayWindow {
             .unwrap_or(false);
 
         if was_empty || focused_item_removed {
-            if let Some(first) = imp.items_box.first_child() {
-                first.grab_focus();
+            self.focus_edge_item(false);
+        }
+
+        imp.items_loaded.set(true);
+        let pending_focus = imp.pending_focus.take();
+        if let Some((item_id, open_menu)) = pending_focus {
+            self.focus_item(&item_id, open_menu);
+        }
+    }
+
+    /// Have the screen reader speak an item change
+    fn announce_change(&self, announcement: &Announcement) {
+        tracing::debug!("Announcing: {}", announcement.message);
+
+        #[cfg(feature = "v4_14")]
+        {
+            let priority = match announcement.politeness {
+                waytray_daemon::config::Politeness::Assertive => {
+                    gtk4::AccessibleAnnouncementPriority::High
+                }
+                _ => gtk4::AccessibleAnnouncementPriority::Medium,
+            };
+            self.announce(&announcement.message, priority);
+        }
+
+        #[cfg(not(feature = "v4_14"))]
+        {
+            // Screen readers report text changes of status-role widgets
+            let imp = self.imp();
+            imp.announce_label.set_text(&announcement.message);
+            imp.announce_label.set_visible(true);
+
+            if let Some(source) = imp.announce_timeout.take() {
+                source.remove();
             }
+            let source = glib::timeout_add_local_once(
+                std::time::Duration::from_secs(5),
+                glib::clone!(
+                    #[weak(rename_to = window)]
+                    self,
+                    move || {
+                        let imp = window.imp();
+                        imp.announce_timeout.take();
+                        imp.announce_label.set_visible(false);
+                    }
+                ),
+            );
+            imp.announce_timeout.replace(Some(source));
         }
     }
 
+    /// Create the widget for an item and connect its signals
+    fn create_item_widget(&self, item: &ModuleItem) -> ModuleItemWidget {
+        let widget = ModuleItemWidget::for_item(item);
+        widget.set_compact(self.imp().mode.get() == ClientMode::Panel);
+
+        let window = self.clone();
+        widget.connect_activate_item(move |widget| {
+            window.activate_item(widget);
+        });
+
+        let window = self.clone();
+        widget.connect_context_menu_item(move |widget| {
+            window.show_context_menu(widget);
+        });
+
+        let window = self.clone();
+        widget.connect_scroll_up(move |widget| {
+            if let Some(action_id) = widget.step_up_action_id() {
+                window.invoke_item_action(widget, &action_id);
+            }
+        });
+
+        let window = self.clone();
+        widget.connect_scroll_down(move |widget| {
+            if let Some(action_id) = widget.step_down_action_id() {
+                window.invoke_item_action(widget, &action_id);
+            }
+        });
+
+        let window = self.clone();
+        widget.connect_adjust_item(move |widget, action_id| {
+            window.invoke_item_action(widget, action_id);
+        });
+
+        widget
+    }
+
     /// Get the ID of the currently focused item, if any
     fn get_focused_item_id(&self) -> Option<String> {
+        self.item_widgets()
+            .into_iter()
+            .find(|widget| widget.has_focus() || widget.is_focus())
+            .and_then(|widget| widget.item_id())
+    }
+
+    /// Find a widget by item ID
+    fn find_item_widget(&self, item_id: &str) -> Option<ModuleItemWidget> {
+        self.item_widgets()
+            .into_iter()
+            .find(|widget| widget.item_id().as_deref() == Some(item_id))
+    }
+
+    /// Boxes directly holding item widgets: the items box, or one per module group
+    fn item_boxes(&self) -> Vec<gtk4::Box> {
         let imp = self.imp();
-        let mut child = imp.items_box.first_child();
-        while let Some(widget) = child {
-            if widget.has_focus() || widget.is_focus() {
+        if imp.window_config.borrow().group_by_module {
+            imp.groups.borrow().iter().map(|group| group.items_box.clone()).collect()
+        } else {
+            vec![imp.items_box.clone()]
+        }
+    }
+
+    /// All item widgets in display order
+    fn item_widgets(&self) -> Vec<ModuleItemWidget> {
+        let mut items = Vec::new();
+        for items_box in self.item_boxes() {
+            let mut child = items_box.first_child();
+            while let Some(widget) = child {
                 if let Some(item_widget) = widget.downcast_ref::<ModuleItemWidget>() {
-                    return item_widget.item_id();
+                    items.push(item_widget.clone());
                 }
+                child = widget.next_sibling();
             }
-            child = widget.next_sibling();
         }
-        None
+        items
     }
 
-    /// Find a widget by item ID
-    fn find_item_widget(&self, item_id: &str) -> Option<ModuleItemWidget> {
+    /// The box new items of a module are appended to, creating its group if needed
+    fn items_box_for(&self, module: &str) -> gtk4::Box {
         let imp = self.imp();
-        let mut child = imp.items_box.first_child();
-        while let Some(widget) = child {
-            if let Some(item_widget) = widget.downcast_ref::<ModuleItemWidget>() {
-                if item_widget.item_id().as_deref() == Some(item_id) {
-                    return Some(item_widget.clone());
-                }
+        let layout = {
+            let config = imp.window_config.borrow();
+            if !config.group_by_module {
+                return imp.items_box.clone();
             }
-            child = widget.next_sibling();
+            config.layout
+        };
+        if let Some(group) = imp.groups.borrow().iter().find(|group| group.module == module) {
+            return group.items_box.clone();
         }
-        None
+
+        let title = module_title(module);
+        let container = gtk4::Box::builder()
+            .orientation(gtk4::Orientation::Vertical)
+            .spacing(4)
+            .accessible_role(gtk4::AccessibleRole::Group)
+            .build();
+        container.update_property(&[gtk4::accessible::Property::Label(&title)]);
+
+        let heading = gtk4::Label::new(Some(&title));
+        heading.add_css_class("heading");
+        heading.set_xalign(0.0);
+        // The group's name already says this
+        heading.set_accessible_role(gtk4::AccessibleRole::Presentation);
+
+        let items_box = gtk4::Box::builder()
+            .accessible_role(gtk4::AccessibleRole::List)
+            .build();
+        configure_items_box(&items_box, layout);
+
+        container.append(&heading);
+        container.append(&items_box);
+        imp.items_box.append(&container);
+        imp.groups.borrow_mut().push(ModuleGroup {
+            module: module.to_string(),
+            container,
+            items_box: items_box.clone(),
+        });
+        items_box
     }
 
-    /// Navigate between items using left/right arrows
-    fn navigate_items(&self, direction: i32) {
+    /// Drop the sections of modules that have no items left
+    fn remove_empty_groups(&self) {
+        let imp = self.imp();
+        imp.groups.borrow_mut().retain(|group| {
+            let empty = group.items_box.first_child().is_none();
+            if empty {
+                imp.items_box.remove(&group.container);
+            }
+            !empty
+        });
+    }
+
+    /// Place the items row by row when using the grid layout
+    fn update_grid_positions(&self) {
         let imp = self.imp();
+        let config = imp.window_config.borrow().clone();
+        if config.layout != ItemLayout::Grid {
+            return;
+        }
 
-        // Find currently focused item index
-        let mut current_index: Option<i32> = None;
-        let mut count = 0i32;
-        let mut child = imp.items_box.first_child();
-        while let Some(widget) = child {
-            if widget.has_focus() || widget.is_focus() {
-                current_index = Some(count);
-                break;
+        // Hidden (filtered out) items don't take a cell
+        let columns = config.grid_columns.max(1) as i32;
+        for items_box in self.item_boxes() {
+            let Some(layout) = items_box.layout_manager() else {
+                continue;
+            };
+            for (index, widget) in visible_children(&items_box).iter().enumerate() {
+                let index = index as i32;
+                if let Ok(position) = layout.layout_child(widget).downcast::<gtk4::GridLayoutChild>() {
+                    position.set_column(index % columns);
+                    position.set_row(index / columns);
+                }
             }
-            count += 1;
-            child = widget.next_sibling();
         }
+    }
+
+    /// Item widgets that aren't hidden by the filter, in order
+    fn visible_items(&self) -> Vec<gtk4::Widget> {
+        self.item_boxes().iter().flat_map(visible_children).collect()
+    }
+
+    /// The visible items arrow keys move between: those of the focused
+    /// item's group, or all of them
+    fn navigation_items(&self) -> Vec<gtk4::Widget> {
+        self.item_boxes()
+            .iter()
+            .map(visible_children)
+            .find(|items| items.iter().any(|widget| widget.has_focus() || widget.is_focus()))
+            .unwrap_or_else(|| self.visible_items())
+    }
+
+    /// Index of the focused item among the visible items, if any
+    fn focused_index(&self) -> Option<i32> {
+        focused_position(&self.visible_items()).map(|index| index as i32)
+    }
+
+    /// Move up or down: one item in the vertical layout, one row in the grid
+    fn navigate_rows(&self, direction: i32) -> glib::Propagation {
+        let config = self.imp().window_config.borrow().clone();
+        match config.layout {
+            ItemLayout::Horizontal => glib::Propagation::Proceed,
+            ItemLayout::Vertical => {
+                self.navigate_items(direction);
+                glib::Propagation::Stop
+            }
+            ItemLayout::Grid => {
+                // Rows don't wrap around
+                let items = self.navigation_items();
+                let target = focused_position(&items)
+                    .map_or(0, |index| index as i32 + direction * config.grid_columns.max(1) as i32);
+                if let Some(widget) = usize::try_from(target).ok().and_then(|index| items.get(index)) {
+                    widget.grab_focus();
+                }
+                glib::Propagation::Stop
+            }
+        }
+    }
+
+    /// Navigate between items using left/right arrows
+    fn navigate_items(&self, direction: i32) {
+        let items = self.navigation_items();
+        let current_index = focused_position(&items).map(|index| index as i32);
 
         // Calculate new index
-        let total = self.item_count();
+        let total = items.len() as i32;
         if total == 0 {
             return;
         }
@@ -424,36 +904,145 @@ impl WayTrayWindow {
         };
 
         // Focus the new item
-        if let Some(widget) = self.child_at_index(new_index) {
+        if let Some(widget) = items.get(new_index as usize) {
+            widget.grab_focus();
+        }
+    }
+
+    /// Move to the first item of the previous or next module group
+    fn navigate_groups(&self, direction: i32) -> glib::Propagation {
+        if !self.imp().window_config.borrow().group_by_module {
+            return glib::Propagation::Proceed;
+        }
+
+        // Groups whose items are all filtered out are skipped
+        let groups: Vec<Vec<gtk4::Widget>> = self
+            .item_boxes()
+            .iter()
+            .map(visible_children)
+            .filter(|items| !items.is_empty())
+            .collect();
+        if groups.is_empty() {
+            return glib::Propagation::Stop;
+        }
+
+        let total = groups.len() as i32;
+        let target = match groups.iter().position(|items| focused_position(items).is_some()) {
+            Some(index) => (index as i32 + direction).rem_euclid(total),
+            None => 0,
+        };
+        groups[target as usize][0].grab_focus();
+        glib::Propagation::Stop
+    }
+
+    /// Whether unhandled keys act on the items: no menu is open and focus is
+    /// on an item, or nowhere yet, rather than in the filter entry
+    fn items_own_keys(&self) -> bool {
+        let imp = self.imp();
+        if imp.open_popover_count.get() > 0 {
+            return false;
+        }
+        match GtkWindowExt::focus(self) {
+            Some(widget) => widget.is::<ModuleItemWidget>() && widget.is_ancestor(&imp.items_box),
+            None => true,
+        }
+    }
+
+    /// Focus the first or last visible item
+    fn focus_edge_item(&self, last: bool) {
+        let items = self.visible_items();
+        let target = if last { items.last() } else { items.first() };
+        if let Some(widget) = target {
             widget.grab_focus();
         }
     }
 
-    /// Get total number of items
-    fn item_count(&self) -> i32 {
+    /// Jump to the next item whose name starts with the typed text
+    ///
+    /// Letters typed within a second of each other form one search; typing the
+    /// same letter repeatedly cycles through the items starting with it.
+    fn type_ahead(&self, c: char) {
         let imp = self.imp();
-        let mut count = 0i32;
-        let mut child = imp.items_box.first_child();
-        while child.is_some() {
-            count += 1;
-            child = child.unwrap().next_sibling();
+        let now = std::time::Instant::now();
+        let expired = imp
+            .type_ahead_time
+            .get()
+            .is_none_or(|last| now.duration_since(last) > TYPE_AHEAD_TIMEOUT);
+        imp.type_ahead_time.set(Some(now));
+
+        let mut buffer = imp.type_ahead.borrow_mut();
+        if expired {
+            buffer.clear();
+        }
+        buffer.extend(c.to_lowercase());
+
+        let first: String = c.to_lowercase().collect();
+        let cycling = buffer.chars().count() == 1 || *buffer == first.repeat(buffer.chars().count());
+        let needle = if cycling { first } else { buffer.clone() };
+        drop(buffer);
+
+        let items = self.visible_items();
+        if items.is_empty() {
+            return;
+        }
+        // A new or cycling search starts after the focused item, a longer
+        // one may stay on it
+        let start = match self.focused_index() {
+            Some(index) if cycling => index as usize + 1,
+            Some(index) => index as usize,
+            None => 0,
+        };
+        let found = (0..items.len())
+            .map(|offset| &items[(start + offset) % items.len()])
+            .find(|widget| {
+                widget
+                    .downcast_ref::<ModuleItemWidget>()
+                    .is_some_and(|item| item.display_name().to_lowercase().starts_with(&needle))
+            });
+        if let Some(widget) = found {
+            widget.grab_focus();
         }
-        count
     }
 
-    /// Get child widget at index
-    fn child_at_index(&self, index: i32) -> Option<gtk4::Widget> {
+    /// Show or hide the filter entry
+    fn toggle_filter(&self) {
         let imp = self.imp();
-        let mut current = 0i32;
-        let mut child = imp.items_box.first_child();
-        while let Some(widget) = child {
-            if current == index {
-                return Some(widget);
+        if imp.filter_entry.is_visible() {
+            imp.filter_entry.set_text("");
+            imp.filter_entry.set_visible(false);
+            self.focus_edge_item(false);
+        } else {
+            imp.filter_entry.set_visible(true);
+            imp.filter_entry.grab_focus();
+        }
+    }
+
+    /// Hide items whose label, module and tooltip don't contain the filter text
+    fn apply_filter(&self) {
+        let imp = self.imp();
+        let filter = imp.filter_entry.text().to_lowercase();
+
+        let mut total = 0;
+        let mut shown = 0;
+        for item_widget in self.item_widgets() {
+            let visible = item_widget.matches_filter(&filter);
+            item_widget.set_visible(visible);
+            total += 1;
+            if visible {
+                shown += 1;
             }
-            current += 1;
-            child = widget.next_sibling();
         }
-        None
+
+        // Hide the sections left without items
+        for group in imp.groups.borrow().iter() {
+            group.container.set_visible(!visible_children(&group.items_box).is_empty());
+        }
+
+        if total > 0 {
+            imp.status_label.set_text("No matching items");
+            imp.status_label.set_visible(shown == 0);
+        }
+        self.update_grid_positions();
     }
 
     /// Listen for item changes from the daemon
@@ -490,6 +1079,12 @@ impl WayTrayWindow {
             return;
         };
 
+        // Menu-by-default items open our accessible menu
+        if action_id == "context_menu" {
+            self.show_context_menu(widget);
+            return;
+        }
+
         let client = self.imp().client.borrow().clone();
         let Some(client) = client else {
             return;
@@ -607,6 +1202,7 @@ impl WayTrayWindow {
                                             if until == suppress_until
                                                 && !window.is_active()
                                                 && imp.open_popover_count.get() == 0
+                                                && imp.mode.get() == ClientMode::Popup
                                             {
                                                 window.close();
                                             }
@@ -617,6 +1213,7 @@ impl WayTrayWindow {
                         }
                     ));
 
+                    popover.watch_for_changes();
                     popover.popup();
                 }
                 Ok(_) => {
@@ -668,5 +1265,71 @@ impl WayTrayWindow {
         while let Some(child) = imp.items_box.first_child() {
             imp.items_box.remove(&child);
         }
+        imp.groups.borrow_mut().clear();
+    }
+}
+
+/// Arrange a box of items according to the layout
+fn configure_items_box(items_box: &gtk4::Box, layout: ItemLayout) {
+    items_box.set_spacing(4);
+    match layout {
+        ItemLayout::Horizontal => {
+            items_box.set_orientation(gtk4::Orientation::Horizontal);
+        }
+        ItemLayout::Vertical => {
+            items_box.set_orientation(gtk4::Orientation::Vertical);
+        }
+        ItemLayout::Grid => {
+            // Keep the box (and its child order) but place children in
+            // rows; positions are assigned in update_grid_positions
+            let grid = gtk4::GridLayout::new();
+            grid.set_row_spacing(4);
+            grid.set_column_spacing(4);
+            grid.set_column_homogeneous(true);
+            items_box.set_layout_manager(Some(grid));
+        }
     }
 }
+
+/// Children of a box that aren't hidden, in order
+fn visible_children(items_box: &gtk4::Box) -> Vec<gtk4::Widget> {
+    let mut items = Vec::new();
+    let mut child = items_box.first_child();
+    while let Some(widget) = child {
+        if widget.is_visible() {
+            items.push(widget.clone());
+        }
+        child = widget.next_sibling();
+    }
+    items
+}
+
+/// Position of the focused widget in a list of items
+fn focused_position(items: &[gtk4::Widget]) -> Option<usize> {
+    items.iter().position(|widget| widget.has_focus() || widget.is_focus())
+}
+
+/// Section heading for a module's items
+fn module_title(module: &str) -> String {
+    match module {
+        "tray" => "System Tray".to_string(),
+        "pipewire" => "Sound".to_string(),
+        "power_profiles" => "Power Profile".to_string(),
+        "gpu" => "GPU".to_string(),
+        _ => {
+            let mut chars = module.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        }
+    }
+}
+
+/// Find a monitor by its connector name, e.g. "DP-1"
+fn find_monitor(name: &str) -> Option<gdk::Monitor> {
+    let monitors = gdk::Display::default()?.monitors();
+    (0..monitors.n_items())
+        .filter_map(|i| monitors.item(i)?.downcast::<gdk::Monitor>().ok())
+        .find(|monitor| monitor.connector().as_deref() == Some(name))
+}
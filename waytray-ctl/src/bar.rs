@@ -0,0 +1,385 @@
+//! Streaming output for status bars
+//!
+//! Follows `ModuleItemsChanged` and prints the selected items either as Waybar
+//! custom-module JSON (one object per line) or using the i3bar/swaybar protocol.
+//! For i3bar, click events read from stdin are mapped back to `InvokeAction`.
+
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, BufReader};
+use waytray_daemon::dbus_service::ModuleItemDto;
+
+use crate::daemon_proxy::WayTrayDaemonProxy;
+
+/// Output protocol for `waytray-ctl watch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarFormat {
+    Waybar,
+    I3bar,
+}
+
+impl BarFormat {
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "waybar" => Some(BarFormat::Waybar),
+            "i3bar" | "swaybar" => Some(BarFormat::I3bar),
+            _ => None,
+        }
+    }
+}
+
+/// Options for `waytray-ctl watch`
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOptions {
+    pub format: BarFormat,
+    /// Module names or item ids to include (all items when empty)
+    pub selectors: Vec<String>,
+    /// Text placed between items in Waybar output
+    pub separator: String,
+}
+
+/// One line of Waybar custom-module output (`"return-type": "json"`)
+#[derive(Debug, Serialize)]
+struct WaybarOutput {
+    text: String,
+    tooltip: String,
+    class: Vec<String>,
+    /// Percentage of the first item that has one, for Waybar's `format-icons`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    percentage: Option<u32>,
+}
+
+/// One block of the i3bar protocol
+#[derive(Debug, Serialize)]
+struct I3barBlock {
+    full_text: String,
+    name: String,
+    instance: String,
+    urgent: bool,
+}
+
+/// A click event sent by i3bar/swaybar on stdin
+#[derive(Debug, Deserialize)]
+struct ClickEvent {
+    #[serde(default)]
+    instance: String,
+    button: u32,
+    #[serde(default)]
+    x: i32,
+    #[serde(default)]
+    y: i32,
+}
+
+/// Check whether an item is selected by module name or item id
+fn is_selected(item: &ModuleItemDto, selectors: &[String]) -> bool {
+    selectors.is_empty() || selectors.iter().any(|s| *s == item.module || *s == item.id)
+}
+
+/// CSS class for an item status
+fn status_class(status: &str) -> &'static str {
+    match status {
+        "NeedsAttention" => "needs-attention",
+        "Passive" => "passive",
+        _ => "active",
+    }
+}
+
+/// Render the items as a single Waybar JSON line
+fn render_waybar(items: &[&ModuleItemDto], separator: &str) -> String {
+    let text = items
+        .iter()
+        .map(|item| item.label.as_str())
+        .collect::<Vec<_>>()
+        .join(separator);
+    let tooltip = items
+        .iter()
+        .map(|item| {
+            if item.tooltip.is_empty() {
+                item.label.as_str()
+            } else {
+                item.tooltip.as_str()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n");
+
+    let mut class = Vec::new();
+    for item in items {
+        for name in [item.module.as_str(), status_class(&item.status)] {
+            if !class.iter().any(|c| c == name) {
+                class.push(name.to_string());
+            }
+        }
+    }
+
+    let percentage = items
+        .iter()
+        .find(|item| item.value_kind == "percentage")
+        .map(|item| item.value.round().max(0.0) as u32);
+
+    serde_json::to_string(&WaybarOutput { text, tooltip, class, percentage }).unwrap_or_default()
+}
+
+/// Render the items as one i3bar status line (without the trailing comma)
+fn render_i3bar(items: &[&ModuleItemDto]) -> String {
+    let blocks: Vec<I3barBlock> = items
+        .iter()
+        .map(|item| I3barBlock {
+            full_text: item.label.clone(),
+            name: item.module.clone(),
+            instance: item.id.clone(),
+            urgent: item.status == "NeedsAttention",
+        })
+        .collect();
+
+    serde_json::to_string(&blocks).unwrap_or_default()
+}
+
+/// Parse one line of the i3bar click event stream
+///
+/// The stream is an endless JSON array, so lines carry a leading `[` or `,`.
+fn parse_click(line: &str) -> Option<ClickEvent> {
+    let line = line.trim().trim_start_matches(['[', ',']).trim();
+    if line.is_empty() {
+        return None;
+    }
+    serde_json::from_str(line).ok()
+}
+
+/// Pick the action to invoke for a mouse button
+///
+/// Left click runs the default action, middle click the secondary action,
+/// right click the context menu and the scroll wheel any `*_up`/`*_down` action
+/// other than the page steps. Module menus only open inside the client, so
+/// `context_menu` is skipped for anything but tray items.
+fn action_for_button(item: &ModuleItemDto, button: u32) -> Option<String> {
+    let mut actions = item
+        .actions
+        .iter()
+        .filter(|a| a.id != "context_menu" || item.module == "tray");
+
+    let action = match button {
+        1 => actions.find(|a| a.is_default),
+        2 => actions.find(|a| a.id == "secondary_activate"),
+        3 => actions.find(|a| a.id == "context_menu"),
+        4 => actions.find(|a| a.id.ends_with("_up") && !a.id.ends_with("_page_up")),
+        5 => actions.find(|a| a.id.ends_with("_down") && !a.id.ends_with("_page_down")),
+        _ => None,
+    };
+    action.map(|a| a.id.clone())
+}
+
+/// Fetch the selected items, returning an empty list if the daemon is unavailable
+async fn fetch_items(proxy: &WayTrayDaemonProxy<'_>, selectors: &[String]) -> Vec<ModuleItemDto> {
+    match proxy.get_all_module_items().await {
+        Ok(items) => items
+            .into_iter()
+            .filter(|item| is_selected(item, selectors))
+            .collect(),
+        Err(e) => {
+            eprintln!("waytray-ctl: failed to get items: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Stream items to stdout until the daemon connection is lost
+pub async fn watch(proxy: &WayTrayDaemonProxy<'static>, options: &WatchOptions) -> anyhow::Result<()> {
+    let mut changes = proxy.receive_module_items_changed().await?;
+
+    if options.format == BarFormat::I3bar {
+        println!("{{\"version\":1,\"click_events\":true}}");
+        println!("[");
+        spawn_click_handler(proxy.clone(), options.selectors.clone());
+    }
+
+    let mut last_output = None;
+    loop {
+        let items = fetch_items(proxy, &options.selectors).await;
+        let selected: Vec<&ModuleItemDto> = items.iter().collect();
+        let output = match options.format {
+            BarFormat::Waybar => render_waybar(&selected, &options.separator),
+            BarFormat::I3bar => format!("{},", render_i3bar(&selected)),
+        };
+
+        // Tray items in particular update often without visible changes
+        if last_output.as_ref() != Some(&output) {
+            println!("{}", output);
+            last_output = Some(output);
+        }
+
+        if changes.next().await.is_none() {
+            anyhow::bail!("D-Bus signal stream ended unexpectedly");
+        }
+    }
+}
+
+/// Read i3bar click events from stdin and invoke the matching item action
+fn spawn_click_handler(proxy: WayTrayDaemonProxy<'static>, selectors: Vec<String>) {
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+
+        while let Ok(Some(line)) = lines.next_line().await {
+            let Some(click) = parse_click(&line) else {
+                continue;
+            };
+
+            let items = fetch_items(&proxy, &selectors).await;
+            let Some(item) = items.iter().find(|item| item.id == click.instance) else {
+                continue;
+            };
+            let Some(action_id) = action_for_button(item, click.button) else {
+                continue;
+            };
+
+            if let Err(e) = proxy
+                .invoke_action(&item.id, &action_id, click.x, click.y)
+                .await
+            {
+                eprintln!("waytray-ctl: failed to invoke {} on {}: {}", action_id, item.id, e);
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use waytray_daemon::dbus_service::ItemActionDto;
+
+    fn item(id: &str, module: &str, label: &str, actions: &[(&str, bool)]) -> ModuleItemDto {
+        ModuleItemDto {
+            id: id.to_string(),
+            module: module.to_string(),
+            label: label.to_string(),
+            icon_name: String::new(),
+            icon_pixmap: Vec::new(),
+            icon_width: 0,
+            icon_height: 0,
+            attention_icon_name: String::new(),
+            attention_icon_pixmap: Vec::new(),
+            attention_icon_width: 0,
+            attention_icon_height: 0,
+            overlay_icon_name: String::new(),
+            overlay_icon_pixmap: Vec::new(),
+            overlay_icon_width: 0,
+            overlay_icon_height: 0,
+            status: "Active".to_string(),
+            category: String::new(),
+            tooltip: String::new(),
+            value_kind: String::new(),
+            value: 0.0,
+            value_min: 0.0,
+            value_max: 0.0,
+            value_unit: String::new(),
+            actions: actions
+                .iter()
+                .map(|(id, is_default)| ItemActionDto {
+                    id: id.to_string(),
+                    label: id.to_string(),
+                    is_default: *is_default,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_selection() {
+        let battery = item("battery:BAT0", "battery", "80%", &[]);
+        assert!(is_selected(&battery, &[]));
+        assert!(is_selected(&battery, &["battery".to_string()]));
+        assert!(is_selected(&battery, &["battery:BAT0".to_string()]));
+        assert!(!is_selected(&battery, &["network".to_string()]));
+    }
+
+    #[test]
+    fn test_render_waybar() {
+        let battery = item("battery:BAT0", "battery", "80%", &[]);
+        let mut gpu = item("gpu:0", "gpu", "GPU 12%", &[]);
+        gpu.status = "NeedsAttention".to_string();
+        gpu.tooltip = "GPU busy".to_string();
+
+        let output: serde_json::Value =
+            serde_json::from_str(&render_waybar(&[&battery, &gpu], " | ")).unwrap();
+        assert_eq!(output["text"], "80% | GPU 12%");
+        assert_eq!(output["tooltip"], "80%\n\nGPU busy");
+        assert_eq!(
+            output["class"],
+            serde_json::json!(["battery", "active", "gpu", "needs-attention"])
+        );
+        assert!(output.get("percentage").is_none());
+
+        gpu.value_kind = "percentage".to_string();
+        gpu.value = 12.4;
+        let output: serde_json::Value =
+            serde_json::from_str(&render_waybar(&[&battery, &gpu], " | ")).unwrap();
+        assert_eq!(output["percentage"], 12);
+    }
+
+    #[test]
+    fn test_render_i3bar() {
+        let battery = item("battery:BAT0", "battery", "80%", &[]);
+        let output: serde_json::Value = serde_json::from_str(&render_i3bar(&[&battery])).unwrap();
+        assert_eq!(output[0]["full_text"], "80%");
+        assert_eq!(output[0]["name"], "battery");
+        assert_eq!(output[0]["instance"], "battery:BAT0");
+        assert_eq!(output[0]["urgent"], false);
+    }
+
+    #[test]
+    fn test_parse_click() {
+        assert!(parse_click("[").is_none());
+        let click = parse_click(r#"{"name":"pipewire","instance":"pipewire:volume","button":4,"x":10,"y":2}"#).unwrap();
+        assert_eq!(click.instance, "pipewire:volume");
+        assert_eq!(click.button, 4);
+        let click = parse_click(r#",{"instance":"gpu:0","button":1}"#).unwrap();
+        assert_eq!(click.instance, "gpu:0");
+        assert_eq!((click.x, click.y), (0, 0));
+    }
+
+    #[test]
+    fn test_action_for_button() {
+        let volume = item(
+            "pipewire:volume",
+            "pipewire",
+            "Volume 50%",
+            &[
+                ("toggle_mute", true),
+                ("volume_page_up", false),
+                ("volume_page_down", false),
+                ("volume_up", false),
+                ("volume_down", false),
+                ("context_menu", false),
+            ],
+        );
+        assert_eq!(action_for_button(&volume, 1).as_deref(), Some("toggle_mute"));
+        assert_eq!(action_for_button(&volume, 2), None);
+        assert_eq!(action_for_button(&volume, 4).as_deref(), Some("volume_up"));
+        assert_eq!(action_for_button(&volume, 5).as_deref(), Some("volume_down"));
+    }
+
+    #[test]
+    fn test_context_menu_only_for_tray_items() {
+        let volume = item(
+            "pipewire:volume",
+            "pipewire",
+            "Volume 50%",
+            &[("toggle_mute", true), ("context_menu", false)],
+        );
+        assert_eq!(action_for_button(&volume, 3), None);
+
+        let battery = item("battery:BAT0", "battery", "80%", &[("context_menu", true)]);
+        assert_eq!(action_for_button(&battery, 1), None);
+        assert_eq!(action_for_button(&battery, 3), None);
+
+        let app = item(
+            "tray:app",
+            "tray",
+            "App",
+            &[("activate", true), ("secondary_activate", false), ("context_menu", false)],
+        );
+        assert_eq!(action_for_button(&app, 1).as_deref(), Some("activate"));
+        assert_eq!(action_for_button(&app, 2).as_deref(), Some("secondary_activate"));
+        assert_eq!(action_for_button(&app, 3).as_deref(), Some("context_menu"));
+    }
+}
@@ -0,0 +1,43 @@
+//! D-Bus proxy for the subset of the daemon interface used by waytray-ctl
+
+use waytray_daemon::dbus_service::{MenuItemDto, ModuleInfoDto, ModuleItemDto};
+use zbus::proxy;
+
+/// Proxy for the WayTray daemon D-Bus interface
+#[proxy(
+    interface = "org.waytray.Daemon",
+    default_service = "org.waytray.Daemon",
+    default_path = "/org/waytray/Daemon"
+)]
+pub trait WayTrayDaemon {
+    /// Get all items from all modules
+    fn get_all_module_items(&self) -> zbus::Result<Vec<ModuleItemDto>>;
+
+    /// Get items from a specific module
+    fn get_module_items(&self, module_name: &str) -> zbus::Result<Vec<ModuleItemDto>>;
+
+    /// Get list of registered modules
+    fn get_modules(&self) -> zbus::Result<Vec<ModuleInfoDto>>;
+
+    /// Invoke an action on a module item
+    fn invoke_action(
+        &self,
+        item_id: &str,
+        action_id: &str,
+        x: i32,
+        y: i32,
+    ) -> zbus::Result<()>;
+
+    /// Signal emitted when module items change
+    #[zbus(signal)]
+    fn module_items_changed(&self, module_name: String) -> zbus::Result<()>;
+
+    /// Reload the daemon config file
+    fn reload_config(&self) -> zbus::Result<()>;
+
+    /// Get menu items for a tray item via DBusMenu
+    fn get_item_menu(&self, item_id: &str) -> zbus::Result<Vec<MenuItemDto>>;
+
+    /// Activate a menu item by sending a "clicked" event
+    fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> zbus::Result<()>;
+}
@@ -0,0 +1,387 @@
+//! WayTray Control
+//!
+//! Command-line client for the org.waytray.Daemon interface. Lets compositor
+//! hotkeys and scripts list items and invoke their actions without opening the
+//! GTK window.
+
+mod bar;
+mod daemon_proxy;
+
+use std::process::ExitCode;
+
+use waytray_daemon::dbus_service::{MenuItemDto, ModuleItemDto};
+use zbus::Connection;
+
+use bar::{BarFormat, WatchOptions};
+use daemon_proxy::WayTrayDaemonProxy;
+
+const USAGE: &str = "\
+Usage: waytray-ctl <command> [arguments]
+
+Commands:
+  modules [--json]                       List registered modules
+  items [MODULE] [--json]                List items, optionally for one module
+  actions ITEM_ID [--json]               List the actions of an item
+  invoke ITEM_ID [ACTION_ID]             Invoke an action (default action if omitted)
+  menu ITEM_ID [--json]                  Show the menu of a tray item
+  activate-menu ITEM_ID MENU_ITEM        Activate a menu entry by id or label
+  reload                                 Reload the daemon config file
+  watch [--format waybar|i3bar] [--separator TEXT] [MODULE|ITEM_ID...]
+                                         Stream items for a status bar
+  help                                   Show this help";
+
+/// A parsed command line
+#[derive(Debug, PartialEq)]
+enum Command {
+    Modules { json: bool },
+    Items { module: Option<String>, json: bool },
+    Actions { item_id: String, json: bool },
+    Invoke { item_id: String, action_id: Option<String> },
+    Menu { item_id: String, json: bool },
+    ActivateMenu { item_id: String, menu_item: String },
+    Reload,
+    Watch(WatchOptions),
+    Help,
+}
+
+/// Parse the arguments following the program name
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    if args.first().map(String::as_str) == Some("watch") {
+        return parse_watch_args(&args[1..]);
+    }
+
+    let json = args.iter().any(|a| a == "--json");
+    let positional: Vec<&str> = args
+        .iter()
+        .map(String::as_str)
+        .filter(|a| *a != "--json")
+        .collect();
+
+    if let Some(flag) = positional.iter().find(|a| a.starts_with("--")) {
+        if *flag == "--help" {
+            return Ok(Command::Help);
+        }
+        return Err(format!("Unknown option: {}", flag));
+    }
+
+    let command = match positional.as_slice() {
+        [] | ["help"] | ["-h"] => Command::Help,
+        ["modules"] => Command::Modules { json },
+        ["items"] => Command::Items { module: None, json },
+        ["items", module] => Command::Items {
+            module: Some(module.to_string()),
+            json,
+        },
+        ["actions", item_id] => Command::Actions {
+            item_id: item_id.to_string(),
+            json,
+        },
+        ["invoke", item_id] => Command::Invoke {
+            item_id: item_id.to_string(),
+            action_id: None,
+        },
+        ["invoke", item_id, action_id] => Command::Invoke {
+            item_id: item_id.to_string(),
+            action_id: Some(action_id.to_string()),
+        },
+        ["menu", item_id] => Command::Menu {
+            item_id: item_id.to_string(),
+            json,
+        },
+        ["activate-menu", item_id, menu_item] => Command::ActivateMenu {
+            item_id: item_id.to_string(),
+            menu_item: menu_item.to_string(),
+        },
+        ["reload"] => Command::Reload,
+        [name, ..] => {
+            let known = [
+                "modules",
+                "items",
+                "actions",
+                "invoke",
+                "menu",
+                "activate-menu",
+                "reload",
+                "watch",
+            ];
+            if known.contains(name) {
+                return Err(format!("Wrong number of arguments for '{}'", name));
+            }
+            return Err(format!("Unknown command: {}", name));
+        }
+    };
+
+    Ok(command)
+}
+
+/// Parse the arguments of `watch`, which takes options with values
+fn parse_watch_args(args: &[String]) -> Result<Command, String> {
+    let mut options = WatchOptions {
+        format: BarFormat::Waybar,
+        selectors: Vec::new(),
+        separator: " ".to_string(),
+    };
+
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = iter.next().ok_or("Missing value for --format")?;
+                options.format = BarFormat::from_str(value)
+                    .ok_or_else(|| format!("Unknown format: {}", value))?;
+            }
+            "--separator" => {
+                options.separator = iter.next().ok_or("Missing value for --separator")?.clone();
+            }
+            flag if flag.starts_with("--") => return Err(format!("Unknown option: {}", flag)),
+            selector => options.selectors.push(selector.to_string()),
+        }
+    }
+
+    Ok(Command::Watch(options))
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("waytray-ctl: {}\n\n{}", e, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("waytray-ctl: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(command: Command) -> anyhow::Result<()> {
+    if command == Command::Help {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+
+    let connection = Connection::session().await?;
+    let proxy = WayTrayDaemonProxy::new(&connection).await?;
+
+    match command {
+        Command::Modules { json } => {
+            let modules = proxy.get_modules().await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&modules)?);
+            } else {
+                for module in modules {
+                    let state = if module.enabled { "enabled" } else { "disabled" };
+                    println!("{}\t{}", module.name, state);
+                }
+            }
+        }
+        Command::Items { module, json } => {
+            let items = match module {
+                Some(ref module) => proxy.get_module_items(module).await?,
+                None => proxy.get_all_module_items().await?,
+            };
+            if json {
+                println!("{}", serde_json::to_string_pretty(&items)?);
+            } else {
+                for item in &items {
+                    println!("{}", format_item(item));
+                }
+            }
+        }
+        Command::Actions { item_id, json } => {
+            let item = find_item(&proxy, &item_id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&item.actions)?);
+            } else {
+                for action in &item.actions {
+                    let marker = if action.is_default { " (default)" } else { "" };
+                    println!("{}\t{}{}", action.id, action.label, marker);
+                }
+            }
+        }
+        Command::Invoke { item_id, action_id } => {
+            let action_id = match action_id {
+                Some(action_id) => action_id,
+                None => {
+                    let item = find_item(&proxy, &item_id).await?;
+                    item.actions
+                        .iter()
+                        .find(|a| a.is_default)
+                        .map(|a| a.id.clone())
+                        .ok_or_else(|| anyhow::anyhow!("Item {} has no default action", item_id))?
+                }
+            };
+            proxy.invoke_action(&item_id, &action_id, 0, 0).await?;
+        }
+        Command::Menu { item_id, json } => {
+            let menu = proxy.get_item_menu(&item_id).await?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&menu)?);
+            } else {
+                print_menu(&menu, 0, 0);
+            }
+        }
+        Command::ActivateMenu { item_id, menu_item } => {
+            let menu_item_id = match menu_item.parse::<i32>() {
+                Ok(id) => id,
+                Err(_) => {
+                    let menu = proxy.get_item_menu(&item_id).await?;
+                    find_menu_item(&menu, &menu_item)
+                        .ok_or_else(|| anyhow::anyhow!("No menu entry labelled '{}'", menu_item))?
+                }
+            };
+            proxy.activate_menu_item(&item_id, menu_item_id).await?;
+        }
+        Command::Reload => {
+            proxy.reload_config().await?;
+        }
+        Command::Watch(options) => {
+            bar::watch(&proxy, &options).await?;
+        }
+        Command::Help => unreachable!(),
+    }
+
+    Ok(())
+}
+
+/// Look up a single item by id across all modules
+async fn find_item(proxy: &WayTrayDaemonProxy<'_>, item_id: &str) -> anyhow::Result<ModuleItemDto> {
+    proxy
+        .get_all_module_items()
+        .await?
+        .into_iter()
+        .find(|item| item.id == item_id)
+        .ok_or_else(|| anyhow::anyhow!("No item with id {}", item_id))
+}
+
+/// Format an item as a tab-separated line: id, label, status and action ids
+fn format_item(item: &ModuleItemDto) -> String {
+    let actions: Vec<&str> = item.actions.iter().map(|a| a.id.as_str()).collect();
+    format!(
+        "{}\t{}\t{}\t{}",
+        item.id,
+        item.label,
+        item.status,
+        actions.join(",")
+    )
+}
+
+/// Print the children of `parent_id`, indenting submenus
+fn print_menu(menu: &[MenuItemDto], parent_id: i32, depth: usize) {
+    for entry in menu.iter().filter(|m| m.parent_id == parent_id) {
+        let indent = "  ".repeat(depth);
+        if entry.item_type == "separator" {
+            println!("{}--------", indent);
+            continue;
+        }
+
+        let toggle = match (entry.toggle_type.as_str(), entry.toggle_state) {
+            ("checkmark", 1) => "[x] ",
+            ("checkmark", _) => "[ ] ",
+            ("radio", 1) => "(*) ",
+            ("radio", _) => "( ) ",
+            _ => "",
+        };
+        let disabled = if entry.enabled { "" } else { " (disabled)" };
+        println!("{}{}\t{}{}{}", indent, entry.id, toggle, entry.label, disabled);
+
+        if entry.has_submenu {
+            print_menu(menu, entry.id, depth + 1);
+        }
+    }
+}
+
+/// Find a menu entry by its label (case-insensitive), ignoring separators
+fn find_menu_item(menu: &[MenuItemDto], label: &str) -> Option<i32> {
+    menu.iter()
+        .filter(|m| m.item_type != "separator")
+        .find(|m| m.label.eq_ignore_ascii_case(label))
+        .map(|m| m.id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn menu_entry(id: i32, label: &str, item_type: &str) -> MenuItemDto {
+        MenuItemDto {
+            id,
+            parent_id: 0,
+            label: label.to_string(),
+            enabled: true,
+            item_type: item_type.to_string(),
+            icon_name: String::new(),
+            toggle_type: String::new(),
+            toggle_state: -1,
+            has_submenu: false,
+        }
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(parse_args(&args(&[])), Ok(Command::Help));
+        assert_eq!(
+            parse_args(&args(&["items", "--json"])),
+            Ok(Command::Items { module: None, json: true })
+        );
+        assert_eq!(
+            parse_args(&args(&["items", "pipewire"])),
+            Ok(Command::Items {
+                module: Some("pipewire".to_string()),
+                json: false
+            })
+        );
+        assert_eq!(
+            parse_args(&args(&["invoke", "pipewire:volume", "volume_up"])),
+            Ok(Command::Invoke {
+                item_id: "pipewire:volume".to_string(),
+                action_id: Some("volume_up".to_string())
+            })
+        );
+        assert_eq!(parse_args(&args(&["reload"])), Ok(Command::Reload));
+    }
+
+    #[test]
+    fn test_parse_watch() {
+        assert_eq!(
+            parse_args(&args(&["watch", "--format", "i3bar", "battery", "gpu"])),
+            Ok(Command::Watch(WatchOptions {
+                format: BarFormat::I3bar,
+                selectors: vec!["battery".to_string(), "gpu".to_string()],
+                separator: " ".to_string(),
+            }))
+        );
+        assert!(parse_args(&args(&["watch", "--format"])).is_err());
+        assert!(parse_args(&args(&["watch", "--format", "polybar"])).is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_args(&args(&["bogus"])).is_err());
+        assert!(parse_args(&args(&["invoke"])).is_err());
+        assert!(parse_args(&args(&["modules", "--verbose"])).is_err());
+    }
+
+    #[test]
+    fn test_find_menu_item_by_label() {
+        let menu = vec![
+            menu_entry(1, "Open", "standard"),
+            menu_entry(2, "", "separator"),
+            menu_entry(3, "Quit", "standard"),
+        ];
+        assert_eq!(find_menu_item(&menu, "quit"), Some(3));
+        assert_eq!(find_menu_item(&menu, "Missing"), None);
+    }
+}
@@ -0,0 +1,417 @@
+//! Threshold alert rules
+//!
+//! Rules from `[[alerts]]` in the config are checked against the metrics that
+//! modules publish with their items (see [`crate::modules::Metric`]). A rule
+//! fires a notification once its condition has held for `for_seconds`, and
+//! fires again only after the reading has moved back past the threshold by
+//! `hysteresis`.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::config::AlertRuleConfig;
+use crate::modules::{Metric, Urgency};
+
+/// Comparison operator of a condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Greater,
+    GreaterEqual,
+    Less,
+    LessEqual,
+    Equal,
+    NotEqual,
+}
+
+impl Comparison {
+    /// Operators in the order they are searched for, two-character ones first
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        (">=", Comparison::GreaterEqual),
+        ("<=", Comparison::LessEqual),
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        (">", Comparison::Greater),
+        ("<", Comparison::Less),
+    ];
+
+    fn compare(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::Greater => value > threshold,
+            Comparison::GreaterEqual => value >= threshold,
+            Comparison::Less => value < threshold,
+            Comparison::LessEqual => value <= threshold,
+            Comparison::Equal => value == threshold,
+            Comparison::NotEqual => value != threshold,
+        }
+    }
+}
+
+/// A parsed `when` expression, e.g. `system.cpu > 90` or `!network.connected`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    /// Metric name; a `*` segment matches any one segment
+    pub metric: String,
+    pub comparison: Comparison,
+    pub threshold: f64,
+}
+
+impl Condition {
+    /// Parse a condition
+    ///
+    /// Accepts `<metric> <op> <number>` with `>`, `>=`, `<`, `<=`, `==` or
+    /// `!=` (a trailing `%` on the number is ignored), a bare `<metric>`
+    /// (true when non-zero) and `!<metric>` (true when zero).
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+
+        let operator = Comparison::OPERATORS
+            .iter()
+            .filter_map(|(op, comparison)| expression.find(op).map(|pos| (pos, *op, *comparison)))
+            .min_by_key(|(pos, op, _)| (*pos, std::cmp::Reverse(op.len())));
+
+        let (metric, comparison, threshold) = match operator {
+            Some((pos, op, comparison)) => {
+                let number = expression[pos + op.len()..].trim();
+                let threshold = number
+                    .trim_end_matches('%')
+                    .trim()
+                    .parse::<f64>()
+                    .map_err(|_| format!("invalid number '{}'", number))?;
+                (expression[..pos].trim(), comparison, threshold)
+            }
+            None => match expression.strip_prefix('!') {
+                Some(metric) => (metric.trim(), Comparison::Equal, 0.0),
+                None => (expression, Comparison::NotEqual, 0.0),
+            },
+        };
+
+        let valid_name = |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-' | '*');
+        if metric.is_empty() || !metric.chars().all(valid_name) {
+            return Err(format!("invalid metric name '{}'", metric));
+        }
+
+        Ok(Self {
+            metric: metric.to_string(),
+            comparison,
+            threshold,
+        })
+    }
+
+    /// Whether a metric name matches this condition's metric pattern
+    pub fn matches(&self, name: &str) -> bool {
+        let mut pattern = self.metric.split('.');
+        let mut segments = name.split('.');
+        loop {
+            match (pattern.next(), segments.next()) {
+                (None, None) => return true,
+                (Some(p), Some(s)) if p == "*" || p == s => {}
+                _ => return false,
+            }
+        }
+    }
+
+    /// Whether the condition holds for a reading
+    pub fn holds(&self, value: f64) -> bool {
+        self.comparison.compare(value, self.threshold)
+    }
+
+    /// Whether a firing alert may clear: the condition no longer holds even
+    /// with the threshold moved `hysteresis` towards the clear side
+    pub fn cleared(&self, value: f64, hysteresis: f64) -> bool {
+        let threshold = match self.comparison {
+            Comparison::Greater | Comparison::GreaterEqual => self.threshold - hysteresis,
+            Comparison::Less | Comparison::LessEqual => self.threshold + hysteresis,
+            Comparison::Equal | Comparison::NotEqual => self.threshold,
+        };
+        !self.comparison.compare(value, threshold)
+    }
+}
+
+/// A notification produced by a rule
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub title: String,
+    pub body: String,
+    pub urgency: Urgency,
+    pub sound: Option<String>,
+}
+
+/// A rule with its parsed condition
+struct AlertRule {
+    config: AlertRuleConfig,
+    condition: Condition,
+}
+
+impl AlertRule {
+    /// Build the notification for a reading of `metric`
+    fn alert(&self, metric: &str, value: f64) -> Alert {
+        let value = format_value(value);
+        let threshold = format_value(self.condition.threshold);
+        let body = match self.config.message {
+            Some(ref message) => message
+                .replace("{metric}", metric)
+                .replace("{value}", &value)
+                .replace("{threshold}", &threshold),
+            None => match self.condition.comparison {
+                Comparison::Greater | Comparison::GreaterEqual => {
+                    format!("{} is {}, above {}", metric, value, threshold)
+                }
+                Comparison::Less | Comparison::LessEqual => {
+                    format!("{} is {}, below {}", metric, value, threshold)
+                }
+                Comparison::Equal | Comparison::NotEqual => format!("{} is {}", metric, value),
+            },
+        };
+        let title = if self.config.name.is_empty() {
+            self.config.when.trim().to_string()
+        } else {
+            self.config.name.clone()
+        };
+
+        Alert {
+            title,
+            body,
+            urgency: self.config.urgency,
+            sound: self.config.sound.clone(),
+        }
+    }
+}
+
+/// Format a reading without needless decimals
+fn format_value(value: f64) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.1}", value)
+    }
+}
+
+/// State of a rule for one metric
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum AlertState {
+    /// The condition holds, but not yet for long enough
+    Pending(Instant),
+    /// The alert was sent and hasn't cleared yet
+    Firing,
+}
+
+/// Checks alert rules against published metrics
+#[derive(Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    /// State per (rule index, metric name); absent means idle
+    states: HashMap<(usize, String), AlertState>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: &[AlertRuleConfig]) -> Self {
+        let mut engine = Self::default();
+        engine.set_rules(rules);
+        engine
+    }
+
+    /// Replace the rules, skipping invalid ones
+    ///
+    /// Rules whose config is unchanged keep their state, so a reload doesn't
+    /// re-send alerts that are already firing or restart pending timers.
+    pub fn set_rules(&mut self, rules: &[AlertRuleConfig]) {
+        let old_rules = std::mem::take(&mut self.rules);
+        self.rules = rules
+            .iter()
+            .filter_map(|config| match Condition::parse(&config.when) {
+                Ok(condition) => Some(AlertRule {
+                    config: config.clone(),
+                    condition,
+                }),
+                Err(e) => {
+                    tracing::warn!("Ignoring alert rule '{}': {}", config.when, e);
+                    None
+                }
+            })
+            .collect();
+
+        // Map each old rule index to the new index of an identical rule
+        let mut remap = HashMap::new();
+        for (new_index, rule) in self.rules.iter().enumerate() {
+            let old_index = old_rules
+                .iter()
+                .enumerate()
+                .find(|(i, old)| old.config == rule.config && !remap.contains_key(i))
+                .map(|(i, _)| i);
+            if let Some(old_index) = old_index {
+                remap.insert(old_index, new_index);
+            }
+        }
+
+        self.states = std::mem::take(&mut self.states)
+            .into_iter()
+            .filter_map(|((index, name), state)| {
+                remap.get(&index).map(|&new_index| ((new_index, name), state))
+            })
+            .collect();
+    }
+
+    /// Check the rules against a module's current metrics, returning the
+    /// alerts to send
+    ///
+    /// Metrics of the module that are no longer published reset their state.
+    pub fn update(&mut self, module: &str, metrics: &[Metric], now: Instant) -> Vec<Alert> {
+        let prefix = format!("{}.", module);
+        self.states.retain(|(_, name), _| {
+            !name.starts_with(&prefix) || metrics.iter().any(|m| m.name == *name)
+        });
+
+        let mut alerts = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let hold_for = Duration::from_secs(rule.config.for_seconds);
+            let hysteresis = rule.config.hysteresis.max(0.0);
+
+            for metric in metrics.iter().filter(|m| rule.condition.matches(&m.name)) {
+                let key = (index, metric.name.clone());
+                let holds = rule.condition.holds(metric.value);
+
+                let next = match self.states.get(&key).copied() {
+                    None if holds => Some(AlertState::Pending(now)),
+                    None => None,
+                    Some(AlertState::Pending(since)) if holds => Some(AlertState::Pending(since)),
+                    Some(AlertState::Pending(_)) => None,
+                    Some(AlertState::Firing) if rule.condition.cleared(metric.value, hysteresis) => None,
+                    Some(AlertState::Firing) => Some(AlertState::Firing),
+                };
+
+                match next {
+                    Some(AlertState::Pending(since)) if now.duration_since(since) >= hold_for => {
+                        alerts.push(rule.alert(&metric.name, metric.value));
+                        self.states.insert(key, AlertState::Firing);
+                    }
+                    Some(state) => {
+                        self.states.insert(key, state);
+                    }
+                    None => {
+                        self.states.remove(&key);
+                    }
+                }
+            }
+        }
+
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(when: &str) -> AlertRuleConfig {
+        AlertRuleConfig {
+            name: "Alert".to_string(),
+            when: when.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn metrics(name: &str, value: f64) -> Vec<Metric> {
+        vec![Metric::new(name, value)]
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        let condition = Condition::parse("system.cpu >= 90%").unwrap();
+        assert_eq!(condition.metric, "system.cpu");
+        assert_eq!(condition.comparison, Comparison::GreaterEqual);
+        assert_eq!(condition.threshold, 90.0);
+
+        let condition = Condition::parse("!network.connected").unwrap();
+        assert_eq!(condition.comparison, Comparison::Equal);
+        assert!(condition.holds(0.0));
+        assert!(!condition.holds(1.0));
+
+        assert_eq!(Condition::parse("battery.*.percentage<10").unwrap().comparison, Comparison::Less);
+        assert!(Condition::parse("gpu.memory > lots").is_err());
+        assert!(Condition::parse("> 5").is_err());
+        assert!(Condition::parse("cpu usage > 5").is_err());
+    }
+
+    #[test]
+    fn test_metric_patterns() {
+        let condition = Condition::parse("battery.*.percentage < 10").unwrap();
+        assert!(condition.matches("battery.mouse_0.percentage"));
+        assert!(!condition.matches("battery.percentage"));
+        assert!(!condition.matches("battery.mouse_0.percentage.extra"));
+    }
+
+    #[test]
+    fn test_duration_and_hysteresis() {
+        let mut config = rule("system.cpu > 90");
+        config.for_seconds = 60;
+        config.hysteresis = 10.0;
+        let mut engine = AlertEngine::new(&[config]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(0)).is_empty());
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(30)).is_empty());
+        let alerts = engine.update("system", &metrics("system.cpu", 93.0), at(60));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].body, "system.cpu is 93, above 90");
+
+        // Still firing, and dipping below 90 but not 80 doesn't re-arm it
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(120)).is_empty());
+        assert!(engine.update("system", &metrics("system.cpu", 85.0), at(130)).is_empty());
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(200)).is_empty());
+
+        // Dropping below 80 clears it, then it needs another full minute
+        assert!(engine.update("system", &metrics("system.cpu", 70.0), at(210)).is_empty());
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(220)).is_empty());
+        assert_eq!(engine.update("system", &metrics("system.cpu", 95.0), at(280)).len(), 1);
+    }
+
+    #[test]
+    fn test_flags_and_per_metric_state() {
+        let mut disconnected = rule("!network.connected");
+        disconnected.message = Some("Lost connection".to_string());
+        disconnected.urgency = Urgency::Critical;
+        let mut engine = AlertEngine::new(&[disconnected, rule("battery.*.percentage < 10")]);
+        let now = Instant::now();
+
+        let alerts = engine.update("network", &[Metric::flag("network.connected", false)], now);
+        assert_eq!(alerts[0].body, "Lost connection");
+        assert_eq!(alerts[0].urgency, Urgency::Critical);
+
+        let devices = vec![
+            Metric::new("battery.mouse_0.percentage", 8.0),
+            Metric::new("battery.keyboard_0.percentage", 50.0),
+        ];
+        assert_eq!(engine.update("battery", &devices, now).len(), 1);
+        assert!(engine.update("battery", &devices, now).is_empty());
+
+        // A device that goes away and comes back alerts again
+        engine.update("battery", &devices[1..], now);
+        assert_eq!(engine.update("battery", &devices, now).len(), 1);
+    }
+
+    #[test]
+    fn test_reload_keeps_state_of_unchanged_rules() {
+        let mut pending = rule("system.cpu > 90");
+        pending.for_seconds = 60;
+        let mut engine = AlertEngine::new(&[rule("!network.connected"), pending.clone()]);
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let offline = [Metric::flag("network.connected", false)];
+        assert_eq!(engine.update("network", &offline, at(0)).len(), 1);
+        assert!(engine.update("system", &metrics("system.cpu", 95.0), at(0)).is_empty());
+
+        // Same rules in a different order, plus a new one: nothing re-fires
+        // and the pending timer keeps running
+        engine.set_rules(&[rule("system.mem > 90"), pending.clone(), rule("!network.connected")]);
+        assert!(engine.update("network", &offline, at(10)).is_empty());
+        assert_eq!(engine.update("system", &metrics("system.cpu", 95.0), at(60)).len(), 1);
+
+        // Editing a rule starts it from scratch
+        let mut edited = rule("!network.connected");
+        edited.message = Some("Offline".to_string());
+        engine.set_rules(&[edited, pending]);
+        assert_eq!(engine.update("network", &offline, at(70)).len(), 1);
+    }
+}
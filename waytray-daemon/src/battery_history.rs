@@ -0,0 +1,195 @@
+//! On-disk battery energy history
+//!
+//! Stores (time, energy, charging) samples per battery in
+//! `$XDG_STATE_HOME/waytray/battery-history.json`. Time remaining is estimated
+//! from the energy trend over the last half hour, which is steadier than
+//! UPower's `TimeToEmpty`, and the history survives daemon restarts.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Samples older than this are dropped
+const MAX_AGE_SECS: i64 = 48 * 3600;
+/// Minimum time between stored samples of one battery
+const MIN_INTERVAL_SECS: i64 = 60;
+/// How far back the time-remaining estimate looks
+const ESTIMATE_WINDOW_SECS: i64 = 30 * 60;
+/// Minimum span of samples before estimating
+const MIN_ESTIMATE_SPAN_SECS: i64 = 5 * 60;
+/// A gap this long between samples (suspend, daemon not running) starts a new run
+const MAX_GAP_SECS: i64 = 10 * 60;
+
+/// One energy reading of a battery
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergySample {
+    /// Unix time in seconds
+    pub time: i64,
+    /// Energy in Wh
+    pub energy: f64,
+    pub charging: bool,
+}
+
+/// Energy samples of all batteries, keyed by device name (e.g. "BAT0")
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BatteryHistory {
+    devices: HashMap<String, Vec<EnergySample>>,
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl BatteryHistory {
+    /// Default history file under the XDG state directory
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::state_dir().map(|dir| dir.join("waytray").join("battery-history.json"))
+    }
+
+    /// Load the history from a file, starting empty if it's missing or unreadable
+    pub fn load(path: &Path) -> Self {
+        let mut history = match std::fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring unreadable battery history {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        history.path = Some(path.to_path_buf());
+        history
+    }
+
+    /// Write the history back to the file it was loaded from
+    pub fn save(&self) -> anyhow::Result<()> {
+        let Some(ref path) = self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+
+        // Write to a temporary file first so a crash can't leave a truncated file
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string(self)?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Record a sample, returning whether it was stored
+    ///
+    /// Samples closer than a minute to the previous one are skipped unless the
+    /// battery switched between charging and discharging.
+    pub fn record(&mut self, device: &str, sample: EnergySample) -> bool {
+        let samples = self.devices.entry(device.to_string()).or_default();
+        if let Some(last) = samples.last() {
+            if sample.time - last.time < MIN_INTERVAL_SECS && last.charging == sample.charging {
+                return false;
+            }
+        }
+
+        samples.push(sample);
+        samples.retain(|s| sample.time - s.time <= MAX_AGE_SECS);
+        true
+    }
+
+    /// Estimate the seconds until the battery is empty, or full when charging
+    ///
+    /// Uses the samples of the current charge or discharge run within the last
+    /// half hour. Returns None until the run covers five minutes.
+    pub fn estimate(&self, device: &str, now: EnergySample, energy_full: f64) -> Option<i64> {
+        let samples = self.devices.get(device)?;
+
+        let mut oldest = None;
+        let mut next_time = now.time;
+        for sample in samples.iter().rev() {
+            if sample.charging != now.charging
+                || now.time - sample.time > ESTIMATE_WINDOW_SECS
+                || next_time - sample.time > MAX_GAP_SECS
+            {
+                break;
+            }
+            oldest = Some(sample);
+            next_time = sample.time;
+        }
+
+        let oldest = oldest?;
+        let span = now.time - oldest.time;
+        if span < MIN_ESTIMATE_SPAN_SECS {
+            return None;
+        }
+
+        let (delta, remaining) = if now.charging {
+            (now.energy - oldest.energy, (energy_full - now.energy).max(0.0))
+        } else {
+            (oldest.energy - now.energy, now.energy)
+        };
+        if delta <= 0.0 {
+            return None;
+        }
+
+        // Energy per second over the run
+        let rate = delta / span as f64;
+        Some((remaining / rate).round() as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(time: i64, energy: f64, charging: bool) -> EnergySample {
+        EnergySample { time, energy, charging }
+    }
+
+    #[test]
+    fn test_record_skips_close_samples() {
+        let mut history = BatteryHistory::default();
+        assert!(history.record("BAT0", sample(1000, 50.0, false)));
+        assert!(!history.record("BAT0", sample(1030, 49.9, false)));
+        // Plugging in is always recorded
+        assert!(history.record("BAT0", sample(1040, 49.9, true)));
+        assert!(history.record("BAT0", sample(1000 + MAX_AGE_SECS + 100, 40.0, true)));
+        assert_eq!(history.devices["BAT0"].len(), 1);
+    }
+
+    #[test]
+    fn test_estimate_from_discharge_run() {
+        let mut history = BatteryHistory::default();
+        // Charging earlier, then discharging at 10 W (1 Wh every 6 minutes)
+        history.record("BAT0", sample(0, 40.0, true));
+        for i in 0..5 {
+            history.record("BAT0", sample(600 + i * 360, 50.0 - i as f64, false));
+        }
+
+        let now = sample(600 + 5 * 360, 45.0, false);
+        // 45 Wh at 10 W
+        assert_eq!(history.estimate("BAT0", now, 60.0), Some(4 * 3600 + 1800));
+
+        // Too little data for another battery, and a gap breaks the run
+        assert_eq!(history.estimate("BAT1", now, 60.0), None);
+        let later = sample(now.time + MAX_GAP_SECS + 1, 44.0, false);
+        assert_eq!(history.estimate("BAT0", later, 60.0), None);
+    }
+
+    #[test]
+    fn test_estimate_while_charging() {
+        let mut history = BatteryHistory::default();
+        history.record("BAT0", sample(0, 30.0, true));
+        history.record("BAT0", sample(600, 35.0, true));
+        // 30 Wh to go at 5 Wh per 10 minutes
+        assert_eq!(history.estimate("BAT0", sample(600, 35.0, true), 65.0), Some(3600));
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = std::env::temp_dir().join(format!("waytray-history-test-{}", std::process::id()));
+        let path = dir.join("battery-history.json");
+
+        let mut history = BatteryHistory::load(&path);
+        history.record("BAT0", sample(1000, 50.0, false));
+        history.save().unwrap();
+
+        let loaded = BatteryHistory::load(&path);
+        assert_eq!(loaded.devices["BAT0"], vec![sample(1000, 50.0, false)]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -164,6 +164,58 @@ impl ItemCache {
         }
     }
 
+    /// Update the attention icon of an item
+    pub async fn update_attention_icon(
+        &self,
+        id: &str,
+        icon_name: Option<String>,
+        icon_pixmap: Option<Vec<u8>>,
+        width: u32,
+        height: u32,
+    ) {
+        let mut items = self.items.write().await;
+        if let Some(item) = items.get_mut(id) {
+            let changed = item.attention_icon_name != icon_name
+                || item.attention_icon_pixmap != icon_pixmap
+                || item.attention_icon_width != width
+                || item.attention_icon_height != height;
+
+            if changed {
+                item.attention_icon_name = icon_name;
+                item.attention_icon_pixmap = icon_pixmap;
+                item.attention_icon_width = width;
+                item.attention_icon_height = height;
+                let _ = self.change_tx.send(CacheEvent::ItemUpdated(id.to_string()));
+            }
+        }
+    }
+
+    /// Update the overlay icon of an item
+    pub async fn update_overlay_icon(
+        &self,
+        id: &str,
+        icon_name: Option<String>,
+        icon_pixmap: Option<Vec<u8>>,
+        width: u32,
+        height: u32,
+    ) {
+        let mut items = self.items.write().await;
+        if let Some(item) = items.get_mut(id) {
+            let changed = item.overlay_icon_name != icon_name
+                || item.overlay_icon_pixmap != icon_pixmap
+                || item.overlay_icon_width != width
+                || item.overlay_icon_height != height;
+
+            if changed {
+                item.overlay_icon_name = icon_name;
+                item.overlay_icon_pixmap = icon_pixmap;
+                item.overlay_icon_width = width;
+                item.overlay_icon_height = height;
+                let _ = self.change_tx.send(CacheEvent::ItemUpdated(id.to_string()));
+            }
+        }
+    }
+
     /// Update the tooltip of an item
     pub async fn update_tooltip(&self, id: &str, tooltip: Option<String>) {
         let mut items = self.items.write().await;
@@ -175,6 +227,11 @@ impl ItemCache {
         }
     }
 
+    /// Notify that an item's cached menu has changed
+    pub fn notify_menu_changed(&self, id: &str) {
+        let _ = self.change_tx.send(CacheEvent::MenuUpdated(id.to_string()));
+    }
+
     /// Notify that items have changed (for manual broadcast)
     pub fn notify_changed(&self) {
         // Send a generic update for when we don't know which item changed
@@ -200,6 +257,7 @@ mod tests {
     fn make_test_item(id: &str) -> TrayItem {
         TrayItem {
             id: id.to_string(),
+            sni_id: format!("test-{}", id),
             bus_name: format!("org.test.{}", id),
             object_path: "/StatusNotifierItem".to_string(),
             title: format!("Test Item {}", id),
@@ -207,6 +265,14 @@ mod tests {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon_name: None,
+            attention_icon_pixmap: None,
+            attention_icon_width: 0,
+            attention_icon_height: 0,
+            overlay_icon_name: None,
+            overlay_icon_pixmap: None,
+            overlay_icon_width: 0,
+            overlay_icon_height: 0,
             tooltip: None,
             status: ItemStatus::Active,
             has_menu: false,
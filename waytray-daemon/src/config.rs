@@ -1,13 +1,20 @@
 use anyhow::{Context, Result};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::modules::Urgency;
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct Config {
     pub modules: ModulesConfig,
     pub notifications: NotificationsConfig,
+    /// Threshold rules checked against module readings
+    pub alerts: Vec<AlertRuleConfig>,
+    /// Settings of the GTK client
+    pub client: ClientConfig,
 }
 
 impl Default for Config {
@@ -15,6 +22,8 @@ impl Default for Config {
         Self {
             modules: ModulesConfig::default(),
             notifications: NotificationsConfig::default(),
+            alerts: Vec::new(),
+            client: ClientConfig::default(),
         }
     }
 }
@@ -65,14 +74,63 @@ impl Default for ModulesConfig {
 #[serde(default)]
 pub struct TrayModuleConfig {
     pub enabled: bool,
+    /// Hide items whose status is Passive (background helpers and the like)
+    pub hide_passive: bool,
+    /// Sort items by SNI category: application status, communications,
+    /// system services, then hardware
+    pub sort_by_category: bool,
+    /// Per-item rules, applied in order
+    pub rules: Vec<TrayRuleConfig>,
 }
 
 impl Default for TrayModuleConfig {
     fn default() -> Self {
-        Self { enabled: true }
+        Self {
+            enabled: true,
+            hide_passive: false,
+            sort_by_category: false,
+            rules: Vec::new(),
+        }
     }
 }
 
+/// A rule that matches tray items and changes how they are shown
+///
+/// All given match fields must match. Patterns are globs (`*`, `?`) unless
+/// `regex` is set.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TrayRuleConfig {
+    /// Pattern for the item's SNI `Id` property
+    pub id: Option<String>,
+    /// Pattern for the item's title
+    pub title: Option<String>,
+    /// Pattern for the item's D-Bus bus name
+    pub bus_name: Option<String>,
+    /// Treat patterns as regular expressions instead of globs
+    pub regex: bool,
+    /// Hide matching items
+    pub hide: bool,
+    /// Replace the item's label
+    pub label: Option<String>,
+    /// Replace the item's icon with this theme icon name
+    pub icon: Option<String>,
+    /// Pin the item to this position (0 is first, -1 is last)
+    pub position: Option<i32>,
+    /// Force what Enter/click does
+    pub default_action: Option<TrayDefaultAction>,
+}
+
+/// Default action forced by a tray rule
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrayDefaultAction {
+    /// Activate the item (primary action)
+    Activate,
+    /// Show the item's menu
+    Menu,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 #[serde(default)]
 pub struct BatteryModuleConfig {
@@ -89,6 +147,11 @@ pub struct BatteryModuleConfig {
     pub critical_sound: Option<String>,
     /// Sound file path to play when fully charged (optional)
     pub full_sound: Option<String>,
+    /// UPower device kinds to show, e.g. "battery", "mouse", "keyboard",
+    /// "headset" (empty = all)
+    pub include_kinds: Vec<String>,
+    /// UPower device kinds to hide
+    pub exclude_kinds: Vec<String>,
 }
 
 impl Default for BatteryModuleConfig {
@@ -101,6 +164,8 @@ impl Default for BatteryModuleConfig {
             low_sound: None,
             critical_sound: None,
             full_sound: None,
+            include_kinds: Vec::new(),
+            exclude_kinds: Vec::new(),
         }
     }
 }
@@ -190,6 +255,18 @@ pub struct NetworkModuleConfig {
     pub show_ip: bool,
     /// Show upload/download speed
     pub show_speed: bool,
+    /// Show the connection name (or Wi-Fi SSID and signal strength) in the label
+    pub show_connection: bool,
+    /// Read connection state from NetworkManager when it is running,
+    /// falling back to /sys/class/net otherwise
+    pub use_networkmanager: bool,
+    /// Interfaces to show as separate items (empty = a single item for `interface`).
+    /// A trailing `*` matches by prefix, and `"*"` alone matches every interface that is up
+    pub interfaces: Vec<String>,
+    /// Add an item with the combined speed of all shown interfaces
+    pub show_total: bool,
+    /// Number of speed samples kept per interface for the tooltip's average and peak
+    pub history_size: usize,
     /// Update interval in seconds
     pub interval_seconds: u64,
 }
@@ -201,6 +278,11 @@ impl Default for NetworkModuleConfig {
             interface: String::new(), // Auto-detect
             show_ip: false,
             show_speed: true,
+            show_connection: true,
+            use_networkmanager: true,
+            interfaces: Vec::new(),
+            show_total: true,
+            history_size: 30,
             interval_seconds: 2,
         }
     }
@@ -248,6 +330,8 @@ pub struct PipewireModuleConfig {
     pub mic_max_volume: u32,
     /// Microphone volume step for scroll adjustment (percentage)
     pub mic_scroll_step: u32,
+    /// Show one item per application stream (playback and recording)
+    pub show_streams: bool,
 }
 
 impl Default for PipewireModuleConfig {
@@ -261,6 +345,7 @@ impl Default for PipewireModuleConfig {
             show_mic_volume: true,
             mic_max_volume: 100,
             mic_scroll_step: 5,
+            show_streams: false,
         }
     }
 }
@@ -269,7 +354,7 @@ impl Default for PipewireModuleConfig {
 #[serde(default)]
 pub struct PrivacyModuleConfig {
     pub enabled: bool,
-    /// Update interval in seconds
+    /// Seconds to wait before reconnecting when the audio server is unavailable
     pub interval_seconds: u64,
     /// Show an idle item when no apps are using the microphone
     pub show_when_idle: bool,
@@ -375,6 +460,180 @@ impl Default for NotificationsConfig {
     }
 }
 
+/// A rule that sends a notification when a module reading crosses a threshold
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AlertRuleConfig {
+    /// Notification title (defaults to the condition)
+    pub name: String,
+    /// Condition such as "system.cpu > 90", "battery.*.percentage < 10"
+    /// or "!network.connected"
+    pub when: String,
+    /// Seconds the condition must hold before the alert fires
+    pub for_seconds: u64,
+    /// How far the reading must move back past the threshold before the
+    /// alert can fire again
+    pub hysteresis: f64,
+    /// Notification body; {metric}, {value} and {threshold} are replaced
+    pub message: Option<String>,
+    /// Notification urgency: low, normal or critical
+    pub urgency: Urgency,
+    /// Sound file path to play when the alert fires (optional)
+    pub sound: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ClientConfig {
+    /// Open on demand, or stay visible as a panel
+    pub mode: ClientMode,
+    pub window: WindowConfig,
+    pub announcements: AnnouncementsConfig,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientMode {
+    /// Opened on demand and closed when it loses focus or on Escape
+    #[default]
+    Popup,
+    /// Always visible with compact items and reserved space; only takes
+    /// keyboard focus when `waytray` is run again, and gives it back on Escape
+    Panel,
+}
+
+/// Placement and layout of the client window
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct WindowConfig {
+    /// Screen edge the window is attached to (layer shell only)
+    pub edge: WindowEdge,
+    /// Layer shell layer
+    pub layer: WindowLayer,
+    /// Distance in pixels from each screen edge the window is attached to
+    pub margin_top: i32,
+    pub margin_bottom: i32,
+    pub margin_left: i32,
+    pub margin_right: i32,
+    /// Output (monitor) connector name such as "DP-1"; empty lets the
+    /// compositor choose, usually the focused output
+    pub output: String,
+    /// How the items are arranged
+    pub layout: ItemLayout,
+    /// Items per row in the grid layout
+    pub grid_columns: u32,
+    /// Put each module's items in a labelled section
+    pub group_by_module: bool,
+    /// Reserve space at the edge so other windows aren't covered
+    pub exclusive_zone: bool,
+    /// Default window size in pixels
+    pub width: i32,
+    pub height: i32,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        Self {
+            edge: WindowEdge::Top,
+            layer: WindowLayer::Overlay,
+            margin_top: 0,
+            margin_bottom: 0,
+            margin_left: 0,
+            margin_right: 0,
+            output: String::new(),
+            layout: ItemLayout::Horizontal,
+            grid_columns: 4,
+            group_by_module: false,
+            exclusive_zone: false,
+            width: 600,
+            height: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowEdge {
+    /// Stretched across the top of the screen
+    #[default]
+    Top,
+    Bottom,
+    /// Stretched along the left side of the screen
+    Left,
+    Right,
+    /// Not attached to any edge
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WindowLayer {
+    Background,
+    Bottom,
+    Top,
+    #[default]
+    Overlay,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemLayout {
+    /// One row, navigated with Left/Right
+    #[default]
+    Horizontal,
+    /// One column, navigated with Up/Down as well
+    Vertical,
+    /// Rows of `grid_columns` items
+    Grid,
+}
+
+/// Screen reader announcements made by the client window for item changes
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AnnouncementsConfig {
+    pub enabled: bool,
+    /// Announce items that start needing attention
+    pub attention: bool,
+    /// Announce batteries dropping to the battery module's low and critical thresholds
+    pub battery_thresholds: bool,
+    /// Modules whose label changes are announced, e.g. ["scripts"]
+    pub label_modules: Vec<String>,
+    /// Politeness per module name (default: polite)
+    pub politeness: HashMap<String, Politeness>,
+}
+
+impl Default for AnnouncementsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            attention: true,
+            battery_thresholds: true,
+            label_modules: vec!["scripts".to_string()],
+            politeness: HashMap::new(),
+        }
+    }
+}
+
+impl AnnouncementsConfig {
+    /// Politeness of announcements for a module
+    pub fn politeness(&self, module: &str) -> Politeness {
+        self.politeness.get(module).copied().unwrap_or_default()
+    }
+}
+
+/// How urgently a screen reader should speak an announcement
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Politeness {
+    /// Don't announce changes of this module
+    Off,
+    /// Wait until the screen reader is idle
+    #[default]
+    Polite,
+    /// Interrupt current speech
+    Assertive,
+}
+
 impl Config {
     /// Load configuration from the default path (~/.config/waytray/config.toml)
     /// Creates the config file with defaults if it doesn't exist
@@ -445,6 +704,24 @@ order = ["tray"]
 
 [modules.tray]
 enabled = true
+# hide_passive = false       # Hide items that report a Passive status
+# sort_by_category = false   # Group items by category
+#
+# Rules match items by SNI id, title or bus name (globs, or regexes with
+# regex = true) and can hide, relabel, re-icon, pin or change the default action:
+#
+# [[modules.tray.rules]]
+# title = "chrome_status_icon_*"
+# label = "Chrome"
+#
+# [[modules.tray.rules]]
+# id = "nm-applet"
+# position = 0                # 0 = first, -1 = last
+# default_action = "menu"     # activate or menu
+#
+# [[modules.tray.rules]]
+# bus_name = "org.example.Helper*"
+# hide = true
 
 # Uncomment to enable battery module
 # [modules.battery]
@@ -455,6 +732,8 @@ enabled = true
 # low_sound = "~/.config/waytray/sounds/low.wav"
 # critical_sound = "~/.config/waytray/sounds/critical.wav"
 # full_sound = "~/.config/waytray/sounds/full.wav"
+# include_kinds = []     # Device kinds to show, e.g. ["battery", "mouse"] (empty = all)
+# exclude_kinds = []     # Device kinds to hide, e.g. ["ups"]
 
 # Uncomment to enable display brightness module (uses logind D-Bus)
 # [modules.brightness]
@@ -492,6 +771,11 @@ enabled = true
 # interface = ""          # Empty = auto-detect default route interface
 # show_ip = false
 # show_speed = true
+# show_connection = true  # Show connection name / Wi-Fi SSID and strength
+# use_networkmanager = true  # Use NetworkManager when running, else /sys/class/net
+# interfaces = []         # One item per interface, e.g. ["en*", "wlan0", "wg0"]; ["*"] = all up
+# show_total = true       # Combined speed item when several interfaces are shown
+# history_size = 30       # Speed samples kept for the tooltip's average and peak
 # interval_seconds = 2
 
 # Uncomment to enable pipewire/pulseaudio volume module
@@ -504,11 +788,12 @@ enabled = true
 # show_mic_volume = true  # Show mic volume percentage in label
 # mic_max_volume = 100    # Maximum mic volume (100 = normal, 150 = allow boost)
 # mic_scroll_step = 5     # Mic volume change per scroll step
+# show_streams = false    # Show an item per application stream
 
 # Uncomment to enable privacy module (microphone usage)
 # [modules.privacy]
 # enabled = true
-# interval_seconds = 2    # Polling interval in seconds
+# interval_seconds = 2    # Reconnect delay if the audio server is unavailable
 # show_when_idle = false  # Show an idle item when no apps are using the mic
 
 # Uncomment to enable power profiles module (requires power-profiles-daemon)
@@ -543,6 +828,51 @@ enabled = true
 [notifications]
 enabled = true
 timeout_ms = 5000
+
+# Alert rules send a notification when a module reading crosses a threshold.
+# Readings are named <module>.<reading>, e.g. system.cpu, system.memory,
+# system.temperature, gpu.usage, gpu.memory, battery.percentage,
+# network.connected; a * segment matches any one segment.
+#
+# [[alerts]]
+# name = "High CPU usage"
+# when = "system.cpu > 90"
+# for_seconds = 60            # Condition must hold this long
+# hysteresis = 10             # Re-arm only once CPU drops below 80
+# urgency = "normal"          # low, normal or critical
+# sound = "~/.config/waytray/sounds/alert.wav"
+#
+# [[alerts]]
+# name = "Network down"
+# when = "!network.connected"
+# message = "No network connection"
+
+# [client]
+# mode = "popup"              # popup (open on demand) or panel (always visible)
+#
+# Placement and layout of the client window
+# [client.window]
+# edge = "top"                # top, bottom, left, right or center
+# layer = "overlay"           # background, bottom, top or overlay
+# margin_top = 0              # Also margin_bottom, margin_left and margin_right
+# output = ""                 # Monitor connector, e.g. "DP-1"; empty = compositor's choice
+# layout = "horizontal"       # horizontal, vertical or grid
+# grid_columns = 4
+# group_by_module = false      # One labelled section per module
+# exclusive_zone = false      # Reserve space so other windows aren't covered
+# width = 600
+# height = 60
+
+# Screen reader announcements made by the client window while it is open
+# [client.announcements]
+# enabled = true
+# attention = true            # Items that start needing attention
+# battery_thresholds = true   # Batteries reaching the low/critical threshold
+# label_modules = ["scripts"] # Modules whose label changes are announced
+#
+# [client.announcements.politeness]
+# battery = "assertive"       # off, polite or assertive (default polite)
+# tray = "off"
 "#
         .to_string()
     }
@@ -644,6 +974,46 @@ enabled = true
 "#;
         let config: Config = toml::from_str(toml).unwrap();
         assert!(config.modules.tray.enabled);
+        assert!(!config.modules.tray.hide_passive);
+        assert!(!config.modules.tray.sort_by_category);
+    }
+
+    #[test]
+    fn test_parse_tray_options() {
+        let toml = r#"
+[modules.tray]
+hide_passive = true
+sort_by_category = true
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert!(config.modules.tray.enabled);
+        assert!(config.modules.tray.hide_passive);
+        assert!(config.modules.tray.sort_by_category);
+    }
+
+    #[test]
+    fn test_parse_tray_rules() {
+        let toml = r#"
+[[modules.tray.rules]]
+title = "chrome_status_icon_*"
+label = "Chrome"
+
+[[modules.tray.rules]]
+id = "^nm-.*$"
+regex = true
+position = -1
+default_action = "menu"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let rules = &config.modules.tray.rules;
+        assert!(config.modules.tray.enabled);
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].title.as_deref(), Some("chrome_status_icon_*"));
+        assert_eq!(rules[0].label.as_deref(), Some("Chrome"));
+        assert!(!rules[0].regex);
+        assert!(rules[1].regex);
+        assert_eq!(rules[1].position, Some(-1));
+        assert_eq!(rules[1].default_action, Some(TrayDefaultAction::Menu));
     }
 
     #[test]
@@ -695,6 +1065,88 @@ timeout_ms = 3000
         assert!(config.modules.scripts[0].enabled);
         assert_eq!(config.modules.scripts[0].mode, ScriptMode::Interval);
         assert_eq!(config.notifications.timeout_ms, 3000);
+        assert!(config.alerts.is_empty());
+    }
+
+    #[test]
+    fn test_parse_alerts() {
+        let toml = r#"
+[[alerts]]
+name = "Hot GPU"
+when = "gpu.temperature > 85"
+for_seconds = 30
+hysteresis = 5
+urgency = "critical"
+sound = "~/alert.wav"
+
+[[alerts]]
+when = "!network.connected"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        assert_eq!(config.alerts.len(), 2);
+        assert_eq!(config.alerts[0].name, "Hot GPU");
+        assert_eq!(config.alerts[0].for_seconds, 30);
+        assert_eq!(config.alerts[0].hysteresis, 5.0);
+        assert_eq!(config.alerts[0].urgency, Urgency::Critical);
+        assert_eq!(config.alerts[0].sound.as_deref(), Some("~/alert.wav"));
+        assert_eq!(config.alerts[1].urgency, Urgency::Normal);
+        assert_eq!(config.alerts[1].message, None);
+    }
+
+    #[test]
+    fn test_parse_client_window() {
+        let toml = r#"
+[client.window]
+edge = "left"
+layer = "top"
+margin_left = 8
+output = "DP-1"
+layout = "grid"
+grid_columns = 3
+group_by_module = true
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let window = &config.client.window;
+        assert_eq!(window.edge, WindowEdge::Left);
+        assert_eq!(window.layer, WindowLayer::Top);
+        assert_eq!(window.margin_left, 8);
+        assert_eq!(window.output, "DP-1");
+        assert_eq!(window.layout, ItemLayout::Grid);
+        assert_eq!(window.grid_columns, 3);
+        assert!(window.group_by_module);
+        assert!(!window.exclusive_zone);
+        assert_eq!((window.width, window.height), (600, 60));
+
+        assert_eq!(config.client.mode, ClientMode::Popup);
+        assert!(toml::from_str::<Config>("[client.window]\nedge = \"middle\"").is_err());
+
+        let config: Config = toml::from_str("[client]\nmode = \"panel\"").unwrap();
+        assert_eq!(config.client.mode, ClientMode::Panel);
+    }
+
+    #[test]
+    fn test_parse_client_announcements() {
+        let config = Config::default();
+        assert!(config.client.announcements.enabled);
+        assert_eq!(config.client.announcements.label_modules, vec!["scripts"]);
+
+        let toml = r#"
+[client.announcements]
+attention = false
+label_modules = []
+
+[client.announcements.politeness]
+battery = "assertive"
+tray = "off"
+"#;
+        let config: Config = toml::from_str(toml).unwrap();
+        let announcements = &config.client.announcements;
+        assert!(announcements.enabled);
+        assert!(!announcements.attention);
+        assert!(announcements.label_modules.is_empty());
+        assert_eq!(announcements.politeness("battery"), Politeness::Assertive);
+        assert_eq!(announcements.politeness("tray"), Politeness::Off);
+        assert_eq!(announcements.politeness("scripts"), Politeness::Polite);
     }
 
     #[test]
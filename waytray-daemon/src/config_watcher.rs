@@ -14,6 +14,16 @@ const MAX_LOAD_RETRIES: u32 = 3;
 /// Delay between retries (to allow editor to finish writing)
 const RETRY_DELAY_MS: u64 = 200;
 
+/// Load the config file once and apply it to all modules
+///
+/// Used for explicit reload requests (SIGHUP, the ReloadConfig D-Bus method).
+pub async fn reload_now(registry: &ModuleRegistry) -> anyhow::Result<()> {
+    let new_config = AppConfig::load()?;
+    registry.reload_config(&new_config).await;
+    tracing::info!("Config reloaded successfully");
+    Ok(())
+}
+
 /// Watch the config file and reload modules when it changes
 pub async fn watch_config(
     config_path: impl AsRef<Path>,
@@ -14,13 +14,16 @@ use zbus::zvariant::Type;
 
 use crate::cache::ItemCache;
 use crate::host::Host;
-use crate::modules::{ItemAction, ModuleEvent, ModuleInfo, ModuleItem, ModuleRegistry};
+use crate::modules::{
+    ItemAction, ItemIcon, ItemValue, ModuleEvent, ModuleInfo, ModuleItem, ModuleRegistry, ValueKind,
+};
 use crate::{ItemCategory, ItemStatus, TrayItem};
 
 /// Serializable version of TrayItem for D-Bus transport
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Type)]
 pub struct TrayItemDto {
     pub id: String,
+    pub sni_id: String,
     pub bus_name: String,
     pub object_path: String,
     pub title: String,
@@ -28,6 +31,14 @@ pub struct TrayItemDto {
     pub icon_pixmap: Vec<u8>,
     pub icon_width: u32,
     pub icon_height: u32,
+    pub attention_icon_name: String,
+    pub attention_icon_pixmap: Vec<u8>,
+    pub attention_icon_width: u32,
+    pub attention_icon_height: u32,
+    pub overlay_icon_name: String,
+    pub overlay_icon_pixmap: Vec<u8>,
+    pub overlay_icon_width: u32,
+    pub overlay_icon_height: u32,
     pub tooltip: String,
     pub status: String,
     pub has_menu: bool,
@@ -40,6 +51,7 @@ impl From<TrayItem> for TrayItemDto {
     fn from(item: TrayItem) -> Self {
         Self {
             id: item.id,
+            sni_id: item.sni_id,
             bus_name: item.bus_name,
             object_path: item.object_path,
             title: item.title,
@@ -47,18 +59,20 @@ impl From<TrayItem> for TrayItemDto {
             icon_pixmap: item.icon_pixmap.unwrap_or_default(),
             icon_width: item.icon_width,
             icon_height: item.icon_height,
+            attention_icon_name: item.attention_icon_name.unwrap_or_default(),
+            attention_icon_pixmap: item.attention_icon_pixmap.unwrap_or_default(),
+            attention_icon_width: item.attention_icon_width,
+            attention_icon_height: item.attention_icon_height,
+            overlay_icon_name: item.overlay_icon_name.unwrap_or_default(),
+            overlay_icon_pixmap: item.overlay_icon_pixmap.unwrap_or_default(),
+            overlay_icon_width: item.overlay_icon_width,
+            overlay_icon_height: item.overlay_icon_height,
             tooltip: item.tooltip.unwrap_or_default(),
             status: item.status.as_str().to_string(),
             has_menu: item.has_menu,
             menu_path: item.menu_path.unwrap_or_default(),
             item_is_menu: item.item_is_menu,
-            category: match item.category {
-                ItemCategory::ApplicationStatus => "ApplicationStatus",
-                ItemCategory::Communications => "Communications",
-                ItemCategory::SystemServices => "SystemServices",
-                ItemCategory::Hardware => "Hardware",
-            }
-            .to_string(),
+            category: item.category.as_str().to_string(),
         }
     }
 }
@@ -67,6 +81,7 @@ impl From<TrayItemDto> for TrayItem {
     fn from(dto: TrayItemDto) -> Self {
         Self {
             id: dto.id,
+            sni_id: dto.sni_id,
             bus_name: dto.bus_name,
             object_path: dto.object_path,
             title: dto.title,
@@ -82,6 +97,14 @@ impl From<TrayItemDto> for TrayItem {
             },
             icon_width: dto.icon_width,
             icon_height: dto.icon_height,
+            attention_icon_name: non_empty(dto.attention_icon_name),
+            attention_icon_pixmap: non_empty(dto.attention_icon_pixmap),
+            attention_icon_width: dto.attention_icon_width,
+            attention_icon_height: dto.attention_icon_height,
+            overlay_icon_name: non_empty(dto.overlay_icon_name),
+            overlay_icon_pixmap: non_empty(dto.overlay_icon_pixmap),
+            overlay_icon_width: dto.overlay_icon_width,
+            overlay_icon_height: dto.overlay_icon_height,
             tooltip: if dto.tooltip.is_empty() {
                 None
             } else {
@@ -100,6 +123,15 @@ impl From<TrayItemDto> for TrayItem {
     }
 }
 
+/// Map empty D-Bus values (strings, byte arrays) back to None
+fn non_empty<T: AsRef<[u8]>>(value: T) -> Option<T> {
+    if value.as_ref().is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
 /// Serializable version of ModuleItem for D-Bus transport
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Type)]
 pub struct ModuleItemDto {
@@ -110,7 +142,22 @@ pub struct ModuleItemDto {
     pub icon_pixmap: Vec<u8>,
     pub icon_width: u32,
     pub icon_height: u32,
+    pub attention_icon_name: String,
+    pub attention_icon_pixmap: Vec<u8>,
+    pub attention_icon_width: u32,
+    pub attention_icon_height: u32,
+    pub overlay_icon_name: String,
+    pub overlay_icon_pixmap: Vec<u8>,
+    pub overlay_icon_width: u32,
+    pub overlay_icon_height: u32,
+    pub status: String,
+    pub category: String, // empty for items without an SNI category
     pub tooltip: String,
+    pub value_kind: String, // empty for items without a value
+    pub value: f64,
+    pub value_min: f64,
+    pub value_max: f64,
+    pub value_unit: String,
     pub actions: Vec<ItemActionDto>,
 }
 
@@ -124,6 +171,9 @@ pub struct ItemActionDto {
 
 impl From<ModuleItem> for ModuleItemDto {
     fn from(item: ModuleItem) -> Self {
+        let attention = item.attention_icon.unwrap_or_default();
+        let overlay = item.overlay_icon.unwrap_or_default();
+        let value = item.value;
         Self {
             id: item.id,
             module: item.module,
@@ -132,7 +182,25 @@ impl From<ModuleItem> for ModuleItemDto {
             icon_pixmap: item.icon_pixmap.unwrap_or_default(),
             icon_width: item.icon_width,
             icon_height: item.icon_height,
+            attention_icon_name: attention.name.unwrap_or_default(),
+            attention_icon_pixmap: attention.pixmap.unwrap_or_default(),
+            attention_icon_width: attention.width,
+            attention_icon_height: attention.height,
+            overlay_icon_name: overlay.name.unwrap_or_default(),
+            overlay_icon_pixmap: overlay.pixmap.unwrap_or_default(),
+            overlay_icon_width: overlay.width,
+            overlay_icon_height: overlay.height,
+            status: item.status.as_str().to_string(),
+            category: item
+                .category
+                .map(|c| c.as_str().to_string())
+                .unwrap_or_default(),
             tooltip: item.tooltip.unwrap_or_default(),
+            value_kind: value.as_ref().map(|v| v.kind.as_str().to_string()).unwrap_or_default(),
+            value: value.as_ref().map_or(0.0, |v| v.value),
+            value_min: value.as_ref().map_or(0.0, |v| v.min),
+            value_max: value.as_ref().map_or(0.0, |v| v.max),
+            value_unit: value.map(|v| v.unit).unwrap_or_default(),
             actions: item.actions.into_iter().map(ItemActionDto::from).collect(),
         }
     }
@@ -156,11 +224,32 @@ impl From<ModuleItemDto> for ModuleItem {
             },
             icon_width: dto.icon_width,
             icon_height: dto.icon_height,
+            attention_icon: ItemIcon::from_parts(
+                non_empty(dto.attention_icon_name),
+                non_empty(dto.attention_icon_pixmap),
+                dto.attention_icon_width,
+                dto.attention_icon_height,
+            ),
+            overlay_icon: ItemIcon::from_parts(
+                non_empty(dto.overlay_icon_name),
+                non_empty(dto.overlay_icon_pixmap),
+                dto.overlay_icon_width,
+                dto.overlay_icon_height,
+            ),
+            status: ItemStatus::from_str(&dto.status),
+            category: non_empty(dto.category).map(|c| ItemCategory::from_str(&c)),
             tooltip: if dto.tooltip.is_empty() {
                 None
             } else {
                 Some(dto.tooltip)
             },
+            value: ValueKind::parse(&dto.value_kind).map(|kind| ItemValue {
+                kind,
+                value: dto.value,
+                min: dto.value_min,
+                max: dto.value_max,
+                unit: dto.value_unit,
+            }),
             actions: dto.actions.into_iter().map(ItemAction::from).collect(),
         }
     }
@@ -282,6 +371,19 @@ impl DaemonService {
             registry: Some(registry),
         }
     }
+
+    async fn menu_items(&self, item_id: &str, refresh: bool) -> zbus::fdo::Result<Vec<MenuItemDto>> {
+        if let Some(ref registry) = self.registry {
+            let items = registry
+                .get_menu_items(item_id, refresh)
+                .await
+                .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
+
+            Ok(flatten_menu_items(items))
+        } else {
+            Err(zbus::fdo::Error::InvalidArgs("Module API not available".to_string()))
+        }
+    }
 }
 
 #[interface(name = "org.waytray.Daemon")]
@@ -478,6 +580,19 @@ impl DaemonService {
         }
     }
 
+    /// Reload the config file and apply it to all modules
+    async fn reload_config(&self) -> zbus::fdo::Result<()> {
+        tracing::debug!("ReloadConfig called");
+
+        if let Some(ref registry) = self.registry {
+            crate::config_watcher::reload_now(registry)
+                .await
+                .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))
+        } else {
+            Err(zbus::fdo::Error::InvalidArgs("Module API not available".to_string()))
+        }
+    }
+
     /// Signal emitted when module items change
     #[zbus(signal)]
     pub async fn module_items_changed(
@@ -485,20 +600,21 @@ impl DaemonService {
         module_name: &str,
     ) -> zbus::Result<()>;
 
+    /// Signal emitted when the menu of an item changes while it may be open
+    #[zbus(signal)]
+    pub async fn menu_changed(emitter: &SignalEmitter<'_>, item_id: &str) -> zbus::Result<()>;
+
     /// Get menu items for a tray item via DBusMenu
     async fn get_item_menu(&self, item_id: &str) -> zbus::fdo::Result<Vec<MenuItemDto>> {
         tracing::debug!("GetItemMenu called for item: {}", item_id);
+        self.menu_items(item_id, false).await
+    }
 
-        if let Some(ref registry) = self.registry {
-            let items = registry
-                .get_menu_items(item_id)
-                .await
-                .map_err(|e| zbus::fdo::Error::Failed(e.to_string()))?;
-
-            Ok(flatten_menu_items(items))
-        } else {
-            Err(zbus::fdo::Error::InvalidArgs("Module API not available".to_string()))
-        }
+    /// Get the menu of an item that is already open, after MenuChanged.
+    /// Unlike GetItemMenu this does not call AboutToShow on the application.
+    async fn get_current_item_menu(&self, item_id: &str) -> zbus::fdo::Result<Vec<MenuItemDto>> {
+        tracing::debug!("GetCurrentItemMenu called for item: {}", item_id);
+        self.menu_items(item_id, true).await
     }
 
     /// Activate a menu item by sending a "clicked" event
@@ -549,7 +665,12 @@ pub async fn start_service(
     let mut rx = cache.subscribe();
 
     tokio::spawn(async move {
-        while rx.recv().await.is_ok() {
+        while let Ok(event) = rx.recv().await {
+            // Menu changes are only exposed through the module API
+            if matches!(event, crate::CacheEvent::MenuUpdated(_)) {
+                continue;
+            }
+
             // Emit the signal
             if let Ok(iface_ref) = connection_clone
                 .object_server()
@@ -630,8 +751,21 @@ pub async fn start_service_with_registry(
                         tracing::debug!("Emitted ItemsChanged signal after config reload");
                     }
                 }
-                ModuleEvent::Notification { .. } => {
-                    // Notifications are handled by the registry, not D-Bus
+                ModuleEvent::MenuUpdated { item_id } => {
+                    if let Ok(iface_ref) = connection_clone
+                        .object_server()
+                        .interface::<_, DaemonService>(crate::dbus::DAEMON_OBJECT_PATH)
+                        .await
+                    {
+                        if let Err(e) =
+                            DaemonService::menu_changed(iface_ref.signal_emitter(), &item_id).await
+                        {
+                            tracing::warn!("Failed to emit MenuChanged signal: {}", e);
+                        }
+                    }
+                }
+                ModuleEvent::Notification { .. } | ModuleEvent::MetricsUpdated { .. } => {
+                    // Notifications and alerts are handled by the registry, not D-Bus
                 }
             }
         }
@@ -12,7 +12,7 @@ use zbus::zvariant::{OwnedValue, Value};
 
 /// Proxy for communicating with DBusMenu interfaces
 #[proxy(interface = "com.canonical.dbusmenu")]
-trait DBusMenu {
+pub trait DBusMenu {
     /// Get the menu layout starting from a parent item
     ///
     /// Returns (revision, layout) where layout is (id, properties, children)
@@ -35,10 +35,22 @@ trait DBusMenu {
     /// Notify the application that a menu item is about to be shown
     /// Returns true if the menu needs to be refreshed
     fn about_to_show(&self, id: i32) -> zbus::Result<bool>;
+
+    /// Emitted when the layout below `parent` has changed
+    #[zbus(signal)]
+    fn layout_updated(&self, revision: u32, parent: i32) -> zbus::Result<()>;
+
+    /// Emitted when properties of one or more items have changed
+    #[zbus(signal)]
+    fn items_properties_updated(
+        &self,
+        updated_props: Vec<(i32, HashMap<String, OwnedValue>)>,
+        removed_props: Vec<(i32, Vec<String>)>,
+    ) -> zbus::Result<()>;
 }
 
 /// A menu item parsed from DBusMenu layout
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct MenuItem {
     /// Unique ID for this menu item
     pub id: i32,
@@ -76,38 +88,135 @@ impl Default for MenuItem {
     }
 }
 
-/// Fetch menu items from a DBusMenu interface
-pub async fn fetch_menu(
+/// Properties requested from GetLayout
+const LAYOUT_PROPERTIES: [&str; 8] = [
+    "label",
+    "enabled",
+    "visible",
+    "type",
+    "icon-name",
+    "toggle-type",
+    "toggle-state",
+    "children-display",
+];
+
+/// A menu tree cached from a DBusMenu interface
+///
+/// The tree keeps hidden items so that later property updates can make them
+/// visible again; use [`CachedMenu::visible_items`] to get what should be shown.
+#[derive(Debug, Clone)]
+pub struct CachedMenu {
+    /// Bus name the menu was fetched from
+    pub bus_name: String,
+    /// Object path of the menu
+    pub menu_path: String,
+    /// Layout revision reported by the application
+    pub revision: u32,
+    /// Root item (not displayed itself)
+    pub root: MenuItem,
+}
+
+impl CachedMenu {
+    /// Visible top-level items, with hidden items removed at every level
+    pub fn visible_items(&self) -> Vec<MenuItem> {
+        visible_children(&self.root.children)
+    }
+
+    /// Apply an ItemsPropertiesUpdated signal to the tree.
+    /// Returns true if any known item was touched.
+    pub fn apply_properties_updated(
+        &mut self,
+        updated: Vec<(i32, HashMap<String, OwnedValue>)>,
+        removed: Vec<(i32, Vec<String>)>,
+    ) -> bool {
+        let mut changed = false;
+
+        for (id, properties) in updated {
+            if let Some(item) = find_item_mut(&mut self.root, id) {
+                for (key, value) in properties {
+                    apply_property(item, &key, &value);
+                }
+                changed = true;
+            }
+        }
+
+        for (id, keys) in removed {
+            if let Some(item) = find_item_mut(&mut self.root, id) {
+                for key in keys {
+                    reset_property(item, &key);
+                }
+                changed = true;
+            }
+        }
+
+        changed
+    }
+
+    /// Replace the subtree rooted at `subtree.id` with a freshly fetched layout.
+    /// Returns whether the subtree differed from the cached one, or None if the
+    /// item is not part of the cached tree.
+    pub fn replace_subtree(&mut self, revision: u32, subtree: MenuItem) -> Option<bool> {
+        let item = find_item_mut(&mut self.root, subtree.id)?;
+        let changed = *item != subtree;
+        *item = subtree;
+        self.revision = revision;
+        Some(changed)
+    }
+}
+
+/// Build a DBusMenu proxy for the given menu
+pub async fn menu_proxy(
     connection: &zbus::Connection,
     bus_name: &str,
     menu_path: &str,
-) -> anyhow::Result<Vec<MenuItem>> {
-    let proxy = DBusMenuProxy::builder(connection)
-        .destination(bus_name)?
-        .path(menu_path)?
+) -> anyhow::Result<DBusMenuProxy<'static>> {
+    Ok(DBusMenuProxy::builder(connection)
+        .destination(bus_name.to_string())?
+        .path(menu_path.to_string())?
         .build()
+        .await?)
+}
+
+/// Fetch the layout below `parent_id`, returning (revision, item)
+pub async fn fetch_layout(
+    proxy: &DBusMenuProxy<'_>,
+    parent_id: i32,
+) -> anyhow::Result<(u32, MenuItem)> {
+    let (revision, layout) = proxy
+        .get_layout(parent_id, -1, LAYOUT_PROPERTIES.to_vec())
         .await?;
+    Ok((revision, parse_menu_item(layout, 0)?))
+}
 
+/// Fetch the complete menu tree from a DBusMenu interface
+pub async fn fetch_menu_tree(
+    proxy: &DBusMenuProxy<'_>,
+    bus_name: &str,
+    menu_path: &str,
+) -> anyhow::Result<CachedMenu> {
     // Call AboutToShow on root to let the app prepare the menu
     let _ = proxy.about_to_show(0).await;
 
-    // Fetch the complete menu layout
-    let property_names = vec![
-        "label",
-        "enabled",
-        "visible",
-        "type",
-        "icon-name",
-        "toggle-type",
-        "toggle-state",
-        "children-display",
-    ];
+    let (revision, root) = fetch_layout(proxy, 0).await?;
+    Ok(CachedMenu {
+        bus_name: bus_name.to_string(),
+        menu_path: menu_path.to_string(),
+        revision,
+        root,
+    })
+}
 
-    let (_revision, layout) = proxy.get_layout(0, -1, property_names).await?;
+/// Fetch menu items from a DBusMenu interface
+pub async fn fetch_menu(
+    connection: &zbus::Connection,
+    bus_name: &str,
+    menu_path: &str,
+) -> anyhow::Result<Vec<MenuItem>> {
+    let proxy = menu_proxy(connection, bus_name, menu_path).await?;
+    let menu = fetch_menu_tree(&proxy, bus_name, menu_path).await?;
 
-    // Parse the root item and return its children (root itself is not displayed)
-    let root = parse_menu_item(layout, 0)?;
-    Ok(root.children)
+    // The root itself is not displayed
+    Ok(menu.visible_items())
 }
 
 /// Maximum menu nesting depth to prevent stack overflow from malicious menus
@@ -120,11 +229,7 @@ pub async fn activate_menu_item(
     menu_path: &str,
     item_id: i32,
 ) -> anyhow::Result<()> {
-    let proxy = DBusMenuProxy::builder(connection)
-        .destination(bus_name)?
-        .path(menu_path)?
-        .build()
-        .await?;
+    let proxy = menu_proxy(connection, bus_name, menu_path).await?;
 
     let timestamp = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -157,49 +262,7 @@ fn parse_menu_item(
 
     // Parse properties
     for (key, value) in properties {
-        match key.as_str() {
-            "label" => {
-                if let Ok(label) = <&str>::try_from(&*value) {
-                    // Remove mnemonic underscores (e.g., "_File" -> "File")
-                    item.label = label.replace('_', "");
-                }
-            }
-            "enabled" => {
-                if let Ok(enabled) = <bool>::try_from(&*value) {
-                    item.enabled = enabled;
-                }
-            }
-            "visible" => {
-                if let Ok(visible) = <bool>::try_from(&*value) {
-                    item.visible = visible;
-                }
-            }
-            "type" => {
-                if let Ok(item_type) = <&str>::try_from(&*value) {
-                    item.item_type = item_type.to_string();
-                }
-            }
-            "icon-name" => {
-                if let Ok(icon_name) = <&str>::try_from(&*value) {
-                    if !icon_name.is_empty() {
-                        item.icon_name = Some(icon_name.to_string());
-                    }
-                }
-            }
-            "toggle-type" => {
-                if let Ok(toggle_type) = <&str>::try_from(&*value) {
-                    if !toggle_type.is_empty() {
-                        item.toggle_type = Some(toggle_type.to_string());
-                    }
-                }
-            }
-            "toggle-state" => {
-                if let Ok(state) = <i32>::try_from(&*value) {
-                    item.toggle_state = state;
-                }
-            }
-            _ => {}
-        }
+        apply_property(&mut item, &key, &value);
     }
 
     // Parse children recursively (with depth limit)
@@ -209,10 +272,8 @@ fn parse_menu_item(
             child_value.clone(),
         ) {
             if let Ok(child_item) = parse_menu_item(child_struct, depth + 1) {
-                // Only include visible items
-                if child_item.visible {
-                    item.children.push(child_item);
-                }
+                // Hidden items are kept so property updates can reveal them
+                item.children.push(child_item);
             }
         }
     }
@@ -220,6 +281,86 @@ fn parse_menu_item(
     Ok(item)
 }
 
+/// Apply a single DBusMenu property to an item
+fn apply_property(item: &mut MenuItem, key: &str, value: &Value<'_>) {
+    match key {
+        "label" => {
+            if let Ok(label) = <&str>::try_from(value) {
+                // Remove mnemonic underscores (e.g., "_File" -> "File")
+                item.label = label.replace('_', "");
+            }
+        }
+        "enabled" => {
+            if let Ok(enabled) = <bool>::try_from(value) {
+                item.enabled = enabled;
+            }
+        }
+        "visible" => {
+            if let Ok(visible) = <bool>::try_from(value) {
+                item.visible = visible;
+            }
+        }
+        "type" => {
+            if let Ok(item_type) = <&str>::try_from(value) {
+                item.item_type = item_type.to_string();
+            }
+        }
+        "icon-name" => {
+            if let Ok(icon_name) = <&str>::try_from(value) {
+                item.icon_name = Some(icon_name.to_string()).filter(|s| !s.is_empty());
+            }
+        }
+        "toggle-type" => {
+            if let Ok(toggle_type) = <&str>::try_from(value) {
+                item.toggle_type = Some(toggle_type.to_string()).filter(|s| !s.is_empty());
+            }
+        }
+        "toggle-state" => {
+            if let Ok(state) = <i32>::try_from(value) {
+                item.toggle_state = state;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Reset a removed DBusMenu property to its default value
+fn reset_property(item: &mut MenuItem, key: &str) {
+    let defaults = MenuItem::default();
+    match key {
+        "label" => item.label = defaults.label,
+        "enabled" => item.enabled = defaults.enabled,
+        "visible" => item.visible = defaults.visible,
+        "type" => item.item_type = defaults.item_type,
+        "icon-name" => item.icon_name = defaults.icon_name,
+        "toggle-type" => item.toggle_type = defaults.toggle_type,
+        "toggle-state" => item.toggle_state = defaults.toggle_state,
+        _ => {}
+    }
+}
+
+/// Find an item by ID anywhere in the tree
+fn find_item_mut(item: &mut MenuItem, id: i32) -> Option<&mut MenuItem> {
+    if item.id == id {
+        return Some(item);
+    }
+    item.children
+        .iter_mut()
+        .find_map(|child| find_item_mut(child, id))
+}
+
+/// Copy a list of items, dropping hidden ones at every level
+fn visible_children(items: &[MenuItem]) -> Vec<MenuItem> {
+    items
+        .iter()
+        .filter(|item| item.visible)
+        .map(|item| MenuItem {
+            children: visible_children(&item.children),
+            ..item.clone()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -242,4 +383,110 @@ mod tests {
         let label = "Save _As...".replace('_', "");
         assert_eq!(label, "Save As...");
     }
+
+    fn owned(value: Value<'_>) -> OwnedValue {
+        value.try_to_owned().unwrap()
+    }
+
+    fn test_menu() -> CachedMenu {
+        let item = |id: i32, label: &str| MenuItem {
+            id,
+            label: label.to_string(),
+            ..Default::default()
+        };
+
+        let mut submenu = item(2, "More");
+        submenu.children = vec![item(3, "Nested"), MenuItem {
+            visible: false,
+            ..item(4, "Hidden")
+        }];
+
+        CachedMenu {
+            bus_name: ":1.42".to_string(),
+            menu_path: "/MenuBar".to_string(),
+            revision: 1,
+            root: MenuItem {
+                children: vec![item(1, "Open"), submenu],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_visible_items_filters_nested_hidden() {
+        let menu = test_menu();
+        let items = menu.visible_items();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[1].children.len(), 1);
+        assert_eq!(items[1].children[0].label, "Nested");
+    }
+
+    #[test]
+    fn test_apply_properties_updated() {
+        let mut menu = test_menu();
+
+        let mut props = HashMap::new();
+        props.insert("visible".to_string(), owned(Value::from(true)));
+        props.insert("label".to_string(), owned(Value::from("_Shown")));
+        let mut toggle = HashMap::new();
+        toggle.insert("toggle-type".to_string(), owned(Value::from("checkmark")));
+        toggle.insert("toggle-state".to_string(), owned(Value::from(1i32)));
+
+        let changed = menu.apply_properties_updated(
+            vec![(4, props), (1, toggle), (99, HashMap::new())],
+            vec![(3, vec!["label".to_string()])],
+        );
+        assert!(changed);
+
+        let items = menu.visible_items();
+        assert_eq!(items[0].toggle_type.as_deref(), Some("checkmark"));
+        assert_eq!(items[0].toggle_state, 1);
+        assert_eq!(items[1].children.len(), 2);
+        assert_eq!(items[1].children[0].label, "");
+        assert_eq!(items[1].children[1].label, "Shown");
+    }
+
+    #[test]
+    fn test_apply_properties_updated_unknown_item() {
+        let mut menu = test_menu();
+        let changed = menu.apply_properties_updated(vec![(99, HashMap::new())], Vec::new());
+        assert!(!changed);
+    }
+
+    #[test]
+    fn test_replace_subtree() {
+        let mut menu = test_menu();
+        let replacement = MenuItem {
+            id: 2,
+            label: "More".to_string(),
+            children: vec![MenuItem {
+                id: 5,
+                label: "Replaced".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(menu.replace_subtree(7, replacement), Some(true));
+        assert_eq!(menu.revision, 7);
+        let items = menu.visible_items();
+        assert_eq!(items[1].children.len(), 1);
+        assert_eq!(items[1].children[0].id, 5);
+
+        let unknown = MenuItem {
+            id: 42,
+            ..Default::default()
+        };
+        assert_eq!(menu.replace_subtree(8, unknown), None);
+        assert_eq!(menu.revision, 7);
+    }
+
+    #[test]
+    fn test_replace_subtree_unchanged() {
+        let mut menu = test_menu();
+        let same = menu.root.children[1].clone();
+
+        assert_eq!(menu.replace_subtree(9, same), Some(false));
+        assert_eq!(menu.revision, 9);
+    }
 }
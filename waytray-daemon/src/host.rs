@@ -5,7 +5,11 @@
 //! listens for item registration/unregistration signals.
 
 use futures::StreamExt;
+use std::collections::HashMap;
 use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::RwLock;
+use tokio::task::AbortHandle;
 use zbus::connection::Connection;
 use zbus::fdo::DBusProxy;
 use zbus::names::WellKnownName;
@@ -14,7 +18,17 @@ use zbus::zvariant::{OwnedValue, Value};
 
 use crate::cache::ItemCache;
 use crate::dbus::HOST_BUS_NAME_PREFIX;
-use crate::{ItemCategory, ItemStatus, TrayItem};
+use crate::dbusmenu::{CachedMenu, DBusMenuProxy};
+use crate::{CacheEvent, ItemCategory, ItemStatus, TrayItem};
+
+/// Menu trees kept up to date from DBusMenu signals, indexed by item ID
+type MenuCache = Arc<RwLock<HashMap<String, TrackedMenu>>>;
+
+/// A cached menu together with the task that keeps it updated
+struct TrackedMenu {
+    menu: CachedMenu,
+    tracker: AbortHandle,
+}
 
 /// Proxy for communicating with the StatusNotifierWatcher
 #[proxy(
@@ -67,6 +81,18 @@ trait StatusNotifierItem {
     #[zbus(property)]
     fn icon_theme_path(&self) -> zbus::Result<String>;
 
+    #[zbus(property)]
+    fn attention_icon_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn attention_icon_pixmap(&self) -> zbus::Result<Vec<(i32, i32, Vec<u8>)>>;
+
+    #[zbus(property)]
+    fn overlay_icon_name(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn overlay_icon_pixmap(&self) -> zbus::Result<Vec<(i32, i32, Vec<u8>)>>;
+
     #[zbus(property, name = "ToolTip")]
     fn tool_tip(&self) -> zbus::Result<OwnedValue>;
 
@@ -82,6 +108,12 @@ trait StatusNotifierItem {
     #[zbus(signal)]
     fn new_icon(&self) -> zbus::Result<()>;
 
+    #[zbus(signal)]
+    fn new_attention_icon(&self) -> zbus::Result<()>;
+
+    #[zbus(signal)]
+    fn new_overlay_icon(&self) -> zbus::Result<()>;
+
     #[zbus(signal)]
     fn new_status(&self, status: String) -> zbus::Result<()>;
 
@@ -93,6 +125,7 @@ trait StatusNotifierItem {
 pub struct Host {
     connection: Connection,
     cache: Arc<ItemCache>,
+    menus: MenuCache,
     host_name: String,
 }
 
@@ -109,6 +142,7 @@ impl Host {
         let host = Self {
             connection,
             cache,
+            menus: Arc::new(RwLock::new(HashMap::new())),
             host_name,
         };
 
@@ -151,6 +185,7 @@ impl Host {
 
         let cache_for_add = cache.clone();
         let connection_for_add = connection.clone();
+        let menus_for_add = self.menus.clone();
 
         // Spawn task to handle item registration
         tokio::spawn(async move {
@@ -162,6 +197,7 @@ impl Host {
                     let host = Host {
                         connection: connection_for_add.clone(),
                         cache: cache_for_add.clone(),
+                        menus: menus_for_add.clone(),
                         host_name: String::new(), // Not used for adding items
                     };
 
@@ -338,6 +374,67 @@ impl Host {
             }
         });
 
+        // Handle NewAttentionIcon and NewOverlayIcon signals
+        let mut new_attention_icon = proxy.receive_new_attention_icon().await?;
+        let mut new_overlay_icon = proxy.receive_new_overlay_icon().await?;
+        let cache_extra = cache.clone();
+        let service_extra = service.clone();
+        let connection_extra = connection.clone();
+        let bus_name_extra = bus_name.clone();
+        let object_path_extra = object_path.clone();
+
+        tokio::spawn(async move {
+            loop {
+                let is_attention = tokio::select! {
+                    Some(_) = new_attention_icon.next() => true,
+                    Some(_) = new_overlay_icon.next() => false,
+                    else => break,
+                };
+
+                // Check if item still exists in cache, exit if removed
+                if cache_extra.get(&service_extra).await.is_none() {
+                    tracing::debug!(
+                        "Item {} removed, stopping attention/overlay icon signal handler",
+                        service_extra
+                    );
+                    break;
+                }
+
+                let proxy = match StatusNotifierItemProxy::builder(&connection_extra)
+                    .destination(bus_name_extra.as_str())
+                    .and_then(|b| b.path(object_path_extra.as_str()))
+                {
+                    Ok(builder) => match builder.build().await {
+                        Ok(p) => p,
+                        Err(e) => {
+                            tracing::debug!("Failed to build proxy for {}: {}", service_extra, e);
+                            continue;
+                        }
+                    },
+                    Err(e) => {
+                        tracing::debug!(
+                            "Failed to create proxy builder for {}: {}",
+                            service_extra,
+                            e
+                        );
+                        continue;
+                    }
+                };
+
+                if is_attention {
+                    let (name, pixmap, width, height) = fetch_attention_icon(&proxy).await;
+                    cache_extra
+                        .update_attention_icon(&service_extra, name, pixmap, width, height)
+                        .await;
+                } else {
+                    let (name, pixmap, width, height) = fetch_overlay_icon(&proxy).await;
+                    cache_extra
+                        .update_overlay_icon(&service_extra, name, pixmap, width, height)
+                        .await;
+                }
+            }
+        });
+
         // Handle NewStatus signal
         let mut new_status = proxy.receive_new_status().await?;
         let cache_status = cache.clone();
@@ -503,7 +600,16 @@ impl Host {
     }
 
     /// Get menu items for a tray item via DBusMenu
-    pub async fn get_menu_items(&self, id: &str) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
+    ///
+    /// The first request fetches the full layout and starts tracking the menu's
+    /// LayoutUpdated/ItemsPropertiesUpdated signals; later requests are served
+    /// from the cached tree. With `about_to_show`, a cached menu also lets the
+    /// application know it is being shown again, so it can update it.
+    pub async fn get_menu_items(
+        &self,
+        id: &str,
+        about_to_show: bool,
+    ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
         let item = self
             .cache
             .get(id)
@@ -515,7 +621,150 @@ impl Host {
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("Item has no menu: {}", id))?;
 
-        crate::dbusmenu::fetch_menu(&self.connection, &item.bus_name, menu_path).await
+        let cached = self
+            .menus
+            .read()
+            .await
+            .get(id)
+            .map(|tracked| &tracked.menu)
+            .filter(|menu| menu.bus_name == item.bus_name && &menu.menu_path == menu_path)
+            .map(CachedMenu::visible_items);
+
+        if let Some(items) = cached {
+            if about_to_show {
+                self.spawn_about_to_show(id, &item.bus_name, menu_path);
+            }
+            return Ok(items);
+        }
+
+        self.track_menu(id, &item.bus_name, menu_path).await
+    }
+
+    /// Fetch a menu tree, cache it and keep it updated from DBusMenu signals
+    ///
+    /// Replaces (and stops) any tracker already running for the item, e.g. after
+    /// it moved to a new menu path or when two first requests raced.
+    async fn track_menu(
+        &self,
+        id: &str,
+        bus_name: &str,
+        menu_path: &str,
+    ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
+        let proxy = crate::dbusmenu::menu_proxy(&self.connection, bus_name, menu_path).await?;
+
+        // Subscribe before fetching so no update between the two is lost
+        let mut layout_updated = proxy.receive_layout_updated().await?;
+        let mut properties_updated = proxy.receive_items_properties_updated().await?;
+        let mut cache_events = self.cache.subscribe();
+
+        let menu = crate::dbusmenu::fetch_menu_tree(&proxy, bus_name, menu_path).await?;
+        let items = menu.visible_items();
+
+        let cache = self.cache.clone();
+        let menus = self.menus.clone();
+        let task_id = id.to_string();
+
+        // Hold the lock while spawning so the entry and its tracker are swapped together
+        let mut tracked = self.menus.write().await;
+
+        let task = tokio::spawn(async move {
+            let id = task_id;
+            loop {
+                let changed = tokio::select! {
+                    Some(signal) = layout_updated.next() => {
+                        match signal.args() {
+                            Ok(args) => refresh_menu_layout(&proxy, &menus, &id, args.parent).await,
+                            Err(e) => {
+                                tracing::debug!("Failed to parse LayoutUpdated for {}: {}", id, e);
+                                false
+                            }
+                        }
+                    }
+                    Some(signal) = properties_updated.next() => {
+                        match signal.args() {
+                            Ok(args) => {
+                                let mut menus = menus.write().await;
+                                match menus.get_mut(&id) {
+                                    Some(tracked) => tracked
+                                        .menu
+                                        .apply_properties_updated(args.updated_props, args.removed_props),
+                                    None => false,
+                                }
+                            }
+                            Err(e) => {
+                                tracing::debug!(
+                                    "Failed to parse ItemsPropertiesUpdated for {}: {}",
+                                    id,
+                                    e
+                                );
+                                false
+                            }
+                        }
+                    }
+                    event = cache_events.recv() => {
+                        match event {
+                            Ok(CacheEvent::ItemRemoved(removed_id)) if removed_id == id => break,
+                            Err(RecvError::Closed) => break,
+                            Err(RecvError::Lagged(_)) => {
+                                // The removal may have been among the skipped events
+                                if cache.get(&id).await.is_none() {
+                                    break;
+                                }
+                                false
+                            }
+                            _ => false,
+                        }
+                    }
+                };
+
+                if changed {
+                    cache.notify_menu_changed(&id);
+                }
+            }
+
+            tracing::debug!("Item {} removed, stopping menu signal handler", id);
+            menus.write().await.remove(&id);
+        });
+
+        let previous = tracked.insert(
+            id.to_string(),
+            TrackedMenu {
+                menu,
+                tracker: task.abort_handle(),
+            },
+        );
+        if let Some(previous) = previous {
+            previous.tracker.abort();
+        }
+
+        Ok(items)
+    }
+
+    /// Let the application refresh a cached menu before it is shown again
+    fn spawn_about_to_show(&self, id: &str, bus_name: &str, menu_path: &str) {
+        let connection = self.connection.clone();
+        let cache = self.cache.clone();
+        let menus = self.menus.clone();
+        let id = id.to_string();
+        let bus_name = bus_name.to_string();
+        let menu_path = menu_path.to_string();
+
+        tokio::spawn(async move {
+            let proxy = match crate::dbusmenu::menu_proxy(&connection, &bus_name, &menu_path).await {
+                Ok(p) => p,
+                Err(e) => {
+                    tracing::debug!("Failed to build menu proxy for {}: {}", id, e);
+                    return;
+                }
+            };
+
+            // AboutToShow returns true when the application changed the layout
+            if let Ok(true) = proxy.about_to_show(0).await {
+                if refresh_menu_layout(&proxy, &menus, &id, 0).await {
+                    cache.notify_menu_changed(&id);
+                }
+            }
+        });
     }
 
     /// Activate a menu item by sending a "clicked" event
@@ -541,6 +790,48 @@ impl Host {
     }
 }
 
+/// Refetch the part of a cached menu below `parent` after a layout change.
+/// Returns true if the cached tree actually changed.
+async fn refresh_menu_layout(
+    proxy: &DBusMenuProxy<'_>,
+    menus: &MenuCache,
+    id: &str,
+    parent: i32,
+) -> bool {
+    let (revision, subtree) = match crate::dbusmenu::fetch_layout(proxy, parent).await {
+        Ok(layout) => layout,
+        Err(e) => {
+            tracing::debug!("Failed to fetch menu layout for {}: {}", id, e);
+            return false;
+        }
+    };
+
+    {
+        let mut menus = menus.write().await;
+        let Some(tracked) = menus.get_mut(id) else {
+            return false;
+        };
+        if let Some(changed) = tracked.menu.replace_subtree(revision, subtree) {
+            return changed;
+        }
+    }
+
+    // The parent is not in our tree (it was added since), so refetch everything
+    match crate::dbusmenu::fetch_layout(proxy, 0).await {
+        Ok((revision, root)) => {
+            let mut menus = menus.write().await;
+            menus
+                .get_mut(id)
+                .and_then(|tracked| tracked.menu.replace_subtree(revision, root))
+                .unwrap_or(false)
+        }
+        Err(e) => {
+            tracing::debug!("Failed to fetch menu layout for {}: {}", id, e);
+            false
+        }
+    }
+}
+
 /// Parse a service string into (bus_name, object_path)
 ///
 /// Service strings can be in several formats:
@@ -596,8 +887,14 @@ async fn fetch_item_properties(
     bus_name: &str,
     object_path: &str,
 ) -> anyhow::Result<TrayItem> {
-    let id = proxy.id().await.unwrap_or_else(|_| service.to_string());
-    let title = proxy.title().await.unwrap_or_else(|_| id.clone());
+    let sni_id = proxy.id().await.unwrap_or_default();
+    let title = proxy.title().await.ok().unwrap_or_else(|| {
+        if sni_id.is_empty() {
+            service.to_string()
+        } else {
+            sni_id.clone()
+        }
+    });
     let status_str = proxy
         .status()
         .await
@@ -614,6 +911,11 @@ async fn fetch_item_properties(
         fetch_icon_pixmap(proxy).await
     };
 
+    let (attention_icon_name, attention_icon_pixmap, attention_icon_width, attention_icon_height) =
+        fetch_attention_icon(proxy).await;
+    let (overlay_icon_name, overlay_icon_pixmap, overlay_icon_width, overlay_icon_height) =
+        fetch_overlay_icon(proxy).await;
+
     let tooltip = fetch_tooltip(proxy).await;
 
     let menu_path = proxy.menu().await.ok().map(|p| p.to_string());
@@ -622,6 +924,7 @@ async fn fetch_item_properties(
 
     Ok(TrayItem {
         id: service.to_string(),
+        sni_id,
         bus_name: bus_name.to_string(),
         object_path: object_path.to_string(),
         title,
@@ -629,6 +932,14 @@ async fn fetch_item_properties(
         icon_pixmap,
         icon_width,
         icon_height,
+        attention_icon_name,
+        attention_icon_pixmap,
+        attention_icon_width,
+        attention_icon_height,
+        overlay_icon_name,
+        overlay_icon_pixmap,
+        overlay_icon_width,
+        overlay_icon_height,
         tooltip,
         status: ItemStatus::from_str(&status_str),
         has_menu,
@@ -641,24 +952,59 @@ async fn fetch_item_properties(
 /// Fetch icon pixmap from an item
 async fn fetch_icon_pixmap(proxy: &StatusNotifierItemProxy<'_>) -> (Option<Vec<u8>>, u32, u32) {
     match proxy.icon_pixmap().await {
-        Ok(pixmaps) if !pixmaps.is_empty() => {
-            // Keep only sane positive dimensions and pick the largest by area.
-            // Use i64 arithmetic to avoid overflow in debug builds.
-            let largest = pixmaps
-                .into_iter()
-                .filter(|(w, h, _)| *w > 0 && *h > 0)
-                .max_by_key(|(w, h, _)| (*w as i64).saturating_mul(*h as i64));
-
-            if let Some((width, height, data)) = largest {
-                (Some(data), width as u32, height as u32)
-            } else {
-                (None, 0, 0)
-            }
-        }
+        Ok(pixmaps) => largest_pixmap(pixmaps),
         _ => (None, 0, 0),
     }
 }
 
+/// Fetch the attention icon (name, pixmap, width, height) from an item
+async fn fetch_attention_icon(
+    proxy: &StatusNotifierItemProxy<'_>,
+) -> (Option<String>, Option<Vec<u8>>, u32, u32) {
+    let name = proxy.attention_icon_name().await.ok().filter(|s| !s.is_empty());
+    let (pixmap, width, height) = if name.is_some() {
+        (None, 0, 0)
+    } else {
+        match proxy.attention_icon_pixmap().await {
+            Ok(pixmaps) => largest_pixmap(pixmaps),
+            _ => (None, 0, 0),
+        }
+    };
+    (name, pixmap, width, height)
+}
+
+/// Fetch the overlay icon (name, pixmap, width, height) from an item
+async fn fetch_overlay_icon(
+    proxy: &StatusNotifierItemProxy<'_>,
+) -> (Option<String>, Option<Vec<u8>>, u32, u32) {
+    let name = proxy.overlay_icon_name().await.ok().filter(|s| !s.is_empty());
+    let (pixmap, width, height) = if name.is_some() {
+        (None, 0, 0)
+    } else {
+        match proxy.overlay_icon_pixmap().await {
+            Ok(pixmaps) => largest_pixmap(pixmaps),
+            _ => (None, 0, 0),
+        }
+    };
+    (name, pixmap, width, height)
+}
+
+/// Pick the largest pixmap from an SNI pixmap list
+fn largest_pixmap(pixmaps: Vec<(i32, i32, Vec<u8>)>) -> (Option<Vec<u8>>, u32, u32) {
+    // Keep only sane positive dimensions and pick the largest by area.
+    // Use i64 arithmetic to avoid overflow in debug builds.
+    let largest = pixmaps
+        .into_iter()
+        .filter(|(w, h, _)| *w > 0 && *h > 0)
+        .max_by_key(|(w, h, _)| (*w as i64).saturating_mul(*h as i64));
+
+    if let Some((width, height, data)) = largest {
+        (Some(data), width as u32, height as u32)
+    } else {
+        (None, 0, 0)
+    }
+}
+
 /// Fetch tooltip from an item
 async fn fetch_tooltip(proxy: &StatusNotifierItemProxy<'_>) -> Option<String> {
     // ToolTip is a complex type: (icon_name: s, icon_pixmap: a(iiay), title: s, description: s)
@@ -767,6 +1113,20 @@ mod tests {
         assert_eq!(path, "/StatusNotifierItem");
     }
 
+    #[test]
+    fn test_largest_pixmap_picks_biggest_valid() {
+        let pixmaps = vec![
+            (16, 16, vec![1; 16 * 16 * 4]),
+            (-1, 64, vec![2]),
+            (32, 32, vec![3; 32 * 32 * 4]),
+        ];
+        let (data, width, height) = largest_pixmap(pixmaps);
+        assert_eq!((width, height), (32, 32));
+        assert_eq!(data.unwrap()[0], 3);
+
+        assert_eq!(largest_pixmap(Vec::new()), (None, 0, 0));
+    }
+
     #[test]
     fn test_parse_service_string_unique_name_only() {
         // Just a unique bus name, no path
@@ -6,6 +6,8 @@
 //! The daemon now supports a modular architecture where different data sources (tray items,
 //! battery status, system info, etc.) are implemented as modules.
 
+pub mod alerts;
+pub mod battery_history;
 pub mod cache;
 pub mod config;
 pub mod config_watcher;
@@ -13,13 +15,17 @@ pub mod dbus_service;
 pub mod dbusmenu;
 pub mod host;
 pub mod modules;
+pub mod networkmanager;
 pub mod notifications;
+pub mod pulse;
+pub mod sound;
+pub mod upower;
 pub mod watcher;
 
 // Re-export commonly used types
 pub use config::Config;
 pub use dbusmenu::MenuItem;
-pub use modules::{ItemAction, Module, ModuleContext, ModuleEvent, ModuleInfo, ModuleItem, ModuleRegistry, Urgency};
+pub use modules::{ItemAction, ItemIcon, ItemValue, Metric, Module, ModuleContext, ModuleEvent, ModuleInfo, ModuleItem, ModuleRegistry, Urgency, ValueKind};
 
 use serde::{Deserialize, Serialize};
 
@@ -28,6 +34,8 @@ use serde::{Deserialize, Serialize};
 pub struct TrayItem {
     /// Unique identifier for this item (typically bus_name + object_path)
     pub id: String,
+    /// Application-provided `Id` property (stable across restarts)
+    pub sni_id: String,
     /// D-Bus bus name of the application
     pub bus_name: String,
     /// D-Bus object path for the StatusNotifierItem
@@ -42,6 +50,22 @@ pub struct TrayItem {
     pub icon_width: u32,
     /// Height of the icon pixmap
     pub icon_height: u32,
+    /// Icon name shown while the item needs attention
+    pub attention_icon_name: Option<String>,
+    /// Raw ARGB32 attention icon pixmap data
+    pub attention_icon_pixmap: Option<Vec<u8>>,
+    /// Width of the attention icon pixmap
+    pub attention_icon_width: u32,
+    /// Height of the attention icon pixmap
+    pub attention_icon_height: u32,
+    /// Icon name drawn over the main icon
+    pub overlay_icon_name: Option<String>,
+    /// Raw ARGB32 overlay icon pixmap data
+    pub overlay_icon_pixmap: Option<Vec<u8>>,
+    /// Width of the overlay icon pixmap
+    pub overlay_icon_width: u32,
+    /// Height of the overlay icon pixmap
+    pub overlay_icon_height: u32,
     /// Tooltip text
     pub tooltip: Option<String>,
     /// Current status of the item
@@ -86,8 +110,8 @@ impl ItemStatus {
     }
 }
 
-/// Category of a tray item
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// Category of a tray item (declared in display order when sorting by category)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub enum ItemCategory {
     /// Application status or notifications
     #[default]
@@ -109,6 +133,15 @@ impl ItemCategory {
             _ => ItemCategory::ApplicationStatus,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemCategory::ApplicationStatus => "ApplicationStatus",
+            ItemCategory::Communications => "Communications",
+            ItemCategory::SystemServices => "SystemServices",
+            ItemCategory::Hardware => "Hardware",
+        }
+    }
 }
 
 /// Events emitted by the item cache
@@ -120,6 +153,8 @@ pub enum CacheEvent {
     ItemRemoved(String),
     /// An item was updated
     ItemUpdated(String),
+    /// An item's menu layout or properties changed
+    MenuUpdated(String),
 }
 
 /// D-Bus well-known names and paths
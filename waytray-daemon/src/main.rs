@@ -7,6 +7,8 @@
 //! Modules can be dynamically loaded and unloaded based on configuration changes.
 
 use std::sync::Arc;
+use sd_notify::NotifyState;
+use tokio::signal::unix::{signal, SignalKind};
 use tracing_subscriber::EnvFilter;
 use zbus::connection::Connection;
 
@@ -89,13 +91,49 @@ async fn main() -> anyhow::Result<()> {
 
     tracing::info!("WayTray daemon is running");
 
-    // Keep running until interrupted
-    tokio::signal::ctrl_c().await?;
+    // Tell systemd we're ready (no-op when not started with Type=notify)
+    if let Err(e) = sd_notify::notify(false, &[NotifyState::Ready]) {
+        tracing::debug!("Failed to notify systemd of readiness: {}", e);
+    }
+
+    // Keep running until interrupted or terminated, reloading on SIGHUP
+    wait_for_shutdown(&registry).await?;
 
     tracing::info!("Shutting down WayTray daemon");
+    let _ = sd_notify::notify(false, &[NotifyState::Stopping]);
+
+    // Stop modules so they can clean up (e.g. kill watch scripts)
+    registry.stop_all().await;
+
     Ok(())
 }
 
+/// Wait for SIGINT or SIGTERM, reloading the config whenever SIGHUP arrives
+async fn wait_for_shutdown(registry: &ModuleRegistry) -> anyhow::Result<()> {
+    let mut sigterm = signal(SignalKind::terminate())?;
+    let mut sighup = signal(SignalKind::hangup())?;
+
+    loop {
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => {
+                result?;
+                tracing::info!("Received SIGINT");
+                return Ok(());
+            }
+            _ = sigterm.recv() => {
+                tracing::info!("Received SIGTERM");
+                return Ok(());
+            }
+            _ = sighup.recv() => {
+                tracing::info!("Received SIGHUP, reloading config...");
+                if let Err(e) = config_watcher::reload_now(registry).await {
+                    tracing::error!("Failed to reload config: {}", e);
+                }
+            }
+        }
+    }
+}
+
 /// Register all module factories with the registry
 fn register_module_factories(registry: &mut ModuleRegistry) {
     // Tray module factory
@@ -1,21 +1,41 @@
 //! Battery module - displays battery status using UPower D-Bus interface
+//!
+//! Shows one item per UPower device: laptop batteries and UPSes as well as
+//! wireless peripherals such as mice, keyboards and headsets. The first laptop
+//! battery keeps the `battery:status` item id; other devices get
+//! `battery:<device>` ids.
+//!
+//! Updates are driven by UPower signals (device property changes, devices
+//! being added or removed, and AC plug/unplug) rather than polling.
+//!
+//! Laptop batteries also show their health and UPower's 24 hour history, and
+//! time remaining is estimated from an on-disk energy history
+//! (see [`crate::battery_history`]) rather than UPower's jumpy `TimeToEmpty`.
 
-use std::path::Path;
+use std::collections::HashMap;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use async_trait::async_trait;
-use gstreamer as gst;
-use gstreamer::prelude::*;
-use tokio::sync::RwLock;
+use futures::stream::SelectAll;
+use futures::StreamExt;
+use tokio::sync::{Notify, RwLock};
+use zbus::zvariant::OwnedObjectPath;
 use zbus::Connection;
-use zbus::proxy;
 
+use crate::battery_history::{BatteryHistory, EnergySample};
 use crate::config::BatteryModuleConfig;
-use super::{Module, ModuleContext, ModuleItem, Urgency};
+use crate::dbusmenu::MenuItem;
+use crate::sound::play_sound;
+use crate::upower::{self, DeviceKind, HistoryPoint, PowerDevice, UPowerProxy};
+use crate::ItemStatus;
+use super::{ItemAction, ItemIcon, ItemValue, Metric, Module, ModuleContext, ModuleItem, Urgency};
 
-/// Global flag to track if GStreamer has been initialized
-static GST_INITIALIZED: AtomicBool = AtomicBool::new(false);
+/// Period covered by the history summaries
+const HISTORY_SPAN_SECS: u32 = 24 * 3600;
+/// Number of history points to request
+const HISTORY_RESOLUTION: u32 = 150;
+/// How long fetched history summaries are reused for the tooltip
+const DETAILS_MAX_AGE: Duration = Duration::from_secs(600);
 
 /// UPower device states
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,143 +105,49 @@ impl BatteryState {
     }
 }
 
-/// Proxy for the UPower DisplayDevice
-#[proxy(
-    interface = "org.freedesktop.UPower.Device",
-    default_service = "org.freedesktop.UPower",
-    default_path = "/org/freedesktop/UPower/devices/DisplayDevice"
-)]
-trait UPowerDevice {
-    #[zbus(property)]
-    fn percentage(&self) -> zbus::Result<f64>;
-
-    #[zbus(property)]
-    fn state(&self) -> zbus::Result<u32>;
-
-    #[zbus(property)]
-    fn is_present(&self) -> zbus::Result<bool>;
-
-    #[zbus(property)]
-    fn time_to_empty(&self) -> zbus::Result<i64>;
-
-    #[zbus(property)]
-    fn time_to_full(&self) -> zbus::Result<i64>;
-
-    #[zbus(property, name = "Type")]
-    fn device_type(&self) -> zbus::Result<u32>;
+/// Average and peak power over a period, in watts
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct RateSummary {
+    average: f64,
+    peak: f64,
 }
 
-/// Initialize GStreamer if not already done
-fn ensure_gst_init() {
-    if !GST_INITIALIZED.swap(true, Ordering::SeqCst) {
-        if let Err(e) = gst::init() {
-            tracing::error!("Failed to initialize GStreamer: {}", e);
-            GST_INITIALIZED.store(false, Ordering::SeqCst);
-        }
-    }
+/// Summaries of UPower's history of a laptop battery
+#[derive(Debug, Clone, Default, PartialEq)]
+struct BatteryDetails {
+    /// Discharge power over the last 24 hours
+    discharge: Option<RateSummary>,
+    /// Lowest and highest charge percentage over the last 24 hours
+    charge_range: Option<(f64, f64)>,
+    /// Mean accuracy of UPower's discharge profile, in percent
+    profile_accuracy: Option<f64>,
 }
 
-/// Validate and expand a sound file path
-/// Returns None if the path is invalid or not a regular file
-fn validate_sound_path(path: &str) -> Option<String> {
-    // Expand ~ to home directory
-    let expanded = if path.starts_with("~/") {
-        let home = dirs::home_dir()?;
-        home.join(&path[2..])
-    } else {
-        Path::new(path).to_path_buf()
-    };
-
-    // Canonicalize to resolve any .. or symlinks (also verifies file exists)
-    let canonical = match expanded.canonicalize() {
-        Ok(p) => p,
-        Err(e) => {
-            tracing::warn!("Cannot resolve sound path '{}': {}", path, e);
-            return None;
-        }
-    };
-
-    // Ensure it's a regular file, not a directory or special file
-    if !canonical.is_file() {
-        tracing::warn!("Sound path '{}' is not a regular file", path);
-        return None;
-    }
-
-    Some(canonical.to_string_lossy().to_string())
-}
-
-/// Play a sound file using GStreamer (fire and forget)
-fn play_sound(path: &str) {
-    ensure_gst_init();
-
-    // Validate and expand the path (also handles path traversal prevention)
-    let expanded_path = match validate_sound_path(path) {
-        Some(p) => p,
-        None => return,
-    };
-
-    // Create playbin element - GStreamer will handle file not found errors
-    let uri = format!("file://{}", expanded_path);
-    let playbin = match gst::ElementFactory::make("playbin")
-        .property("uri", &uri)
-        .build()
-    {
-        Ok(p) => p,
-        Err(e) => {
-            tracing::error!("Failed to create playbin: {}", e);
-            return;
-        }
-    };
-
-    // Start playback
-    if let Err(e) = playbin.set_state(gst::State::Playing) {
-        tracing::error!("Failed to start sound playback: {}", e);
-        return;
-    }
-
-    // Spawn a task to wait for playback to finish and clean up
-    let playbin_weak = playbin.downgrade();
-    std::thread::spawn(move || {
-        let Some(playbin) = playbin_weak.upgrade() else {
-            return;
-        };
-
-        // Get the bus and wait for EOS or error
-        let bus = match playbin.bus() {
-            Some(b) => b,
-            None => {
-                let _ = playbin.set_state(gst::State::Null);
-                return;
-            }
-        };
-
-        for msg in bus.iter_timed(gst::ClockTime::from_seconds(30)) {
-            match msg.view() {
-                gst::MessageView::Eos(_) => break,
-                gst::MessageView::Error(err) => {
-                    tracing::error!(
-                        "Sound playback error: {} ({:?})",
-                        err.error(),
-                        err.debug()
-                    );
-                    break;
-                }
-                _ => {}
-            }
-        }
-
-        // Clean up
-        let _ = playbin.set_state(gst::State::Null);
-    });
+/// Which notifications were already sent for a device, so each fires once
+/// until the condition clears
+#[derive(Debug, Default, Clone, Copy)]
+struct NotifiedState {
+    low: bool,
+    critical: bool,
+    full: bool,
 }
 
 /// Battery module that displays battery status
 pub struct BatteryModule {
     config: RwLock<BatteryModuleConfig>,
     connection: RwLock<Option<Connection>>,
-    last_low_notification: RwLock<bool>,
-    last_critical_notification: RwLock<bool>,
-    last_full_notification: RwLock<bool>,
+    /// Notification state keyed by device object path
+    notified: RwLock<HashMap<String, NotifiedState>>,
+    /// Devices as last sent, to skip updates that change nothing
+    last_devices: RwLock<Option<Vec<PowerDevice>>>,
+    /// Woken on config reload, since kind filters change the watched devices
+    refresh: Notify,
+    /// Energy history used for time-remaining estimates
+    history: RwLock<Option<BatteryHistory>>,
+    /// History summaries keyed by device object path, with when they were fetched
+    details: RwLock<HashMap<String, (Instant, BatteryDetails)>>,
+    /// Devices of the items last sent, keyed by item id, for the detail menu
+    item_devices: RwLock<HashMap<String, PowerDevice>>,
 }
 
 impl BatteryModule {
@@ -229,49 +155,46 @@ impl BatteryModule {
         Self {
             config: RwLock::new(config),
             connection: RwLock::new(None),
-            last_low_notification: RwLock::new(false),
-            last_critical_notification: RwLock::new(false),
-            last_full_notification: RwLock::new(false),
+            notified: RwLock::new(HashMap::new()),
+            last_devices: RwLock::new(None),
+            refresh: Notify::new(),
+            history: RwLock::new(None),
+            details: RwLock::new(HashMap::new()),
+            item_devices: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Check whether the config shows devices of this kind
+    fn is_included(config: &BatteryModuleConfig, kind: DeviceKind) -> bool {
+        // The AC adapter has no charge to show
+        if kind == DeviceKind::LinePower {
+            return false;
         }
+        let name = kind.config_name();
+        (config.include_kinds.is_empty() || config.include_kinds.iter().any(|k| k == name))
+            && !config.exclude_kinds.iter().any(|k| k == name)
     }
 
-    async fn get_battery_info(&self) -> Option<(u8, BatteryState, i64)> {
+    /// Get the devices to show, laptop batteries first
+    async fn get_devices(&self) -> Vec<PowerDevice> {
         let conn_lock = self.connection.read().await;
-        let connection = conn_lock.as_ref()?;
+        let Some(connection) = conn_lock.as_ref() else {
+            return Vec::new();
+        };
 
-        let proxy = match UPowerDeviceProxy::new(connection).await {
-            Ok(p) => p,
+        let mut devices = match upower::list_devices(connection).await {
+            Ok(devices) => devices,
             Err(e) => {
-                tracing::debug!("Failed to create UPower proxy: {}", e);
-                return None;
+                tracing::debug!("Failed to enumerate UPower devices: {}", e);
+                return Vec::new();
             }
         };
+        drop(conn_lock);
 
-        // Check if battery is present
-        let is_present = proxy.is_present().await.unwrap_or(false);
-        if !is_present {
-            tracing::debug!("No battery present");
-            return None;
-        }
-
-        // Check device type (2 = Battery)
-        let device_type = proxy.device_type().await.unwrap_or(0);
-        if device_type != 2 {
-            tracing::debug!("DisplayDevice is not a battery (type={})", device_type);
-            return None;
-        }
-
-        let percentage = proxy.percentage().await.unwrap_or(0.0) as u8;
-        let state = BatteryState::from_u32(proxy.state().await.unwrap_or(0));
-
-        // Get time remaining based on state
-        let time_remaining = match state {
-            BatteryState::Charging => proxy.time_to_full().await.unwrap_or(0),
-            BatteryState::Discharging => proxy.time_to_empty().await.unwrap_or(0),
-            _ => 0,
-        };
-
-        Some((percentage, state, time_remaining))
+        let config = self.config.read().await;
+        devices.retain(|d| d.is_present && Self::is_included(&config, d.kind));
+        devices.sort_by(|a, b| b.power_supply.cmp(&a.power_supply).then_with(|| a.path.cmp(&b.path)));
+        devices
     }
 
     fn format_time(seconds: i64) -> String {
@@ -289,103 +212,534 @@ impl BatteryModule {
         }
     }
 
-    async fn create_module_item(&self, percentage: u8, state: BatteryState, time_remaining: i64) -> ModuleItem {
-        let label = format!("{}%", percentage);
+    /// Whether a device is the computer's own battery rather than a peripheral
+    fn is_system_battery(device: &PowerDevice) -> bool {
+        device.power_supply && device.kind == DeviceKind::Battery
+    }
+
+    /// Whether the device is running on its battery, for low/critical alerts
+    ///
+    /// Peripherals often report an unknown state while in use, so for those
+    /// anything but charging counts.
+    fn is_discharging(device: &PowerDevice, state: BatteryState) -> bool {
+        if device.power_supply {
+            state == BatteryState::Discharging
+        } else {
+            !matches!(
+                state,
+                BatteryState::Charging | BatteryState::FullyCharged | BatteryState::PendingCharge
+            )
+        }
+    }
 
-        let mut tooltip_parts = vec![
-            format!("Battery: {}%", percentage),
-            format!("Status: {}", state.as_str()),
-        ];
+    /// Time remaining line for the tooltip and menu, preferring the estimate
+    /// from the energy history over UPower's figure
+    fn time_line(device: &PowerDevice, estimate: Option<i64>) -> Option<String> {
+        let state = BatteryState::from_u32(device.state);
+        let (prefix, upower_time) = match state {
+            BatteryState::Charging => ("Time to full", device.time_to_full),
+            BatteryState::Discharging => ("Time remaining", device.time_to_empty),
+            _ => return None,
+        };
+        let time_str = Self::format_time(estimate.unwrap_or(upower_time));
+        if time_str.is_empty() {
+            None
+        } else {
+            Some(format!("{}: {}", prefix, time_str))
+        }
+    }
+
+    /// Health line, e.g. "Health: 88% (50.0 of 57.0 Wh)"
+    fn health_line(device: &PowerDevice) -> Option<String> {
+        let health = device.health?;
+        Some(format!(
+            "Health: {:.0}% ({:.1} of {:.1} Wh)",
+            health.capacity, health.energy_full, health.energy_full_design
+        ))
+    }
+
+    /// Summary line of the last 24 hours of discharging
+    fn discharge_line(details: &BatteryDetails) -> Option<String> {
+        let discharge = details.discharge?;
+        Some(format!(
+            "Last 24h: {:.1} W average discharge, {:.1} W peak",
+            discharge.average, discharge.peak
+        ))
+    }
+
+    /// Create the item for a device; `primary` marks the main laptop battery
+    fn create_module_item(
+        device: &PowerDevice,
+        primary: bool,
+        estimate: Option<i64>,
+        details: Option<&BatteryDetails>,
+    ) -> ModuleItem {
+        let percentage = device.percentage as u8;
+        let state = BatteryState::from_u32(device.state);
+        let system = Self::is_system_battery(device);
+
+        // Peripherals are named by type so they can be told apart in the bar
+        let label = if primary {
+            format!("{}%", percentage)
+        } else if system {
+            format!("{} {}%", device.native_path, percentage)
+        } else {
+            format!("{} {}%", device.kind.description(), percentage)
+        };
+
+        let mut tooltip_parts = Vec::new();
+        if !system {
+            tooltip_parts.push(format!("Device: {} ({})", device.display_name(), device.kind.description()));
+        }
+        tooltip_parts.push(format!("Battery: {}%", percentage));
+        if system || state != BatteryState::Unknown {
+            tooltip_parts.push(format!("Status: {}", state.as_str()));
+        }
+
+        tooltip_parts.extend(Self::time_line(device, estimate));
 
-        let time_str = Self::format_time(time_remaining);
-        if !time_str.is_empty() {
-            match state {
-                BatteryState::Charging => tooltip_parts.push(format!("Time to full: {}", time_str)),
-                BatteryState::Discharging => tooltip_parts.push(format!("Time remaining: {}", time_str)),
-                _ => {}
+        if device.energy_rate > 0.0 {
+            tooltip_parts.push(format!("Power: {:.1} W", device.energy_rate));
+        }
+        if let Some(threshold) = device.charge_threshold {
+            if threshold.enabled {
+                tooltip_parts.push(format!(
+                    "Charge limit: On ({}-{}%)",
+                    threshold.start, threshold.end
+                ));
+            } else {
+                tooltip_parts.push("Charge limit: Off".to_string());
             }
         }
+        tooltip_parts.extend(Self::health_line(device));
+        if let Some(cycles) = device.health.and_then(|h| h.charge_cycles) {
+            tooltip_parts.push(format!("Charge cycles: {}", cycles));
+        }
+        tooltip_parts.extend(details.and_then(Self::discharge_line));
 
         let tooltip = tooltip_parts.join("\n");
-        let icon_name = state.icon_name(percentage);
+        let charge_icon = state.icon_name(percentage);
+
+        // Peripherals show their own icon with the charge level as a badge
+        let (icon_name, overlay_icon) = match device.kind.icon_name() {
+            Some(icon) => (
+                icon,
+                ItemIcon::from_parts(Some(charge_icon.to_string()), None, 0, 0),
+            ),
+            None => (charge_icon, None),
+        };
+
+        let id = if primary {
+            "battery:status".to_string()
+        } else {
+            format!("battery:{}", device.short_id())
+        };
+
+        let actions = if system {
+            vec![ItemAction::default_action("context_menu", "Battery Details")]
+        } else {
+            Vec::new()
+        };
 
         ModuleItem {
-            id: "battery:status".to_string(),
+            id,
             module: "battery".to_string(),
             label,
             icon_name: Some(icon_name.to_string()),
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
-            actions: Vec::new(), // Battery has no actions
+            value: Some(ItemValue::percentage(device.percentage)),
+            actions,
         }
     }
 
-    async fn check_and_send_notifications(&self, ctx: &ModuleContext, percentage: u8, state: BatteryState) {
+    /// Create the items for all devices
+    fn create_items(
+        devices: &[PowerDevice],
+        estimates: &HashMap<String, i64>,
+        details: &HashMap<String, BatteryDetails>,
+    ) -> Vec<ModuleItem> {
+        let primary = devices.iter().position(Self::is_system_battery);
+        devices
+            .iter()
+            .enumerate()
+            .map(|(i, device)| {
+                Self::create_module_item(
+                    device,
+                    Some(i) == primary,
+                    estimates.get(&device.path).copied(),
+                    details.get(&device.path),
+                )
+            })
+            .collect()
+    }
+
+    /// Metrics for alert rules, named after the items: `battery.percentage`
+    /// and `battery.discharging` for the main battery, and
+    /// `battery.<device>.percentage` for the others
+    fn create_metrics(items: &[ModuleItem], devices: &[PowerDevice]) -> Vec<Metric> {
+        let mut metrics = Vec::new();
+        for (item, device) in items.iter().zip(devices) {
+            let state = BatteryState::from_u32(device.state);
+            match item.id.strip_prefix("battery:") {
+                Some("status") => {
+                    metrics.push(Metric::new("battery.percentage", device.percentage));
+                    metrics.push(Metric::flag(
+                        "battery.discharging",
+                        Self::is_discharging(device, state),
+                    ));
+                }
+                Some(id) => metrics.push(Metric::new(format!("battery.{}.percentage", id), device.percentage)),
+                None => {}
+            }
+        }
+        metrics
+    }
+
+    /// Summarize discharge power from UPower's "rate" history
+    fn discharge_summary(points: &[HistoryPoint]) -> Option<RateSummary> {
+        let rates: Vec<f64> = points
+            .iter()
+            .filter(|p| BatteryState::from_u32(p.state) == BatteryState::Discharging && p.value > 0.0)
+            .map(|p| p.value)
+            .collect();
+        if rates.is_empty() {
+            return None;
+        }
+        Some(RateSummary {
+            average: rates.iter().sum::<f64>() / rates.len() as f64,
+            peak: rates.iter().cloned().fold(0.0, f64::max),
+        })
+    }
+
+    /// Lowest and highest percentage in UPower's "charge" history
+    fn charge_range(points: &[HistoryPoint]) -> Option<(f64, f64)> {
+        let values = points.iter().map(|p| p.value).filter(|v| *v > 0.0);
+        let min = values.clone().fold(f64::INFINITY, f64::min);
+        let max = values.fold(0.0, f64::max);
+        (min.is_finite()).then_some((min, max))
+    }
+
+    /// Fetch the history summaries of a laptop battery
+    async fn fetch_details(connection: &Connection, device: &PowerDevice) -> BatteryDetails {
+        let history = |kind| upower::get_history(connection, &device.path, kind, HISTORY_SPAN_SECS, HISTORY_RESOLUTION);
+        let (rate, charge, statistics) = futures::join!(
+            history("rate"),
+            history("charge"),
+            upower::get_statistics(connection, &device.path, "discharging")
+        );
+
+        let profile_accuracy = statistics.ok().filter(|s| !s.is_empty()).map(|s| {
+            s.iter().map(|(_, accuracy)| accuracy).sum::<f64>() / s.len() as f64
+        });
+
+        BatteryDetails {
+            discharge: rate.ok().and_then(|points| Self::discharge_summary(&points)),
+            charge_range: charge.ok().and_then(|points| Self::charge_range(&points)),
+            profile_accuracy,
+        }
+    }
+
+    /// Get the history summaries of the laptop batteries, refetching stale ones
+    async fn battery_details(&self, devices: &[PowerDevice], force: bool) -> HashMap<String, BatteryDetails> {
+        let Some(connection) = self.connection.read().await.clone() else {
+            return HashMap::new();
+        };
+
+        let mut cache = self.details.write().await;
+        let mut details = HashMap::new();
+        for device in devices.iter().filter(|d| Self::is_system_battery(d)) {
+            let fresh = cache
+                .get(&device.path)
+                .filter(|(fetched, _)| !force && fetched.elapsed() < DETAILS_MAX_AGE)
+                .map(|(_, d)| d.clone());
+            let summary = match fresh {
+                Some(summary) => summary,
+                None => {
+                    let summary = Self::fetch_details(&connection, device).await;
+                    cache.insert(device.path.clone(), (Instant::now(), summary.clone()));
+                    summary
+                }
+            };
+            details.insert(device.path.clone(), summary);
+        }
+        details
+    }
+
+    /// Record energy samples of the laptop batteries and estimate their time remaining
+    async fn record_history(&self, devices: &[PowerDevice]) -> HashMap<String, i64> {
+        let mut estimates = HashMap::new();
+        let mut history_lock = self.history.write().await;
+        let Some(history) = history_lock.as_mut() else {
+            return estimates;
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut changed = false;
+        for device in devices.iter().filter(|d| Self::is_system_battery(d) && d.energy > 0.0) {
+            let charging = match BatteryState::from_u32(device.state) {
+                BatteryState::Charging => true,
+                BatteryState::Discharging => false,
+                _ => continue,
+            };
+            let sample = EnergySample {
+                time: now,
+                energy: device.energy,
+                charging,
+            };
+            changed |= history.record(&device.native_path, sample);
+
+            let energy_full = device.health.map_or(0.0, |h| h.energy_full);
+            if charging && energy_full <= 0.0 {
+                continue;
+            }
+            if let Some(secs) = history.estimate(&device.native_path, sample, energy_full) {
+                estimates.insert(device.path.clone(), secs);
+            }
+        }
+
+        if changed {
+            if let Err(e) = history.save() {
+                tracing::warn!("Failed to save battery history: {}", e);
+            }
+        }
+        estimates
+    }
+
+    /// Build the detail menu of a laptop battery as a list of read-only entries
+    fn build_details_menu(device: &PowerDevice, estimate: Option<i64>, details: &BatteryDetails) -> Vec<MenuItem> {
+        let state = BatteryState::from_u32(device.state);
+        let mut lines = Vec::new();
+
+        let percentage = device.percentage as u8;
+        match device.health {
+            Some(health) if device.energy > 0.0 => lines.push(format!(
+                "Charge: {}% ({:.1} of {:.1} Wh)",
+                percentage, device.energy, health.energy_full
+            )),
+            _ => lines.push(format!("Charge: {}%", percentage)),
+        }
+        lines.push(format!("Status: {}", state.as_str()));
+        if let Some(line) = Self::time_line(device, estimate) {
+            let source = if estimate.is_some() {
+                " (from recent use)"
+            } else {
+                ""
+            };
+            lines.push(format!("{}{}", line, source));
+        }
+        if device.energy_rate > 0.0 {
+            lines.push(format!("Power: {:.1} W", device.energy_rate));
+        }
+
+        if let Some(health) = device.health {
+            lines.push(format!("Health: {:.0}%", health.capacity));
+            lines.push(format!("Full charge capacity: {:.1} Wh", health.energy_full));
+            lines.push(format!("Design capacity: {:.1} Wh", health.energy_full_design));
+            if let Some(cycles) = health.charge_cycles {
+                lines.push(format!("Charge cycles: {}", cycles));
+            }
+        }
+        if let Some(threshold) = device.charge_threshold {
+            lines.push(if threshold.enabled {
+                format!("Charge limit: On ({}-{}%)", threshold.start, threshold.end)
+            } else {
+                "Charge limit: Off".to_string()
+            });
+        }
+
+        match details.discharge {
+            Some(discharge) => lines.push(format!(
+                "Last 24h discharge: {:.1} W average, {:.1} W peak",
+                discharge.average, discharge.peak
+            )),
+            None => lines.push("Last 24h discharge: no data".to_string()),
+        }
+        if let Some((min, max)) = details.charge_range {
+            lines.push(format!("Last 24h charge: {:.0}% to {:.0}%", min, max));
+        }
+        if let Some(accuracy) = details.profile_accuracy {
+            lines.push(format!("Discharge profile accuracy: {:.0}%", accuracy));
+        }
+
+        lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| MenuItem {
+                id: i as i32 + 1,
+                label,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    async fn check_and_send_notifications(&self, ctx: &ModuleContext, device: &PowerDevice) {
         let config = self.config.read().await;
+        let percentage = device.percentage as u8;
+        let state = BatteryState::from_u32(device.state);
+        let system = Self::is_system_battery(device);
+        let name = if system {
+            "Battery".to_string()
+        } else {
+            device.display_name()
+        };
+
+        let mut notified_map = self.notified.write().await;
+        let notified = notified_map.entry(device.path.clone()).or_default();
 
         // Check for fully charged notification
         if state == BatteryState::FullyCharged && config.notify_full_charge {
-            let already_notified = *self.last_full_notification.read().await;
-            if !already_notified {
-                ctx.send_notification(
-                    "Battery Fully Charged",
-                    "Battery is fully charged. You can unplug the charger.",
-                    Urgency::Low,
-                );
+            if !notified.full {
+                let body = if system {
+                    "Battery is fully charged. You can unplug the charger.".to_string()
+                } else {
+                    format!("{} is fully charged.", name)
+                };
+                ctx.send_notification("Battery Fully Charged", &body, Urgency::Low);
                 // Play sound if configured
                 if let Some(ref sound_path) = config.full_sound {
                     play_sound(sound_path);
                 }
-                *self.last_full_notification.write().await = true;
+                notified.full = true;
             }
         } else if state != BatteryState::FullyCharged {
             // Reset full notification flag when no longer fully charged
-            *self.last_full_notification.write().await = false;
+            notified.full = false;
         }
 
         // Only send low/critical notifications when discharging
-        if state != BatteryState::Discharging {
+        if !Self::is_discharging(device, state) {
             // Reset low/critical notification flags when not discharging
-            *self.last_low_notification.write().await = false;
-            *self.last_critical_notification.write().await = false;
+            notified.low = false;
+            notified.critical = false;
             return;
         }
 
         // Critical battery notification
         if percentage <= config.critical_threshold {
-            let already_notified = *self.last_critical_notification.read().await;
-            if !already_notified {
-                ctx.send_notification(
-                    "Critical Battery",
-                    &format!("Battery is at {}%. Connect charger immediately.", percentage),
-                    Urgency::Critical,
-                );
+            if !notified.critical {
+                let (summary, body) = if system {
+                    (
+                        "Critical Battery".to_string(),
+                        format!("Battery is at {}%. Connect charger immediately.", percentage),
+                    )
+                } else {
+                    (
+                        format!("{} Battery Critical", device.kind.description()),
+                        format!("{} is at {}%. Charge it now.", name, percentage),
+                    )
+                };
+                ctx.send_notification(&summary, &body, Urgency::Critical);
                 // Play sound if configured
                 if let Some(ref sound_path) = config.critical_sound {
                     play_sound(sound_path);
                 }
-                *self.last_critical_notification.write().await = true;
+                notified.critical = true;
             }
         }
         // Low battery notification
-        else if percentage <= config.low_threshold {
-            let already_notified = *self.last_low_notification.read().await;
-            if !already_notified {
-                ctx.send_notification(
-                    "Low Battery",
-                    &format!("Battery is at {}%. Consider connecting charger.", percentage),
-                    Urgency::Normal,
-                );
-                // Play sound if configured
-                if let Some(ref sound_path) = config.low_sound {
-                    play_sound(sound_path);
-                }
-                *self.last_low_notification.write().await = true;
+        else if percentage <= config.low_threshold && !notified.low {
+            let (summary, body) = if system {
+                (
+                    "Low Battery".to_string(),
+                    format!("Battery is at {}%. Consider connecting charger.", percentage),
+                )
+            } else {
+                (
+                    format!("{} Battery Low", device.kind.description()),
+                    format!("{} is at {}%. Charge it soon.", name, percentage),
+                )
+            };
+            ctx.send_notification(&summary, &body, Urgency::Normal);
+            // Play sound if configured
+            if let Some(ref sound_path) = config.low_sound {
+                play_sound(sound_path);
             }
+            notified.low = true;
         }
     }
+
+    /// Subscribe to property changes of the given devices
+    async fn watch_devices(
+        connection: &Connection,
+        devices: &[PowerDevice],
+    ) -> SelectAll<zbus::fdo::PropertiesChangedStream> {
+        let mut streams = SelectAll::new();
+        for device in devices {
+            match upower::receive_device_changes(connection, &device.path).await {
+                Ok(stream) => streams.push(stream),
+                Err(e) => tracing::debug!("Failed to watch UPower device {}: {}", device.path, e),
+            }
+        }
+        streams
+    }
+
+    /// Reread one device in place, false if it no longer belongs in the list
+    async fn refetch_device(
+        &self,
+        connection: &Connection,
+        path: &OwnedObjectPath,
+        devices: &mut [PowerDevice],
+    ) -> bool {
+        let Some(slot) = devices.iter_mut().find(|d| d.path == path.as_str()) else {
+            return false;
+        };
+        let device = match upower::fetch_device(connection, path).await {
+            Ok(device) => device,
+            Err(e) => {
+                tracing::debug!("Failed to read UPower device {}: {}", path.as_str(), e);
+                return false;
+            }
+        };
+        if !device.is_present || !Self::is_included(&*self.config.read().await, device.kind) {
+            return false;
+        }
+        *slot = device;
+        true
+    }
+
+    /// Send items and notifications for the devices if anything changed
+    async fn update(&self, ctx: &ModuleContext, devices: &[PowerDevice]) {
+        let estimates = self.record_history(devices).await;
+        {
+            let mut last = self.last_devices.write().await;
+            if last.as_deref() == Some(devices) {
+                return;
+            }
+            *last = Some(devices.to_vec());
+        }
+
+        self.details
+            .write()
+            .await
+            .retain(|path, _| devices.iter().any(|d| d.path == *path));
+        let details = self.battery_details(devices, false).await;
+        let items = Self::create_items(devices, &estimates, &details);
+        *self.item_devices.write().await = items
+            .iter()
+            .map(|item| item.id.clone())
+            .zip(devices.iter().cloned())
+            .collect();
+        let metrics = Self::create_metrics(&items, devices);
+        ctx.send_items("battery", items);
+        ctx.send_metrics("battery", metrics);
+
+        for device in devices {
+            self.check_and_send_notifications(ctx, device).await;
+        }
+
+        // Forget devices that went away so they alert again when they return
+        self.notified
+            .write()
+            .await
+            .retain(|path, _| devices.iter().any(|d| d.path == *path));
+    }
 }
 
 #[async_trait]
@@ -413,51 +767,118 @@ impl Module for BatteryModule {
             }
         };
 
-        *self.connection.write().await = Some(connection);
+        *self.connection.write().await = Some(connection.clone());
 
-        // Send initial state
-        if let Some((percentage, state, time_remaining)) = self.get_battery_info().await {
-            let item = self.create_module_item(percentage, state, time_remaining).await;
-            ctx.send_items("battery", vec![item]);
-            self.check_and_send_notifications(&ctx, percentage, state).await;
-        } else {
-            // No battery - send empty items
-            ctx.send_items("battery", vec![]);
-            tracing::info!("No battery detected");
-            return;
+        if let Some(path) = BatteryHistory::default_path() {
+            *self.history.write().await = Some(BatteryHistory::load(&path));
         }
 
-        // Poll for updates (UPower PropertiesChanged signals could be used instead,
-        // but polling is simpler and battery changes are infrequent)
-        let poll_interval = Duration::from_secs(30);
+        let upower = match UPowerProxy::new(&connection).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                tracing::error!("Failed to create UPower proxy: {}", e);
+                return;
+            }
+        };
+
+        // Peripherals come and go as they connect, pair and sleep
+        let (mut device_added, mut device_removed) = match futures::try_join!(
+            upower.receive_device_added(),
+            upower.receive_device_removed()
+        ) {
+            Ok(streams) => streams,
+            Err(e) => {
+                tracing::error!("Failed to subscribe to UPower device signals: {}", e);
+                return;
+            }
+        };
+        // AC plug/unplug, which devices report with a delay
+        let mut on_battery_changed = upower.receive_on_battery_changed().await;
 
         loop {
-            tokio::select! {
-                _ = ctx.cancelled() => break,
-                _ = tokio::time::sleep(poll_interval) => {
-                    if let Some((percentage, state, time_remaining)) = self.get_battery_info().await {
-                        let item = self.create_module_item(percentage, state, time_remaining).await;
-                        ctx.send_items("battery", vec![item]);
-                        self.check_and_send_notifications(&ctx, percentage, state).await;
+            let mut devices = self.get_devices().await;
+            let mut changes = Self::watch_devices(&connection, &devices).await;
+            self.update(&ctx, &devices).await;
+
+            // Refetch on property changes; resubscribe when the device set changes
+            loop {
+                tokio::select! {
+                    _ = ctx.cancelled() => return,
+                    Some(_) = device_added.next() => break,
+                    Some(_) = device_removed.next() => break,
+                    _ = self.refresh.notified() => break,
+                    Some(signal) = changes.next() => {
+                        // Only the device that reported the change needs rereading
+                        let Some(path) = signal.message().header().path().map(|p| p.to_owned().into()) else {
+                            continue;
+                        };
+                        if !self.refetch_device(&connection, &path, &mut devices).await {
+                            break;
+                        }
+                    }
+                    Some(_) = on_battery_changed.next() => {
+                        let current = self.get_devices().await;
+                        let same_devices = current.len() == devices.len()
+                            && current.iter().zip(&devices).all(|(a, b)| a.path == b.path);
+                        if !same_devices {
+                            break;
+                        }
+                        devices = current;
                     }
                 }
+
+                self.update(&ctx, &devices).await;
             }
         }
     }
 
     async fn stop(&self) {
         *self.connection.write().await = None;
+        self.notified.write().await.clear();
+        *self.last_devices.write().await = None;
+        *self.history.write().await = None;
+        self.details.write().await.clear();
+        self.item_devices.write().await.clear();
         tracing::info!("Battery module stopped");
     }
 
     async fn invoke_action(&self, _item_id: &str, _action_id: &str, _x: i32, _y: i32) {
-        // Battery module has no actions
+        // The only action opens the detail menu, which the client handles
+    }
+
+    async fn get_menu_items(&self, item_id: &str) -> anyhow::Result<Vec<MenuItem>> {
+        let device = self
+            .item_devices
+            .read()
+            .await
+            .get(item_id)
+            .filter(|d| Self::is_system_battery(d))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("No menu for item: {}", item_id))?;
+
+        // Refresh the history summaries whenever the menu is opened
+        let details = self
+            .battery_details(std::slice::from_ref(&device), true)
+            .await
+            .remove(&device.path)
+            .unwrap_or_default();
+        let estimate = self.record_history(std::slice::from_ref(&device)).await.remove(&device.path);
+        Ok(Self::build_details_menu(&device, estimate, &details))
+    }
+
+    async fn activate_menu_item(&self, _item_id: &str, _menu_item_id: i32) -> anyhow::Result<()> {
+        // The detail menu is read-only
+        Ok(())
     }
 
     async fn reload_config(&self, config: &crate::config::Config) -> bool {
         if let Some(ref battery_config) = config.modules.battery {
             let mut current = self.config.write().await;
             *current = battery_config.clone();
+            drop(current);
+            // Resend items even if the devices are unchanged, and re-apply the kind filters
+            *self.last_devices.write().await = None;
+            self.refresh.notify_one();
             tracing::debug!("Battery module config reloaded");
             true
         } else {
@@ -465,3 +886,165 @@ impl Module for BatteryModule {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(path: &str, kind: DeviceKind, power_supply: bool, percentage: f64, state: u32) -> PowerDevice {
+        PowerDevice {
+            path: format!("/org/freedesktop/UPower/devices/{}", path),
+            native_path: path.rsplit('_').next().unwrap_or(path).to_string(),
+            kind,
+            vendor: "Logitech".to_string(),
+            model: if power_supply { String::new() } else { "MX Master 3".to_string() },
+            power_supply,
+            is_present: true,
+            percentage,
+            state,
+            time_to_empty: 5400,
+            time_to_full: 0,
+            energy: if power_supply { 45.0 } else { 0.0 },
+            energy_rate: if power_supply { 8.5 } else { 0.0 },
+            charge_threshold: None,
+            health: None,
+        }
+    }
+
+    #[test]
+    fn test_kind_filter() {
+        let mut config = BatteryModuleConfig::default();
+        assert!(BatteryModule::is_included(&config, DeviceKind::Mouse));
+        assert!(!BatteryModule::is_included(&config, DeviceKind::LinePower));
+
+        config.include_kinds = vec!["battery".to_string(), "mouse".to_string()];
+        config.exclude_kinds = vec!["mouse".to_string()];
+        assert!(BatteryModule::is_included(&config, DeviceKind::Battery));
+        assert!(!BatteryModule::is_included(&config, DeviceKind::Mouse));
+        assert!(!BatteryModule::is_included(&config, DeviceKind::Headset));
+    }
+
+    #[test]
+    fn test_items_per_device() {
+        let devices = vec![
+            device("battery_BAT0", DeviceKind::Battery, true, 81.0, 2),
+            device("battery_BAT1", DeviceKind::Battery, true, 40.0, 2),
+            device("mouse_hidpp_battery_0", DeviceKind::Mouse, false, 8.0, 0),
+        ];
+        let items = BatteryModule::create_items(&devices, &HashMap::new(), &HashMap::new());
+
+        assert_eq!(items[0].id, "battery:status");
+        assert_eq!(items[0].label, "81%");
+        assert_eq!(items[0].icon_name.as_deref(), Some("battery-good"));
+        assert!(items[0].tooltip.as_deref().unwrap().contains("Time remaining: 1h 30m"));
+
+        assert!(items[0].tooltip.as_deref().unwrap().contains("Power: 8.5 W"));
+        assert_eq!(items[0].actions[0].id, "context_menu");
+        assert_eq!(items[0].value.as_ref().map(|v| v.spoken()).as_deref(), Some("81 percent"));
+
+        assert_eq!(items[1].id, "battery:battery_BAT1");
+        assert_eq!(items[1].label, "BAT1 40%");
+
+        let mouse = &items[2];
+        assert_eq!(mouse.id, "battery:mouse_hidpp_battery_0");
+        assert_eq!(mouse.label, "Mouse 8%");
+        assert_eq!(mouse.icon_name.as_deref(), Some("input-mouse"));
+        assert_eq!(
+            mouse.overlay_icon.as_ref().and_then(|i| i.name.as_deref()),
+            Some("battery-empty")
+        );
+        let tooltip = mouse.tooltip.as_deref().unwrap();
+        assert!(tooltip.starts_with("Device: Logitech MX Master 3 (Mouse)"));
+        assert!(!tooltip.contains("Status"));
+        assert!(mouse.actions.is_empty());
+
+        let metrics = BatteryModule::create_metrics(&items, &devices);
+        let names: Vec<&str> = metrics.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "battery.percentage",
+                "battery.discharging",
+                "battery.battery_BAT1.percentage",
+                "battery.mouse_hidpp_battery_0.percentage"
+            ]
+        );
+        assert_eq!(metrics[1].value, 1.0);
+        assert_eq!(metrics[3].value, 8.0);
+    }
+
+    #[test]
+    fn test_charge_threshold_tooltip() {
+        let mut laptop = device("battery_BAT0", DeviceKind::Battery, true, 80.0, 5);
+        laptop.charge_threshold = Some(upower::ChargeThreshold {
+            enabled: true,
+            start: 75,
+            end: 80,
+        });
+        let item = BatteryModule::create_module_item(&laptop, true, None, None);
+        let tooltip = item.tooltip.unwrap();
+        assert!(tooltip.contains("Status: Pending charge"));
+        assert!(tooltip.contains("Charge limit: On (75-80%)"));
+    }
+
+    fn point(time: u32, value: f64, state: u32) -> HistoryPoint {
+        HistoryPoint { time, value, state }
+    }
+
+    #[test]
+    fn test_discharge_summary() {
+        let points = vec![
+            point(100, 12.0, 2),
+            point(200, 6.0, 2),
+            point(300, 30.0, 1),
+            point(400, 0.0, 2),
+        ];
+        assert_eq!(
+            BatteryModule::discharge_summary(&points),
+            Some(RateSummary { average: 9.0, peak: 12.0 })
+        );
+        assert_eq!(BatteryModule::discharge_summary(&points[2..]), None);
+        assert_eq!(BatteryModule::charge_range(&[point(1, 35.0, 2), point(2, 90.0, 1)]), Some((35.0, 90.0)));
+    }
+
+    #[test]
+    fn test_health_and_details_menu() {
+        let mut laptop = device("battery_BAT0", DeviceKind::Battery, true, 81.0, 2);
+        laptop.health = Some(upower::BatteryHealth {
+            capacity: 87.7,
+            energy_full: 50.0,
+            energy_full_design: 57.0,
+            charge_cycles: Some(213),
+        });
+        let details = BatteryDetails {
+            discharge: Some(RateSummary { average: 7.4, peak: 15.0 }),
+            charge_range: Some((20.0, 80.0)),
+            profile_accuracy: None,
+        };
+
+        // The estimate from the history wins over UPower's 1h 30m
+        let item = BatteryModule::create_module_item(&laptop, true, Some(7200), Some(&details));
+        let tooltip = item.tooltip.unwrap();
+        assert!(tooltip.contains("Time remaining: 2h 0m"));
+        assert!(tooltip.contains("Health: 88% (50.0 of 57.0 Wh)"));
+        assert!(tooltip.contains("Charge cycles: 213"));
+        assert!(tooltip.contains("Last 24h: 7.4 W average discharge, 15.0 W peak"));
+
+        let menu = BatteryModule::build_details_menu(&laptop, None, &details);
+        let labels: Vec<&str> = menu.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(labels[0], "Charge: 81% (45.0 of 50.0 Wh)");
+        assert!(labels.contains(&"Time remaining: 1h 30m"));
+        assert!(labels.contains(&"Design capacity: 57.0 Wh"));
+        assert!(labels.contains(&"Last 24h charge: 20% to 80%"));
+        assert_eq!(menu.last().unwrap().id, menu.len() as i32);
+    }
+
+    #[test]
+    fn test_peripheral_discharging() {
+        let laptop = device("battery_BAT0", DeviceKind::Battery, true, 50.0, 0);
+        let mouse = device("mouse_0", DeviceKind::Mouse, false, 50.0, 0);
+        assert!(!BatteryModule::is_discharging(&laptop, BatteryState::Unknown));
+        assert!(BatteryModule::is_discharging(&mouse, BatteryState::Unknown));
+        assert!(!BatteryModule::is_discharging(&mouse, BatteryState::Charging));
+    }
+}
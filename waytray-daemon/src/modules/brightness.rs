@@ -12,8 +12,9 @@ use zbus::zvariant::OwnedObjectPath;
 use zbus::Connection;
 
 use crate::config::BrightnessModuleConfig;
+use crate::ItemStatus;
 
-use super::{ItemAction, Module, ModuleContext, ModuleItem};
+use super::{Adjustment, ItemAction, ItemValue, Module, ModuleContext, ModuleItem};
 
 const BACKLIGHT_BASE_PATH: &str = "/sys/class/backlight";
 
@@ -168,14 +169,16 @@ impl BrightnessModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(format!(
                 "Brightness: {}%\nDevice: {}",
                 state.percent, state.device.name
             )),
-            actions: vec![
-                ItemAction::new("brightness_up", "Brightness Up"),
-                ItemAction::new("brightness_down", "Brightness Down"),
-            ],
+            value: Some(ItemValue::percentage(state.percent as f64)),
+            actions: ItemAction::adjust_actions("brightness", "Brightness"),
         }
     }
 
@@ -300,12 +303,21 @@ impl BrightnessModule {
         }
     }
 
-    async fn step_brightness(&self, delta_percent: i32) {
+    /// Brightness after applying an adjustment to the current percentage
+    fn adjusted_percent(percent: u32, adjustment: Adjustment, step_percent: i32) -> u32 {
+        match adjustment {
+            Adjustment::Steps(steps) => percent.saturating_add_signed(steps * step_percent).min(100),
+            Adjustment::Minimum => 0,
+            Adjustment::Maximum => 100,
+        }
+    }
+
+    async fn adjust_brightness(&self, adjustment: Adjustment, step_percent: i32) {
         let Some(state) = self.get_brightness_state().await else {
             return;
         };
 
-        let new_percent = state.percent.saturating_add_signed(delta_percent).min(100);
+        let new_percent = Self::adjusted_percent(state.percent, adjustment, step_percent);
         self.set_brightness_percent(&state.device.name, new_percent, state.device.max_brightness)
             .await;
     }
@@ -378,23 +390,12 @@ impl Module for BrightnessModule {
     async fn invoke_action(&self, _item_id: &str, action_id: &str, _x: i32, _y: i32) {
         let step_percent = self.config.read().await.step_percent.max(1) as i32;
 
-        let handled = match action_id {
-            "brightness_up" => {
-                self.step_brightness(step_percent).await;
-                true
+        match Adjustment::parse(action_id, "brightness") {
+            Some(adjustment) => {
+                self.adjust_brightness(adjustment, step_percent).await;
+                self.send_update().await;
             }
-            "brightness_down" => {
-                self.step_brightness(-step_percent).await;
-                true
-            }
-            _ => {
-                tracing::warn!("Unknown brightness action: {}", action_id);
-                false
-            }
-        };
-
-        if handled {
-            self.send_update().await;
+            None => tracing::warn!("Unknown brightness action: {}", action_id),
         }
     }
 
@@ -452,6 +453,22 @@ mod tests {
         assert_eq!(BrightnessModule::raw_from_percent(150, 937), 937);
     }
 
+    #[test]
+    fn adjust_actions_move_within_range() {
+        let adjust = |action: &str, percent: u32| {
+            let adjustment = Adjustment::parse(action, "brightness").unwrap();
+            BrightnessModule::adjusted_percent(percent, adjustment, 5)
+        };
+        assert_eq!(adjust("brightness_up", 50), 55);
+        assert_eq!(adjust("brightness_down", 3), 0);
+        assert_eq!(adjust("brightness_page_up", 50), 70);
+        assert_eq!(adjust("brightness_page_up", 90), 100);
+        assert_eq!(adjust("brightness_min", 50), 0);
+        assert_eq!(adjust("brightness_max", 50), 100);
+        assert_eq!(Adjustment::parse("volume_up", "brightness"), None);
+        assert_eq!(Adjustment::parse("brightness_upward", "brightness"), None);
+    }
+
     #[test]
     fn configured_device_takes_precedence() {
         let base = temp_backlight_dir();
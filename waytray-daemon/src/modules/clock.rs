@@ -7,6 +7,7 @@ use chrono::{Local, Timelike};
 use tokio::sync::RwLock;
 
 use crate::config::ClockModuleConfig;
+use crate::ItemStatus;
 use super::{Module, ModuleContext, ModuleItem};
 
 /// Clock module that displays the current time
@@ -33,7 +34,12 @@ impl ClockModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(date_str),
+            value: None,
             actions: Vec::new(),
         }
     }
@@ -6,7 +6,8 @@ use async_trait::async_trait;
 use tokio::sync::RwLock;
 
 use crate::config::GpuModuleConfig;
-use super::{Module, ModuleContext, ModuleItem};
+use crate::ItemStatus;
+use super::{ItemValue, Metric, Module, ModuleContext, ModuleItem};
 
 /// Information about a GPU process
 struct GpuProcessInfo {
@@ -14,6 +15,23 @@ struct GpuProcessInfo {
     memory_mb: u64,
 }
 
+/// GPU memory usage
+#[derive(Debug, Clone, Copy)]
+struct GpuMemory {
+    used_mb: u64,
+    total_mb: u64,
+}
+
+impl GpuMemory {
+    fn percent(&self) -> f64 {
+        if self.total_mb == 0 {
+            0.0
+        } else {
+            self.used_mb as f64 / self.total_mb as f64 * 100.0
+        }
+    }
+}
+
 /// Detected GPU type
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum GpuType {
@@ -172,6 +190,27 @@ impl GpuModule {
         stdout.trim().parse().ok()
     }
 
+    /// Get GPU memory usage from NVIDIA
+    async fn get_nvidia_memory(&self) -> Option<GpuMemory> {
+        let output = tokio::process::Command::new("nvidia-smi")
+            .arg("--query-gpu=memory.used,memory.total")
+            .arg("--format=csv,noheader,nounits")
+            .output()
+            .await
+            .ok()?;
+
+        if !output.status.success() {
+            return None;
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let (used, total) = stdout.lines().next()?.split_once(',')?;
+        Some(GpuMemory {
+            used_mb: used.trim().parse().ok()?,
+            total_mb: total.trim().parse().ok()?,
+        })
+    }
+
     /// Get top GPU process from NVIDIA
     async fn get_nvidia_top_process(&self) -> Option<GpuProcessInfo> {
         let output = tokio::process::Command::new("nvidia-smi")
@@ -229,6 +268,21 @@ impl GpuModule {
         content.trim().parse().ok()
     }
 
+    /// Get VRAM usage from AMD via sysfs
+    async fn get_amd_memory(&self) -> Option<GpuMemory> {
+        let device_path = self.amd_device_path.read().await;
+        let device_path = device_path.as_ref()?;
+
+        let read_bytes = |name: &str| {
+            let path = format!("{}/{}", device_path, name);
+            async move { tokio::fs::read_to_string(&path).await.ok()?.trim().parse::<u64>().ok() }
+        };
+        Some(GpuMemory {
+            used_mb: read_bytes("mem_info_vram_used").await? / (1024 * 1024),
+            total_mb: read_bytes("mem_info_vram_total").await? / (1024 * 1024),
+        })
+    }
+
     /// Get GPU temperature from AMD via sysfs (hwmon)
     async fn get_amd_temperature(&self) -> Option<f32> {
         let device_path = self.amd_device_path.read().await;
@@ -269,6 +323,15 @@ impl GpuModule {
         }
     }
 
+    /// Get GPU memory usage (dispatches to correct implementation)
+    async fn get_memory(&self) -> Option<GpuMemory> {
+        match self.detect_gpu_type().await {
+            GpuType::Nvidia => self.get_nvidia_memory().await,
+            GpuType::Amd => self.get_amd_memory().await,
+            GpuType::Intel | GpuType::Unknown => None, // Intel GPUs share system memory
+        }
+    }
+
     /// Get top GPU process (currently NVIDIA only)
     async fn get_top_process(&self) -> Option<GpuProcessInfo> {
         match self.detect_gpu_type().await {
@@ -277,7 +340,13 @@ impl GpuModule {
         }
     }
 
-    fn create_gpu_item(&self, usage: u8, temperature: Option<f32>, top_process: Option<GpuProcessInfo>) -> ModuleItem {
+    fn create_gpu_item(
+        &self,
+        usage: u8,
+        memory: Option<GpuMemory>,
+        temperature: Option<f32>,
+        top_process: Option<GpuProcessInfo>,
+    ) -> ModuleItem {
         let icon_name = if temperature.map(|t| t >= 80.0).unwrap_or(false) {
             "dialog-warning"
         } else {
@@ -286,6 +355,15 @@ impl GpuModule {
 
         let mut tooltip_parts = vec![format!("GPU Usage: {}%", usage)];
 
+        if let Some(memory) = memory {
+            tooltip_parts.push(format!(
+                "Memory: {} / {} MB ({:.0}%)",
+                memory.used_mb,
+                memory.total_mb,
+                memory.percent()
+            ));
+        }
+
         if let Some(temp) = temperature {
             tooltip_parts.push(format!("Temperature: {:.0}°C", temp));
         }
@@ -304,21 +382,38 @@ impl GpuModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
+            value: Some(ItemValue::percentage(usage as f64)),
             actions: Vec::new(),
         }
     }
 
-    async fn create_items(&self) -> Vec<ModuleItem> {
+    /// Create the GPU item and the metrics for alert rules
+    ///
+    /// The temperature is read even when the item doesn't show it, so alert
+    /// rules can use it.
+    async fn create_items(&self) -> (Vec<ModuleItem>, Vec<Metric>) {
         let config = self.config.read().await;
         let mut items = Vec::new();
+        let mut metrics = Vec::new();
 
         if let Some(usage) = self.get_usage().await {
-            let temperature = if config.show_temperature {
-                self.get_temperature().await
-            } else {
-                None
-            };
+            metrics.push(Metric::new("gpu.usage", usage as f64));
+
+            let memory = self.get_memory().await;
+            if let Some(memory) = memory {
+                metrics.push(Metric::new("gpu.memory", memory.percent()));
+            }
+
+            let temperature = self.get_temperature().await;
+            if let Some(temp) = temperature {
+                metrics.push(Metric::new("gpu.temperature", temp as f64));
+            }
+            let temperature = temperature.filter(|_| config.show_temperature);
 
             let top_process = if config.show_top_process {
                 self.get_top_process().await
@@ -326,10 +421,10 @@ impl GpuModule {
                 None
             };
 
-            items.push(self.create_gpu_item(usage, temperature, top_process));
+            items.push(self.create_gpu_item(usage, memory, temperature, top_process));
         }
 
-        items
+        (items, metrics)
     }
 }
 
@@ -356,8 +451,9 @@ impl Module for GpuModule {
         }
 
         // Send initial items
-        let items = self.create_items().await;
+        let (items, metrics) = self.create_items().await;
         ctx.send_items("gpu", items);
+        ctx.send_metrics("gpu", metrics);
 
         // Poll at configured interval
         loop {
@@ -369,8 +465,9 @@ impl Module for GpuModule {
             tokio::select! {
                 _ = ctx.cancelled() => break,
                 _ = tokio::time::sleep(interval) => {
-                    let items = self.create_items().await;
+                    let (items, metrics) = self.create_items().await;
                     ctx.send_items("gpu", items);
+                    ctx.send_metrics("gpu", metrics);
                 }
             }
         }
@@ -17,8 +17,10 @@ use std::sync::Arc;
 use tokio::sync::{broadcast, RwLock};
 use tokio_util::sync::CancellationToken;
 
+use crate::{ItemCategory, ItemStatus};
+
 /// A module item that can be displayed in the panel
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ModuleItem {
     /// Unique identifier in format "{module_name}:{item_id}"
     pub id: String,
@@ -34,8 +36,19 @@ pub struct ModuleItem {
     pub icon_width: u32,
     /// Icon height if pixmap is used
     pub icon_height: u32,
+    /// Icon to show instead while the item needs attention
+    pub attention_icon: Option<ItemIcon>,
+    /// Small icon drawn over the main icon (e.g. an unread badge)
+    pub overlay_icon: Option<ItemIcon>,
+    /// Whether the item is passive, active or needs attention
+    pub status: ItemStatus,
+    /// SNI category (tray items only)
+    pub category: Option<ItemCategory>,
     /// Tooltip text
     pub tooltip: Option<String>,
+    /// The reading the label shows, for progress bars and screen readers
+    #[serde(default)]
+    pub value: Option<ItemValue>,
     /// Available actions for this item
     pub actions: Vec<ItemAction>,
 }
@@ -50,7 +63,12 @@ impl ModuleItem {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: None,
+            value: None,
             actions: Vec::new(),
         }
     }
@@ -71,6 +89,159 @@ impl ModuleItem {
     }
 }
 
+/// Kind of reading an item value holds
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ValueKind {
+    /// A percentage, e.g. battery charge or volume
+    Percentage,
+    /// A transfer rate in bytes per second
+    BytesPerSecond,
+    /// A temperature in degrees Celsius
+    Temperature,
+}
+
+impl ValueKind {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "percentage" => Some(ValueKind::Percentage),
+            "bytes_per_second" => Some(ValueKind::BytesPerSecond),
+            "temperature" => Some(ValueKind::Temperature),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ValueKind::Percentage => "percentage",
+            ValueKind::BytesPerSecond => "bytes_per_second",
+            ValueKind::Temperature => "temperature",
+        }
+    }
+}
+
+/// A typed reading attached to an item alongside its formatted label
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ItemValue {
+    pub kind: ValueKind,
+    pub value: f64,
+    /// Lower end of the range
+    pub min: f64,
+    /// Upper end of the range (0 when there is no natural maximum)
+    pub max: f64,
+    /// Unit as written after the value, e.g. "%", "B/s" or "°C"
+    pub unit: String,
+}
+
+impl ItemValue {
+    /// A percentage between 0 and 100
+    pub fn percentage(value: f64) -> Self {
+        Self {
+            kind: ValueKind::Percentage,
+            value,
+            min: 0.0,
+            max: 100.0,
+            unit: "%".to_string(),
+        }
+    }
+
+    /// A transfer rate without a fixed maximum
+    pub fn bytes_per_second(value: f64) -> Self {
+        Self {
+            kind: ValueKind::BytesPerSecond,
+            value,
+            min: 0.0,
+            max: 0.0,
+            unit: "B/s".to_string(),
+        }
+    }
+
+    /// A temperature in degrees Celsius, on a 0-100 scale
+    pub fn temperature(celsius: f64) -> Self {
+        Self {
+            kind: ValueKind::Temperature,
+            value: celsius,
+            min: 0.0,
+            max: 100.0,
+            unit: "°C".to_string(),
+        }
+    }
+
+    /// Use a different range, e.g. 0-150 for boosted volume
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = min;
+        self.max = max;
+        self
+    }
+
+    /// Position of the value within its range (0.0 to 1.0), if it has one
+    pub fn fraction(&self) -> Option<f64> {
+        if self.max <= self.min {
+            return None;
+        }
+        Some(((self.value - self.min) / (self.max - self.min)).clamp(0.0, 1.0))
+    }
+
+    /// The value as a screen reader should say it, e.g. "42 percent"
+    pub fn spoken(&self) -> String {
+        match self.kind {
+            ValueKind::Percentage => format!("{:.0} percent", self.value),
+            ValueKind::Temperature => format!("{:.0} degrees Celsius", self.value),
+            ValueKind::BytesPerSecond => {
+                let (scaled, unit) = if self.value >= 1_000_000_000.0 {
+                    (self.value / 1_000_000_000.0, "gigabytes")
+                } else if self.value >= 1_000_000.0 {
+                    (self.value / 1_000_000.0, "megabytes")
+                } else if self.value >= 1_000.0 {
+                    (self.value / 1_000.0, "kilobytes")
+                } else {
+                    (self.value, "bytes")
+                };
+                if scaled >= 10.0 || unit == "bytes" {
+                    format!("{:.0} {} per second", scaled, unit)
+                } else {
+                    format!("{:.1} {} per second", scaled, unit)
+                }
+            }
+        }
+    }
+}
+
+/// An icon given by theme name or as raw ARGB32 pixmap data
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ItemIcon {
+    /// Icon name from theme (preferred)
+    pub name: Option<String>,
+    /// Raw icon data in ARGB32 format (fallback)
+    pub pixmap: Option<Vec<u8>>,
+    /// Pixmap width
+    pub width: u32,
+    /// Pixmap height
+    pub height: u32,
+}
+
+impl ItemIcon {
+    /// Build an icon from name and pixmap data, or None if neither is usable
+    pub fn from_parts(
+        name: Option<String>,
+        pixmap: Option<Vec<u8>>,
+        width: u32,
+        height: u32,
+    ) -> Option<Self> {
+        let name = name.filter(|n| !n.is_empty());
+        let pixmap = pixmap.filter(|p| !p.is_empty() && width > 0 && height > 0);
+        if name.is_none() && pixmap.is_none() {
+            return None;
+        }
+        let (width, height) = if pixmap.is_some() { (width, height) } else { (0, 0) };
+        Some(Self {
+            name,
+            pixmap,
+            width,
+            height,
+        })
+    }
+}
+
 /// An action that can be performed on a module item
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ItemAction {
@@ -98,12 +269,80 @@ impl ItemAction {
             is_default: true,
         }
     }
+
+    /// Step, page and min/max actions of an adjustable value, e.g. `volume_up`
+    /// or `brightness_max`
+    ///
+    /// Clients present items that have these actions and a ranged value as sliders.
+    pub fn adjust_actions(prefix: &str, label: &str) -> Vec<Self> {
+        vec![
+            Self::new(&format!("{}_up", prefix), &format!("{} Up", label)),
+            Self::new(&format!("{}_down", prefix), &format!("{} Down", label)),
+            Self::new(&format!("{}_page_up", prefix), &format!("{} Page Up", label)),
+            Self::new(&format!("{}_page_down", prefix), &format!("{} Page Down", label)),
+            Self::new(&format!("{}_min", prefix), &format!("Minimum {}", label)),
+            Self::new(&format!("{}_max", prefix), &format!("Maximum {}", label)),
+        ]
+    }
 }
 
-/// Urgency level for notifications
+/// Number of steps moved by the `*_page_up` and `*_page_down` actions
+pub const PAGE_STEPS: i32 = 4;
+
+/// A change requested through one of the [`ItemAction::adjust_actions`]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    /// Move by this many steps (negative to decrease)
+    Steps(i32),
+    Minimum,
+    Maximum,
+}
+
+impl Adjustment {
+    /// Parse an adjust action id with the given prefix, e.g. `mic_volume_page_up`
+    pub fn parse(action_id: &str, prefix: &str) -> Option<Self> {
+        match action_id.strip_prefix(prefix)?.strip_prefix('_')? {
+            "up" => Some(Adjustment::Steps(1)),
+            "down" => Some(Adjustment::Steps(-1)),
+            "page_up" => Some(Adjustment::Steps(PAGE_STEPS)),
+            "page_down" => Some(Adjustment::Steps(-PAGE_STEPS)),
+            "min" => Some(Adjustment::Minimum),
+            "max" => Some(Adjustment::Maximum),
+            _ => None,
+        }
+    }
+}
+
+/// A numeric reading published by a module, checked by alert rules
+///
+/// Names are dotted and start with the module name, e.g. `system.cpu` or
+/// `battery.mouse_hidpp_battery_0.percentage`. Flags use 1.0 for true and
+/// 0.0 for false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metric {
+    pub name: String,
+    pub value: f64,
+}
+
+impl Metric {
+    pub fn new(name: impl Into<String>, value: f64) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+
+    pub fn flag(name: impl Into<String>, value: bool) -> Self {
+        Self::new(name, if value { 1.0 } else { 0.0 })
+    }
+}
+
+/// Urgency level for notifications
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Urgency {
     Low,
+    #[default]
     Normal,
     Critical,
 }
@@ -116,6 +355,11 @@ pub enum ModuleEvent {
         module_name: String,
         items: Vec<ModuleItem>,
     },
+    /// Module published new readings (all of its current metrics)
+    MetricsUpdated {
+        module_name: String,
+        metrics: Vec<Metric>,
+    },
     /// Module wants to send a desktop notification
     Notification {
         title: String,
@@ -124,6 +368,8 @@ pub enum ModuleEvent {
     },
     /// Config has been reloaded, clients should refresh
     ConfigReloaded,
+    /// The menu of an item changed, open menus should refresh
+    MenuUpdated { item_id: String },
 }
 
 /// Context provided to modules for communication and lifecycle management
@@ -150,6 +396,14 @@ impl ModuleContext {
         });
     }
 
+    /// Publish the module's current readings for alert rules
+    pub fn send_metrics(&self, module_name: &str, metrics: Vec<Metric>) {
+        let _ = self.event_sender.send(ModuleEvent::MetricsUpdated {
+            module_name: module_name.to_string(),
+            metrics,
+        });
+    }
+
     pub fn send_notification(&self, title: &str, body: &str, urgency: Urgency) {
         let _ = self.event_sender.send(ModuleEvent::Notification {
             title: title.to_string(),
@@ -158,6 +412,13 @@ impl ModuleContext {
         });
     }
 
+    /// Tell clients that the menu of an item has changed
+    pub fn send_menu_updated(&self, item_id: &str) {
+        let _ = self.event_sender.send(ModuleEvent::MenuUpdated {
+            item_id: item_id.to_string(),
+        });
+    }
+
     /// Check if the module should stop
     pub fn is_cancelled(&self) -> bool {
         self.cancellation_token.is_cancelled()
@@ -200,6 +461,16 @@ pub trait Module: Send + Sync {
         anyhow::bail!("Menu not supported by this module")
     }
 
+    /// Get menu items again for a menu that is already open, e.g. after
+    /// MenuChanged. Unlike `get_menu_items` this must not ask the application
+    /// to prepare the menu, which would just trigger another change.
+    async fn get_current_menu_items(
+        &self,
+        item_id: &str,
+    ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
+        self.get_menu_items(item_id).await
+    }
+
     /// Activate a menu item (only supported by tray module)
     async fn activate_menu_item(&self, _item_id: &str, _menu_item_id: i32) -> anyhow::Result<()> {
         anyhow::bail!("Menu not supported by this module")
@@ -212,6 +483,7 @@ pub trait Module: Send + Sync {
     }
 }
 
+use crate::alerts::AlertEngine;
 use crate::config::Config;
 use crate::notifications::NotificationService;
 
@@ -239,6 +511,8 @@ pub struct ModuleRegistry {
     event_sender: broadcast::Sender<ModuleEvent>,
     /// Notification service
     notification_service: Arc<NotificationService>,
+    /// Alert rules checked against module metrics
+    alerts: Arc<RwLock<AlertEngine>>,
     /// D-Bus connection (needed for tray module)
     connection: zbus::Connection,
 }
@@ -257,6 +531,7 @@ impl ModuleRegistry {
             items: Arc::new(RwLock::new(HashMap::new())),
             event_sender: sender,
             notification_service: Arc::new(notification_service),
+            alerts: Arc::new(RwLock::new(AlertEngine::default())),
             connection,
         }
     }
@@ -268,6 +543,8 @@ impl ModuleRegistry {
 
     /// Start the event listener and initial modules based on config
     pub async fn start(&self, config: &Config) {
+        self.alerts.write().await.set_rules(&config.alerts);
+
         // Start the event listener
         self.start_event_listener();
 
@@ -279,6 +556,7 @@ impl ModuleRegistry {
     fn start_event_listener(&self) {
         let items = self.items.clone();
         let notification_service = self.notification_service.clone();
+        let alerts = self.alerts.clone();
         let mut receiver = self.event_sender.subscribe();
 
         tokio::spawn(async move {
@@ -292,6 +570,22 @@ impl ModuleRegistry {
                         items_lock.insert(module_name.clone(), new_items);
                         tracing::debug!("Updated items for module: {}", module_name);
                     }
+                    ModuleEvent::MetricsUpdated {
+                        module_name,
+                        metrics,
+                    } => {
+                        let fired = alerts
+                            .write()
+                            .await
+                            .update(&module_name, &metrics, std::time::Instant::now());
+                        for alert in fired {
+                            tracing::info!("Alert: {} - {}", alert.title, alert.body);
+                            notification_service.send(&alert.title, &alert.body, alert.urgency);
+                            if let Some(ref sound) = alert.sound {
+                                crate::sound::play_sound(sound);
+                            }
+                        }
+                    }
                     ModuleEvent::Notification {
                         title,
                         body,
@@ -305,7 +599,7 @@ impl ModuleRegistry {
                         );
                         notification_service.send(&title, &body, urgency);
                     }
-                    ModuleEvent::ConfigReloaded => {
+                    ModuleEvent::ConfigReloaded | ModuleEvent::MenuUpdated { .. } => {
                         // Handled by D-Bus service, nothing to do here
                     }
                 }
@@ -391,12 +685,32 @@ impl ModuleRegistry {
                 items.remove(name);
             }
 
+            // Forget the alert state of the module's metrics
+            self.alerts
+                .write()
+                .await
+                .update(name, &[], std::time::Instant::now());
+
             true
         } else {
             false
         }
     }
 
+    /// Stop every running module (used on daemon shutdown)
+    pub async fn stop_all(&self) {
+        let names: Vec<String> = {
+            let running = self.running_modules.read().await;
+            running.keys().cloned().collect()
+        };
+
+        for name in &names {
+            self.stop_module(name).await;
+        }
+
+        tracing::info!("Stopped {} modules", names.len());
+    }
+
     /// Sync running modules with config (start new, stop removed)
     async fn sync_modules_with_config(&self, config: &Config) {
         let enabled_modules = Self::get_enabled_modules(config);
@@ -553,9 +867,13 @@ impl ModuleRegistry {
     }
 
     /// Get menu items for a module item
+    ///
+    /// `refresh` is set when re-reading a menu that is already open, see
+    /// [`Module::get_current_menu_items`].
     pub async fn get_menu_items(
         &self,
         item_id: &str,
+        refresh: bool,
     ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
         // Parse module name from item_id (format: "module:item")
         let parts: Vec<&str> = item_id.splitn(2, ':').collect();
@@ -567,7 +885,11 @@ impl ModuleRegistry {
         // Find the module and get menu items
         let running = self.running_modules.read().await;
         if let Some(rm) = running.get(module_name) {
-            rm.module.get_menu_items(item_id).await
+            if refresh {
+                rm.module.get_current_menu_items(item_id).await
+            } else {
+                rm.module.get_menu_items(item_id).await
+            }
         } else {
             anyhow::bail!("Module not found for item: {}", item_id)
         }
@@ -613,6 +935,8 @@ impl ModuleRegistry {
             tracing::info!("Updated module order");
         }
 
+        self.alerts.write().await.set_rules(&config.alerts);
+
         // Sync modules with new config (start/stop as needed)
         self.sync_modules_with_config(config).await;
 
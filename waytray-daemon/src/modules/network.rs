@@ -1,30 +1,165 @@
 //! Network module - displays network status and speed
+//!
+//! When NetworkManager is running, connection state (name, Wi-Fi SSID and
+//! signal strength, VPNs) comes from its D-Bus API and updates on its signals.
+//! Otherwise the module falls back to polling /sys/class/net. Transfer speeds
+//! always come from the interface's sysfs counters.
+//!
+//! By default a single item follows the primary interface. With `interfaces`
+//! configured, each matching interface gets its own item, plus a total.
 
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use async_trait::async_trait;
+use futures::StreamExt;
 use tokio::sync::RwLock;
+use zbus::proxy::PropertyStream;
+use zbus::Connection;
 
 use crate::config::NetworkModuleConfig;
-use super::{Module, ModuleContext, ModuleItem};
+use crate::dbusmenu::MenuItem;
+use crate::networkmanager::{
+    self, AccessPointProxy, ConnectionKind, NetworkManagerProxy, NmConnection, NmSnapshot,
+    RadioState, SavedConnection, WifiNetwork,
+};
+use crate::ItemStatus;
+use super::{ItemAction, ItemIcon, ItemValue, Metric, Module, ModuleContext, ModuleItem, Urgency};
 
-/// Network traffic tracking state
-struct TrafficState {
+/// Fixed menu ids; access points and saved connections get ids from MENU_DYNAMIC_BASE
+const MENU_WIFI_TOGGLE: i32 = 1;
+const MENU_AIRPLANE_TOGGLE: i32 = 2;
+const MENU_RESCAN: i32 = 3;
+const MENU_SAVED_SUBMENU: i32 = 4;
+const MENU_VPN_SUBMENU: i32 = 5;
+const MENU_DISCONNECT: i32 = 6;
+const MENU_SEPARATOR_RADIOS: i32 = 10;
+const MENU_NO_NETWORKS: i32 = 11;
+const MENU_SEPARATOR_SAVED: i32 = 12;
+const MENU_DYNAMIC_BASE: i32 = 100;
+
+/// What a menu entry does when activated
+#[derive(Debug, Clone, PartialEq)]
+enum MenuTarget {
+    ToggleWifi,
+    ToggleAirplaneMode,
+    Rescan,
+    /// Connect to a visible network, with the saved profile for its SSID if any
+    Network {
+        network: WifiNetwork,
+        saved_profile: Option<String>,
+        connected: bool,
+    },
+    /// Toggle a saved profile (including VPNs) on or off
+    Saved(SavedConnection),
+    Disconnect(String),
+}
+
+/// Interface flag for an administratively up interface (IFF_UP)
+const IFF_UP: u32 = 0x1;
+
+/// Counters and recent speeds of one interface
+struct InterfaceTraffic {
     rx_bytes: u64,
     tx_bytes: u64,
+    sampled_at: Instant,
+    /// Tunnel traffic is also counted on the underlying link, so it's left
+    /// out of the total
+    tunnel: bool,
+    /// Speeds in bytes per second (rx, tx), oldest first
+    history: VecDeque<(u64, u64)>,
+}
+
+impl InterfaceTraffic {
+    fn new(rx_bytes: u64, tx_bytes: u64, now: Instant, tunnel: bool) -> Self {
+        Self {
+            rx_bytes,
+            tx_bytes,
+            sampled_at: now,
+            tunnel,
+            history: VecDeque::new(),
+        }
+    }
+
+    /// Record new counter values, returning the speed since the previous sample
+    fn record(&mut self, rx_bytes: u64, tx_bytes: u64, now: Instant, history_size: usize) -> Option<(u64, u64)> {
+        let elapsed = now.duration_since(self.sampled_at).as_secs_f64();
+        if elapsed <= 0.0 {
+            return None;
+        }
+
+        // Counters restart when an interface is recreated; saturate to zero
+        let speed = (
+            (rx_bytes.saturating_sub(self.rx_bytes) as f64 / elapsed) as u64,
+            (tx_bytes.saturating_sub(self.tx_bytes) as f64 / elapsed) as u64,
+        );
+        self.rx_bytes = rx_bytes;
+        self.tx_bytes = tx_bytes;
+        self.sampled_at = now;
+        push_sample(&mut self.history, speed, history_size);
+        Some(speed)
+    }
+}
+
+/// Append a speed sample, dropping the oldest beyond `history_size`
+fn push_sample(history: &mut VecDeque<(u64, u64)>, speed: (u64, u64), history_size: usize) {
+    history.push_back(speed);
+    while history.len() > history_size.max(1) {
+        history.pop_front();
+    }
+}
+
+/// Current, average and peak speeds (rx, tx) in bytes per second
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SpeedStats {
+    current: (u64, u64),
+    average: (u64, u64),
+    peak: (u64, u64),
+    samples: usize,
+}
+
+impl SpeedStats {
+    fn from_history(history: &VecDeque<(u64, u64)>) -> Option<Self> {
+        let current = *history.back()?;
+        let samples = history.len();
+        let (rx_sum, tx_sum) = history
+            .iter()
+            .fold((0u64, 0u64), |(rx, tx), s| (rx.saturating_add(s.0), tx.saturating_add(s.1)));
+        Some(Self {
+            current,
+            average: (rx_sum / samples as u64, tx_sum / samples as u64),
+            peak: (
+                history.iter().map(|s| s.0).max().unwrap_or(0),
+                history.iter().map(|s| s.1).max().unwrap_or(0),
+            ),
+            samples,
+        })
+    }
 }
 
 /// Network module that displays connection status and speed
 pub struct NetworkModule {
     config: RwLock<NetworkModuleConfig>,
-    traffic_state: RwLock<Option<TrafficState>>,
+    /// Traffic of the monitored interfaces, keyed by interface name
+    traffic: RwLock<HashMap<String, InterfaceTraffic>>,
+    /// Combined speeds of the non-tunnel interfaces
+    total_history: RwLock<VecDeque<(u64, u64)>>,
+    /// System bus connection while the NetworkManager backend is in use
+    nm_connection: RwLock<Option<Connection>>,
+    ctx: RwLock<Option<Arc<ModuleContext>>>,
+    /// Targets of the entries in the most recently built menu
+    menu_targets: RwLock<HashMap<i32, MenuTarget>>,
 }
 
 impl NetworkModule {
     pub fn new(config: NetworkModuleConfig) -> Self {
         Self {
             config: RwLock::new(config),
-            traffic_state: RwLock::new(None),
+            traffic: RwLock::new(HashMap::new()),
+            total_history: RwLock::new(VecDeque::new()),
+            nm_connection: RwLock::new(None),
+            ctx: RwLock::new(None),
+            menu_targets: RwLock::new(HashMap::new()),
         }
     }
 
@@ -57,46 +192,156 @@ impl NetworkModule {
     /// Check if interface is up and connected
     async fn is_connected(&self, interface: &str) -> bool {
         let path = format!("/sys/class/net/{}/operstate", interface);
-        if let Ok(content) = tokio::fs::read_to_string(&path).await {
-            let state = content.trim();
-            return state == "up";
+        let Ok(operstate) = tokio::fs::read_to_string(&path).await else {
+            return false;
+        };
+        let flags_path = format!("/sys/class/net/{}/flags", interface);
+        let flags = tokio::fs::read_to_string(&flags_path).await.unwrap_or_default();
+        Self::is_up(&operstate, &flags)
+    }
+
+    /// Decide whether an interface is up from its sysfs operstate and flags
+    ///
+    /// Tunnels such as WireGuard report an "unknown" operstate, so for those
+    /// the administrative IFF_UP flag decides.
+    fn is_up(operstate: &str, flags: &str) -> bool {
+        match operstate.trim() {
+            "up" => true,
+            "unknown" => u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16)
+                .is_ok_and(|flags| flags & IFF_UP != 0),
+            _ => false,
         }
-        false
     }
 
-    /// Get IP address for interface
-    async fn get_ip_address(&self, interface: &str) -> Option<String> {
-        // Try to read from /proc/net/fib_trie or use ip command
-        // For simplicity, we'll parse ip addr output
+    /// Check whether an interface is a tunnel (WireGuard, tun/tap)
+    async fn is_tunnel(interface: &str) -> bool {
+        if ["wg", "tun", "tap"].iter().any(|p| interface.starts_with(p)) {
+            return true;
+        }
+        // ARPHRD_NONE, used by WireGuard and tun devices with other names
+        let path = format!("/sys/class/net/{}/type", interface);
+        tokio::fs::read_to_string(&path)
+            .await
+            .is_ok_and(|content| content.trim() == "65534")
+    }
+
+    /// List the interface names in /sys/class/net, sorted
+    async fn list_interfaces() -> Vec<String> {
+        let mut names = Vec::new();
+        if let Ok(mut entries) = tokio::fs::read_dir("/sys/class/net").await {
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+        }
+        names.sort();
+        names
+    }
+
+    /// Resolve the configured interface patterns against the available interfaces
+    ///
+    /// Plain names are kept even while the interface is down or missing (a
+    /// docking station's ethernet, say), while `*` patterns only match
+    /// interfaces that are up. Loopback is never matched by a pattern.
+    fn match_interfaces(patterns: &[String], available: &[(String, bool)]) -> Vec<String> {
+        let mut matched: Vec<String> = Vec::new();
+        for pattern in patterns {
+            match pattern.strip_suffix('*') {
+                Some(prefix) => {
+                    for (name, up) in available {
+                        if *up && name != "lo" && name.starts_with(prefix) && !matched.contains(name) {
+                            matched.push(name.clone());
+                        }
+                    }
+                }
+                None => {
+                    if !matched.contains(pattern) {
+                        matched.push(pattern.clone());
+                    }
+                }
+            }
+        }
+        matched
+    }
+
+    /// Whether the config asks for one item per interface
+    async fn is_multi_interface(&self) -> bool {
+        !self.config.read().await.interfaces.is_empty()
+    }
+
+    /// Get the interfaces to monitor
+    ///
+    /// Without `interfaces` configured this is the single configured interface,
+    /// the primary connection's interface or the default route interface.
+    async fn monitored_interfaces(&self, primary: Option<&str>) -> Vec<String> {
+        let patterns = {
+            let config = self.config.read().await;
+            if config.interfaces.is_empty() {
+                if !config.interface.is_empty() {
+                    return vec![config.interface.clone()];
+                }
+                if let Some(primary) = primary {
+                    return vec![primary.to_string()];
+                }
+                drop(config);
+                return self.get_default_interface().await.into_iter().collect();
+            }
+            config.interfaces.clone()
+        };
+
+        let mut available = Vec::new();
+        for name in Self::list_interfaces().await {
+            let up = self.is_connected(&name).await;
+            available.push((name, up));
+        }
+        Self::match_interfaces(&patterns, &available)
+    }
+
+    /// Get the IPv4 and global IPv6 addresses of an interface
+    async fn get_ip_addresses(&self, interface: &str) -> (Vec<String>, Vec<String>) {
         let output = tokio::process::Command::new("ip")
-            .args(["-4", "addr", "show", interface])
+            .args(["addr", "show", interface])
             .output()
-            .await
-            .ok()?;
+            .await;
 
-        if !output.status.success() {
-            return None;
+        match output {
+            Ok(output) if output.status.success() => {
+                Self::parse_ip_addresses(&String::from_utf8_lossy(&output.stdout))
+            }
+            _ => (Vec::new(), Vec::new()),
         }
+    }
+
+    /// Parse `ip addr show` output into IPv4 and IPv6 addresses without prefix
+    /// lengths, skipping link-local IPv6 addresses
+    fn parse_ip_addresses(output: &str) -> (Vec<String>, Vec<String>) {
+        let mut ipv4 = Vec::new();
+        let mut ipv6 = Vec::new();
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        for line in stdout.lines() {
+        for line in output.lines() {
             let line = line.trim();
-            if line.starts_with("inet ") {
-                // Format: inet 192.168.1.100/24 brd ...
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if parts.len() >= 2 {
-                    // Remove CIDR suffix
-                    let ip = parts[1].split('/').next()?;
-                    return Some(ip.to_string());
+            // Format: inet 192.168.1.100/24 brd ... / inet6 2001:db8::1/64 scope global ...
+            let (list, rest) = if let Some(rest) = line.strip_prefix("inet ") {
+                (&mut ipv4, rest)
+            } else if let Some(rest) = line.strip_prefix("inet6 ") {
+                if rest.contains("scope link") {
+                    continue;
                 }
+                (&mut ipv6, rest)
+            } else {
+                continue;
+            };
+
+            if let Some(address) = rest.split_whitespace().next() {
+                let ip = address.split('/').next().unwrap_or(address);
+                list.push(ip.to_string());
             }
         }
 
-        None
+        (ipv4, ipv6)
     }
 
     /// Get network traffic bytes
-    async fn get_traffic_bytes(&self, interface: &str) -> Option<(u64, u64)> {
+    async fn get_traffic_bytes(interface: &str) -> Option<(u64, u64)> {
         let rx_path = format!("/sys/class/net/{}/statistics/rx_bytes", interface);
         let tx_path = format!("/sys/class/net/{}/statistics/tx_bytes", interface);
 
@@ -109,42 +354,117 @@ impl NetworkModule {
         Some((rx_bytes, tx_bytes))
     }
 
-    /// Calculate and update speed, returns (rx_speed_bps, tx_speed_bps)
-    async fn get_speed(&self, interface: &str, interval_secs: u64) -> Option<(u64, u64)> {
-        let (rx_bytes, tx_bytes) = self.get_traffic_bytes(interface).await?;
+    /// Start tracking the counters of an interface
+    async fn new_traffic(interface: &str, now: Instant) -> Option<InterfaceTraffic> {
+        let (rx_bytes, tx_bytes) = Self::get_traffic_bytes(interface).await?;
+        Some(InterfaceTraffic::new(rx_bytes, tx_bytes, now, Self::is_tunnel(interface).await))
+    }
 
-        let mut state = self.traffic_state.write().await;
+    /// Read the counters of the given interfaces and record their speeds
+    ///
+    /// Interfaces that are no longer monitored are forgotten.
+    async fn sample_traffic(&self, interfaces: &[String]) {
+        let history_size = self.config.read().await.history_size;
+        let now = Instant::now();
+        let mut traffic = self.traffic.write().await;
+        traffic.retain(|name, _| interfaces.contains(name));
 
-        let speed = if let Some(prev) = state.as_ref() {
-            let rx_delta = rx_bytes.saturating_sub(prev.rx_bytes);
-            let tx_delta = tx_bytes.saturating_sub(prev.tx_bytes);
+        // Stays None until an interface has two samples to compare
+        let mut total: Option<(u64, u64)> = None;
+        for interface in interfaces {
+            let Some((rx_bytes, tx_bytes)) = Self::get_traffic_bytes(interface).await else {
+                traffic.remove(interface);
+                continue;
+            };
+            match traffic.get_mut(interface) {
+                Some(state) => {
+                    if let Some((rx, tx)) = state.record(rx_bytes, tx_bytes, now, history_size) {
+                        let total = total.get_or_insert((0, 0));
+                        if !state.tunnel {
+                            total.0 += rx;
+                            total.1 += tx;
+                        }
+                    }
+                }
+                None => {
+                    let tunnel = Self::is_tunnel(interface).await;
+                    traffic.insert(
+                        interface.clone(),
+                        InterfaceTraffic::new(rx_bytes, tx_bytes, now, tunnel),
+                    );
+                }
+            }
+        }
+        drop(traffic);
 
-            // Convert to bytes per second (ensure at least 1 second to prevent division by zero)
-            let safe_interval = interval_secs.max(1);
-            let rx_speed = rx_delta / safe_interval;
-            let tx_speed = tx_delta / safe_interval;
+        if let Some(total) = total {
+            push_sample(&mut *self.total_history.write().await, total, history_size);
+        }
+    }
 
-            Some((rx_speed, tx_speed))
-        } else {
-            None // First reading
-        };
+    /// Start tracking interfaces that aren't tracked yet, so their speed is
+    /// known at the next sample
+    async fn track_interfaces(&self, interfaces: &[String]) {
+        let now = Instant::now();
+        let mut traffic = self.traffic.write().await;
+        for interface in interfaces {
+            if !traffic.contains_key(interface) {
+                if let Some(state) = Self::new_traffic(interface, now).await {
+                    traffic.insert(interface.clone(), state);
+                }
+            }
+        }
+    }
 
-        *state = Some(TrafficState { rx_bytes, tx_bytes });
+    /// Get the speed statistics of an interface, once two samples were taken
+    async fn speed_stats(&self, interface: &str) -> Option<SpeedStats> {
+        let traffic = self.traffic.read().await;
+        SpeedStats::from_history(&traffic.get(interface)?.history)
+    }
 
-        speed
+    /// Format bytes to human readable string
+    fn format_bytes(bytes: u64) -> String {
+        if bytes >= 1_000_000_000 {
+            format!("{:.1}GB", bytes as f64 / 1_000_000_000.0)
+        } else if bytes >= 1_000_000 {
+            format!("{:.1}MB", bytes as f64 / 1_000_000.0)
+        } else if bytes >= 1_000 {
+            format!("{:.0}KB", bytes as f64 / 1_000.0)
+        } else {
+            format!("{}B", bytes)
+        }
     }
 
     /// Format bytes per second to human readable string
     fn format_speed(bytes_per_sec: u64) -> String {
-        if bytes_per_sec >= 1_000_000_000 {
-            format!("{:.1}GB/s", bytes_per_sec as f64 / 1_000_000_000.0)
-        } else if bytes_per_sec >= 1_000_000 {
-            format!("{:.1}MB/s", bytes_per_sec as f64 / 1_000_000.0)
-        } else if bytes_per_sec >= 1_000 {
-            format!("{:.0}KB/s", bytes_per_sec as f64 / 1_000.0)
-        } else {
-            format!("{}B/s", bytes_per_sec)
-        }
+        format!("{}/s", Self::format_bytes(bytes_per_sec))
+    }
+
+    /// Label text for a (rx, tx) speed pair, e.g. "↓1.2MB/s ↑50KB/s"
+    fn format_speeds((rx, tx): (u64, u64)) -> String {
+        format!("↓{} ↑{}", Self::format_speed(rx), Self::format_speed(tx))
+    }
+
+    /// Tooltip lines for download and upload speeds, with the average and
+    /// peak once there is some history
+    fn speed_lines(stats: &SpeedStats) -> Vec<String> {
+        let line = |name: &str, current: u64, average: u64, peak: u64| {
+            if stats.samples > 1 {
+                format!(
+                    "{}: {} (avg {}, peak {})",
+                    name,
+                    Self::format_speed(current),
+                    Self::format_speed(average),
+                    Self::format_speed(peak)
+                )
+            } else {
+                format!("{}: {}", name, Self::format_speed(current))
+            }
+        };
+        vec![
+            line("Download", stats.current.0, stats.average.0, stats.peak.0),
+            line("Upload", stats.current.1, stats.average.1, stats.peak.1),
+        ]
     }
 
     /// Get connection type icon based on interface name
@@ -153,33 +473,766 @@ impl NetworkModule {
             "network-wireless"
         } else if interface.starts_with("eth") || interface.starts_with("en") {
             "network-wired"
-        } else if interface.starts_with("tun") || interface.starts_with("tap") {
+        } else if interface.starts_with("tun") || interface.starts_with("tap") || interface.starts_with("wg") {
             "network-vpn"
         } else {
             "network-transmit-receive"
         }
     }
 
+    /// Get the icon for a NetworkManager connection
+    fn get_icon_for_connection(kind: ConnectionKind, strength: Option<u8>) -> &'static str {
+        match kind {
+            ConnectionKind::Wifi => match strength.unwrap_or(0) {
+                80.. => "network-wireless-signal-excellent",
+                55..=79 => "network-wireless-signal-good",
+                30..=54 => "network-wireless-signal-ok",
+                5..=29 => "network-wireless-signal-weak",
+                _ => "network-wireless-signal-none",
+            },
+            ConnectionKind::Ethernet => "network-wired",
+            ConnectionKind::Vpn => "network-vpn",
+            ConnectionKind::Mobile => "network-cellular",
+            ConnectionKind::Other => "network-transmit-receive",
+        }
+    }
+
+    /// Item value for a speed: the download rate, scaled to the recent peak
+    fn speed_value(stats: &SpeedStats) -> ItemValue {
+        ItemValue::bytes_per_second(stats.current.0 as f64).with_range(0.0, stats.peak.0 as f64)
+    }
+
+    /// Label for a connection: the Wi-Fi SSID and strength, or the connection name
+    fn connection_label(connection: &NmConnection) -> String {
+        match (&connection.ssid, connection.strength) {
+            (Some(ssid), Some(strength)) => format!("{} {}%", ssid, strength),
+            (Some(ssid), None) => ssid.clone(),
+            _ => connection.id.clone(),
+        }
+    }
+
+    /// Build a status item with the given label, icon and tooltip and no actions
+    fn status_item(label: &str, icon: &str, tooltip: String) -> ModuleItem {
+        ModuleItem {
+            id: "network:status".to_string(),
+            module: "network".to_string(),
+            label: label.to_string(),
+            icon_name: Some(icon.to_string()),
+            icon_pixmap: None,
+            icon_width: 0,
+            icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
+            tooltip: Some(tooltip),
+            value: None,
+            actions: Vec::new(),
+        }
+    }
+
+    /// Create items from a NetworkManager snapshot
+    ///
+    /// With `interfaces` configured, the status item only carries the
+    /// connection state and menu, and the per-interface items follow it.
+    async fn create_nm_items(&self, snapshot: &NmSnapshot) -> Vec<ModuleItem> {
+        let mut items = match snapshot.primary.as_ref().filter(|_| snapshot.state.is_connected()) {
+            Some(primary) => {
+                vec![self.create_nm_status_item(snapshot, primary).await]
+            }
+            None => {
+                let description = snapshot.state.description();
+                let mut tooltip = format!("NetworkManager: {}", description);
+                if !snapshot.wireless_enabled {
+                    tooltip.push_str("\nWi-Fi is disabled");
+                }
+                let mut item = Self::status_item(description, "network-offline", tooltip);
+                item.actions = Self::nm_actions();
+                vec![item]
+            }
+        };
+
+        if self.is_multi_interface().await {
+            let interfaces = self.monitored_interfaces(None).await;
+            if !interfaces.is_empty() {
+                items.extend(self.create_interface_items(&interfaces, Some(snapshot)).await);
+            }
+        }
+
+        items
+    }
+
+    /// Create the status item for the primary NetworkManager connection
+    async fn create_nm_status_item(&self, snapshot: &NmSnapshot, primary: &NmConnection) -> ModuleItem {
+        let config = self.config.read().await;
+        let show_ip = config.show_ip;
+        let show_speed = config.show_speed && config.interfaces.is_empty();
+        let show_connection = config.show_connection;
+        let configured_interface = config.interface.clone();
+        drop(config);
+
+        let mut label_parts = Vec::new();
+        let mut tooltip_parts = vec![format!("Connection: {}", primary.id)];
+
+        if let Some(ref ssid) = primary.ssid {
+            tooltip_parts.push(format!("SSID: {}", ssid));
+        }
+        if let Some(strength) = primary.strength {
+            tooltip_parts.push(format!("Signal: {}%", strength));
+        }
+
+        if show_connection {
+            label_parts.push(Self::connection_label(primary));
+        }
+
+        let interface = if configured_interface.is_empty() {
+            primary.interface.clone()
+        } else {
+            Some(configured_interface)
+        };
+        if let Some(ref interface) = interface {
+            tooltip_parts.push(format!("Interface: {}", interface));
+        }
+
+        if show_ip {
+            if let Some(ref ip) = primary.ipv4 {
+                tooltip_parts.push(format!("IP: {}", ip));
+            }
+            if let Some(ref ip) = primary.ipv6 {
+                tooltip_parts.push(format!("IPv6: {}", ip));
+            }
+        }
+
+        let mut value = None;
+        if show_speed {
+            let stats = match interface {
+                Some(ref interface) => self.speed_stats(interface).await,
+                None => None,
+            };
+            if let Some(stats) = stats {
+                label_parts.push(Self::format_speeds(stats.current));
+                tooltip_parts.extend(Self::speed_lines(&stats));
+                value = Some(Self::speed_value(&stats));
+            } else {
+                label_parts.push("↓-- ↑--".to_string());
+            }
+        }
+
+        if !snapshot.vpns.is_empty() {
+            let names: Vec<&str> = snapshot.vpns.iter().map(|v| v.id.as_str()).collect();
+            tooltip_parts.push(format!("VPN: {}", names.join(", ")));
+        }
+
+        if snapshot.is_portal() {
+            tooltip_parts.push("Status: Captive portal, sign-in required".to_string());
+        } else if snapshot.state != networkmanager::NmState::ConnectedGlobal {
+            tooltip_parts.push(format!("Status: {}", snapshot.state.description()));
+        }
+
+        let label = if label_parts.is_empty() {
+            primary.id.clone()
+        } else {
+            label_parts.join(" ")
+        };
+
+        let icon = Self::get_icon_for_connection(primary.kind, primary.strength);
+        let mut item = Self::status_item(&label, icon, tooltip_parts.join("\n"));
+        item.actions = Self::nm_actions();
+        item.value = value;
+
+        // Badge the connection icon while a VPN runs on top of it
+        if primary.kind != ConnectionKind::Vpn && !snapshot.vpns.is_empty() {
+            item.overlay_icon = ItemIcon::from_parts(Some("network-vpn".to_string()), None, 0, 0);
+        }
+
+        item
+    }
+
+    /// Create one item per interface, plus the total when several are shown
+    ///
+    /// The NetworkManager snapshot, when available, names the connection on
+    /// each interface.
+    async fn create_interface_items(
+        &self,
+        interfaces: &[String],
+        snapshot: Option<&NmSnapshot>,
+    ) -> Vec<ModuleItem> {
+        let config = self.config.read().await;
+        let show_ip = config.show_ip;
+        let show_speed = config.show_speed;
+        let show_connection = config.show_connection;
+        let show_total = config.show_total;
+        drop(config);
+
+        let mut items = Vec::new();
+        // Per-interface lines of the total item's tooltip
+        let mut breakdown = Vec::new();
+        let mut totals = (0u64, 0u64);
+
+        for interface in interfaces {
+            let connection = snapshot.and_then(|s| {
+                s.active
+                    .iter()
+                    .find(|c| c.interface.as_deref() == Some(interface.as_str()))
+            });
+            let mut value = None;
+
+            let mut item = if self.is_connected(interface).await {
+                let icon = match connection {
+                    Some(c) => Self::get_icon_for_connection(c.kind, c.strength),
+                    None => Self::get_icon_for_interface(interface),
+                };
+                let mut label_parts = vec![match connection {
+                    Some(c) if show_connection => Self::connection_label(c),
+                    _ => interface.clone(),
+                }];
+                let mut tooltip_parts = vec![format!("Interface: {}", interface)];
+
+                if let Some(c) = connection {
+                    tooltip_parts.push(format!("Connection: {}", c.id));
+                    if let Some(ref ssid) = c.ssid {
+                        tooltip_parts.push(format!("SSID: {}", ssid));
+                    }
+                    if let Some(strength) = c.strength {
+                        tooltip_parts.push(format!("Signal: {}%", strength));
+                    }
+                }
+
+                if show_ip {
+                    let (ipv4, ipv6) = self.get_ip_addresses(interface).await;
+                    tooltip_parts.extend(ipv4.iter().map(|ip| format!("IP: {}", ip)));
+                    tooltip_parts.extend(ipv6.iter().map(|ip| format!("IPv6: {}", ip)));
+                }
+
+                let traffic = self.traffic.read().await;
+                if let Some(state) = traffic.get(interface) {
+                    let stats = SpeedStats::from_history(&state.history);
+                    if show_speed {
+                        match stats {
+                            Some(ref stats) => {
+                                label_parts.push(Self::format_speeds(stats.current));
+                                tooltip_parts.extend(Self::speed_lines(stats));
+                                value = Some(Self::speed_value(stats));
+                            }
+                            None => label_parts.push("↓-- ↑--".to_string()),
+                        }
+                    }
+                    tooltip_parts.push(format!("Received: {}", Self::format_bytes(state.rx_bytes)));
+                    tooltip_parts.push(format!("Sent: {}", Self::format_bytes(state.tx_bytes)));
+
+                    let tunnel = state.tunnel || connection.is_some_and(|c| c.kind == ConnectionKind::Vpn);
+                    if !tunnel {
+                        totals.0 += state.rx_bytes;
+                        totals.1 += state.tx_bytes;
+                    }
+                    let speeds = stats.map_or_else(|| "↓-- ↑--".to_string(), |s| Self::format_speeds(s.current));
+                    let suffix = if tunnel { " (tunnel, not counted)" } else { "" };
+                    breakdown.push(format!("{}: {}{}", interface, speeds, suffix));
+                }
+                drop(traffic);
+
+                Self::status_item(&label_parts.join(" "), icon, tooltip_parts.join("\n"))
+            } else {
+                Self::status_item(
+                    interface,
+                    "network-offline",
+                    format!("Interface {} is disconnected", interface),
+                )
+            };
+
+            item.id = format!("network:{}", interface);
+            item.value = value;
+            items.push(item);
+        }
+
+        if show_total && !breakdown.is_empty() && interfaces.len() > 1 {
+            let stats = SpeedStats::from_history(&*self.total_history.read().await);
+            let mut tooltip_parts = Vec::new();
+            let value = stats.as_ref().filter(|_| show_speed).map(Self::speed_value);
+            let label = if show_speed {
+                match stats {
+                    Some(ref stats) => {
+                        tooltip_parts.extend(Self::speed_lines(stats));
+                        format!("Total {}", Self::format_speeds(stats.current))
+                    }
+                    None => "Total ↓-- ↑--".to_string(),
+                }
+            } else {
+                format!("Total ↓{} ↑{}", Self::format_bytes(totals.0), Self::format_bytes(totals.1))
+            };
+            tooltip_parts.push(format!("Received: {}", Self::format_bytes(totals.0)));
+            tooltip_parts.push(format!("Sent: {}", Self::format_bytes(totals.1)));
+            tooltip_parts.extend(breakdown);
+
+            let mut item = Self::status_item(&label, "network-transmit-receive", tooltip_parts.join("\n"));
+            item.id = "network:total".to_string();
+            item.value = value;
+            items.push(item);
+        }
+
+        items
+    }
+
+    /// Metrics for alert rules: `network.connected`, the current speed of
+    /// each tracked interface (`network.<interface>.rx`/`.tx`) and the
+    /// combined speed without tunnels (`network.rx`/`.tx`), in bytes per second
+    fn create_metrics(connected: bool, traffic: &HashMap<String, InterfaceTraffic>) -> Vec<Metric> {
+        let mut metrics = vec![Metric::flag("network.connected", connected)];
+        let mut names: Vec<&String> = traffic.keys().collect();
+        names.sort();
+
+        let mut total = (0u64, 0u64);
+        for name in names {
+            let state = &traffic[name];
+            let Some(&(rx, tx)) = state.history.back() else {
+                continue;
+            };
+            metrics.push(Metric::new(format!("network.{}.rx", name), rx as f64));
+            metrics.push(Metric::new(format!("network.{}.tx", name), tx as f64));
+            if !state.tunnel {
+                total.0 += rx;
+                total.1 += tx;
+            }
+        }
+        metrics.push(Metric::new("network.rx", total.0 as f64));
+        metrics.push(Metric::new("network.tx", total.1 as f64));
+        metrics
+    }
+
+    /// Whether any monitored interface is up, for when NetworkManager isn't used
+    async fn sysfs_connected(&self) -> bool {
+        for interface in self.monitored_interfaces(None).await {
+            if self.is_connected(&interface).await {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Publish the current metrics
+    async fn send_metrics(&self, ctx: &ModuleContext, connected: bool) {
+        let metrics = Self::create_metrics(connected, &*self.traffic.read().await);
+        ctx.send_metrics("network", metrics);
+    }
+
+    /// Item shown when there is no interface to monitor
+    fn no_network_item() -> ModuleItem {
+        Self::status_item("No Network", "network-offline", "No network interface found".to_string())
+    }
+
+    /// Actions available while NetworkManager is in use
+    fn nm_actions() -> Vec<ItemAction> {
+        vec![
+            ItemAction::default_action("context_menu", "Network Connections"),
+            ItemAction::new("toggle_wifi", "Toggle Wi-Fi"),
+            ItemAction::new("toggle_airplane_mode", "Toggle Airplane Mode"),
+        ]
+    }
+
+    /// Label for a visible network, e.g. "HomeNet, 72%, WPA2"
+    fn network_label(network: &WifiNetwork) -> String {
+        format!("{}, {}%, {}", network.ssid, network.strength, network.security)
+    }
+
+    /// Build the connections menu and the targets of its entries
+    fn build_menu(
+        radio: RadioState,
+        networks: &[WifiNetwork],
+        saved: &[SavedConnection],
+        primary: Option<(&str, &str)>,
+    ) -> (Vec<MenuItem>, HashMap<i32, MenuTarget>) {
+        let mut targets = HashMap::new();
+        let mut next_id = MENU_DYNAMIC_BASE;
+        let mut items = vec![
+            MenuItem {
+                id: MENU_WIFI_TOGGLE,
+                label: "Wi-Fi".to_string(),
+                toggle_type: Some("checkmark".to_string()),
+                toggle_state: if radio.wireless_enabled { 1 } else { 0 },
+                ..Default::default()
+            },
+            MenuItem {
+                id: MENU_AIRPLANE_TOGGLE,
+                label: "Airplane mode".to_string(),
+                toggle_type: Some("checkmark".to_string()),
+                toggle_state: if radio.airplane_mode() { 1 } else { 0 },
+                ..Default::default()
+            },
+        ];
+        targets.insert(MENU_WIFI_TOGGLE, MenuTarget::ToggleWifi);
+        targets.insert(MENU_AIRPLANE_TOGGLE, MenuTarget::ToggleAirplaneMode);
+
+        if radio.wireless_enabled {
+            items.push(MenuItem {
+                id: MENU_SEPARATOR_RADIOS,
+                item_type: "separator".to_string(),
+                ..Default::default()
+            });
+
+            if networks.is_empty() {
+                items.push(MenuItem {
+                    id: MENU_NO_NETWORKS,
+                    label: "No Wi-Fi networks found".to_string(),
+                    enabled: false,
+                    ..Default::default()
+                });
+            }
+
+            for network in networks {
+                let saved_profile = saved
+                    .iter()
+                    .find(|s| s.kind == ConnectionKind::Wifi && s.ssid.as_deref() == Some(network.ssid.as_str()));
+                let connected = saved_profile.is_some_and(|s| s.active.is_some());
+
+                items.push(MenuItem {
+                    id: next_id,
+                    label: Self::network_label(network),
+                    toggle_type: Some("radio".to_string()),
+                    toggle_state: if connected { 1 } else { 0 },
+                    ..Default::default()
+                });
+                targets.insert(
+                    next_id,
+                    MenuTarget::Network {
+                        network: network.clone(),
+                        saved_profile: saved_profile.map(|s| s.path.clone()),
+                        connected,
+                    },
+                );
+                next_id += 1;
+            }
+
+            items.push(MenuItem {
+                id: MENU_RESCAN,
+                label: "Scan for networks".to_string(),
+                ..Default::default()
+            });
+            targets.insert(MENU_RESCAN, MenuTarget::Rescan);
+        }
+
+        // Saved profiles and VPNs as checkmark toggles in submenus
+        let mut submenu = |id: i32, label: &str, entries: Vec<&SavedConnection>| {
+            if entries.is_empty() {
+                return None;
+            }
+            let children = entries
+                .into_iter()
+                .map(|profile| {
+                    let entry = MenuItem {
+                        id: next_id,
+                        label: profile.id.clone(),
+                        toggle_type: Some("checkmark".to_string()),
+                        toggle_state: if profile.active.is_some() { 1 } else { 0 },
+                        ..Default::default()
+                    };
+                    targets.insert(next_id, MenuTarget::Saved(profile.clone()));
+                    next_id += 1;
+                    entry
+                })
+                .collect();
+            Some(MenuItem {
+                id,
+                label: label.to_string(),
+                children,
+                ..Default::default()
+            })
+        };
+
+        let (vpns, others): (Vec<&SavedConnection>, Vec<&SavedConnection>) =
+            saved.iter().partition(|s| s.kind == ConnectionKind::Vpn);
+        let mut connections = Vec::new();
+        connections.extend(submenu(MENU_SAVED_SUBMENU, "Saved connections", others));
+        connections.extend(submenu(MENU_VPN_SUBMENU, "VPN", vpns));
+
+        if let Some((name, active_path)) = primary {
+            connections.push(MenuItem {
+                id: MENU_DISCONNECT,
+                label: format!("Disconnect {}", name),
+                ..Default::default()
+            });
+            targets.insert(MENU_DISCONNECT, MenuTarget::Disconnect(active_path.to_string()));
+        }
+
+        if !connections.is_empty() {
+            items.push(MenuItem {
+                id: MENU_SEPARATOR_SAVED,
+                item_type: "separator".to_string(),
+                ..Default::default()
+            });
+            items.extend(connections);
+        }
+
+        (items, targets)
+    }
+
+    /// Get the NetworkManager connection, or an error if the backend isn't in use
+    async fn require_nm(&self) -> anyhow::Result<Connection> {
+        self.nm_connection
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("NetworkManager is not available"))
+    }
+
+    /// Toggle a radio switch and refresh any open menu
+    async fn toggle_radio(&self, target: &MenuTarget) -> anyhow::Result<()> {
+        let connection = self.require_nm().await?;
+        let radio = networkmanager::radio_state(&connection).await?;
+        match target {
+            MenuTarget::ToggleWifi => {
+                networkmanager::set_wireless_enabled(&connection, !radio.wireless_enabled).await?
+            }
+            MenuTarget::ToggleAirplaneMode => {
+                networkmanager::set_airplane_mode(&connection, !radio.airplane_mode()).await?
+            }
+            _ => {}
+        }
+        self.notify_menu_updated().await;
+        Ok(())
+    }
+
+    /// Ask clients with the menu open to refetch it
+    async fn notify_menu_updated(&self) {
+        if let Some(ctx) = self.ctx.read().await.as_ref() {
+            ctx.send_menu_updated("network:status");
+        }
+    }
+
+    /// Tell the user an action failed (connection attempts fail asynchronously
+    /// from the user's point of view, e.g. without a password agent)
+    async fn notify_failure(&self, summary: &str, error: &anyhow::Error) {
+        tracing::warn!("{}: {}", summary, error);
+        if let Some(ctx) = self.ctx.read().await.as_ref() {
+            ctx.send_notification(summary, &error.to_string(), Urgency::Normal);
+        }
+    }
+
+    /// Perform the action of a menu entry
+    async fn activate_target(&self, target: MenuTarget) -> anyhow::Result<()> {
+        let connection = self.require_nm().await?;
+        match target {
+            MenuTarget::ToggleWifi | MenuTarget::ToggleAirplaneMode => {
+                self.toggle_radio(&target).await?
+            }
+            MenuTarget::Rescan => networkmanager::request_scan(&connection).await?,
+            MenuTarget::Network { connected: true, .. } => {}
+            MenuTarget::Network {
+                network,
+                saved_profile,
+                ..
+            } => {
+                if let Err(e) =
+                    networkmanager::connect_wifi(&connection, &network, saved_profile.as_deref()).await
+                {
+                    let e = anyhow::Error::from(e);
+                    self.notify_failure(&format!("Failed to connect to {}", network.ssid), &e)
+                        .await;
+                    return Err(e);
+                }
+            }
+            MenuTarget::Saved(profile) => {
+                let result = match profile.active {
+                    Some(ref active) => networkmanager::deactivate(&connection, active).await,
+                    None => networkmanager::activate_saved(&connection, &profile.path).await,
+                };
+                if let Err(e) = result {
+                    let e = anyhow::Error::from(e);
+                    self.notify_failure(&format!("Failed to switch {}", profile.id), &e)
+                        .await;
+                    return Err(e);
+                }
+            }
+            MenuTarget::Disconnect(active) => networkmanager::deactivate(&connection, &active).await?,
+        }
+        Ok(())
+    }
+
+    /// Connect to the system bus if NetworkManager is running there
+    async fn connect_network_manager() -> Option<Connection> {
+        let connection = match Connection::system().await {
+            Ok(c) => c,
+            Err(e) => {
+                tracing::debug!("Failed to connect to system D-Bus for NetworkManager: {}", e);
+                return None;
+            }
+        };
+
+        if networkmanager::is_available(&connection).await {
+            Some(connection)
+        } else {
+            None
+        }
+    }
+
+    /// Wait for the next strength change of the active access point, if any
+    async fn next_strength(stream: &mut Option<PropertyStream<'static, u8>>) -> Option<u8> {
+        match stream {
+            Some(stream) => stream.next().await?.get().await.ok(),
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Watch the signal strength of an access point
+    async fn watch_strength(
+        connection: &Connection,
+        access_point: Option<&str>,
+    ) -> Option<PropertyStream<'static, u8>> {
+        let proxy = AccessPointProxy::builder(connection)
+            .path(access_point?.to_string())
+            .ok()?
+            .build()
+            .await
+            .ok()?;
+        Some(proxy.receive_strength_changed().await)
+    }
+
+    /// Follow NetworkManager signals until cancelled
+    async fn run_network_manager(&self, ctx: &ModuleContext, connection: Connection) {
+        let nm = match NetworkManagerProxy::new(&connection).await {
+            Ok(proxy) => proxy,
+            Err(e) => {
+                tracing::warn!("Failed to create NetworkManager proxy: {}", e);
+                return;
+            }
+        };
+
+        let mut state_changed = match nm.receive_state_changed().await {
+            Ok(stream) => stream,
+            Err(e) => {
+                tracing::warn!("Failed to subscribe to NetworkManager StateChanged: {}", e);
+                return;
+            }
+        };
+        let mut primary_changed = nm.receive_primary_connection_changed().await;
+        let mut active_changed = nm.receive_active_connections_changed().await;
+        let mut wireless_changed = nm.receive_wireless_enabled_changed().await;
+
+        loop {
+            let mut snapshot = match networkmanager::fetch_snapshot(&connection).await {
+                Ok(snapshot) => Some(snapshot),
+                Err(e) => {
+                    tracing::debug!("Failed to read NetworkManager state: {}", e);
+                    None
+                }
+            };
+
+            let access_point = snapshot
+                .as_ref()
+                .and_then(|s| s.primary.as_ref())
+                .and_then(|p| p.access_point.clone());
+            let mut strength_changed = Self::watch_strength(&connection, access_point.as_deref()).await;
+
+            // Connection changes affect the radio and checkmark states of an open menu
+            ctx.send_menu_updated("network:status");
+
+            let primary_interface = snapshot
+                .as_ref()
+                .and_then(|s| s.primary.as_ref())
+                .and_then(|p| p.interface.clone());
+            let interfaces = self.monitored_interfaces(primary_interface.as_deref()).await;
+            self.track_interfaces(&interfaces).await;
+
+            // Redraw on strength changes and speed ticks; refetch on state changes
+            loop {
+                let (items, connected) = match snapshot {
+                    Some(ref snapshot) => (self.create_nm_items(snapshot).await, snapshot.state.is_connected()),
+                    None => (self.create_items().await, self.sysfs_connected().await),
+                };
+                ctx.send_items("network", items);
+                self.send_metrics(ctx, connected).await;
+
+                let (interval, show_speed, multi_interface) = {
+                    let config = self.config.read().await;
+                    (
+                        Duration::from_secs(config.interval_seconds),
+                        config.show_speed,
+                        !config.interfaces.is_empty(),
+                    )
+                };
+
+                tokio::select! {
+                    _ = ctx.cancelled() => return,
+                    Some(_) = state_changed.next() => break,
+                    Some(_) = primary_changed.next() => break,
+                    Some(_) = active_changed.next() => break,
+                    Some(_) = wireless_changed.next() => break,
+                    Some(strength) = Self::next_strength(&mut strength_changed) => {
+                        if let Some(primary) = snapshot.as_mut().and_then(|s| s.primary.as_mut()) {
+                            primary.strength = Some(strength);
+                        }
+                    }
+                    // Tunnels come and go without NetworkManager, so poll them too
+                    _ = tokio::time::sleep(interval), if show_speed || multi_interface || snapshot.is_none() => {
+                        let interfaces = self.monitored_interfaces(primary_interface.as_deref()).await;
+                        self.sample_traffic(&interfaces).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Poll /sys/class/net until cancelled
+    async fn run_sysfs(&self, ctx: &ModuleContext) {
+        // Initial read to populate traffic state
+        let interfaces = self.monitored_interfaces(None).await;
+        self.sample_traffic(&interfaces).await;
+
+        // Wait a moment for first delta (cancellable)
+        let interval = {
+            let config = self.config.read().await;
+            Duration::from_secs(config.interval_seconds)
+        };
+        tokio::select! {
+            _ = ctx.cancelled() => return,
+            _ = tokio::time::sleep(interval) => {}
+        }
+
+        // Send initial items
+        let interfaces = self.monitored_interfaces(None).await;
+        self.sample_traffic(&interfaces).await;
+        let items = self.create_items().await;
+        ctx.send_items("network", items);
+        let connected = self.sysfs_connected().await;
+        self.send_metrics(ctx, connected).await;
+
+        // Poll at configured interval
+        loop {
+            let interval = {
+                let config = self.config.read().await;
+                Duration::from_secs(config.interval_seconds)
+            };
+
+            tokio::select! {
+                _ = ctx.cancelled() => break,
+                _ = tokio::time::sleep(interval) => {
+                    let interfaces = self.monitored_interfaces(None).await;
+                    self.sample_traffic(&interfaces).await;
+                    let items = self.create_items().await;
+                    ctx.send_items("network", items);
+                    let connected = self.sysfs_connected().await;
+                    self.send_metrics(ctx, connected).await;
+                }
+            }
+        }
+    }
+
     async fn create_items(&self) -> Vec<ModuleItem> {
         let config = self.config.read().await;
-        let interval = config.interval_seconds;
         let show_ip = config.show_ip;
         let show_speed = config.show_speed;
         drop(config);
 
+        if self.is_multi_interface().await {
+            let interfaces = self.monitored_interfaces(None).await;
+            if interfaces.is_empty() {
+                return vec![Self::no_network_item()];
+            }
+            return self.create_interface_items(&interfaces, None).await;
+        }
+
         let Some(interface) = self.get_interface().await else {
             // No interface found
-            return vec![ModuleItem {
-                id: "network:status".to_string(),
-                module: "network".to_string(),
-                label: "No Network".to_string(),
-                icon_name: Some("network-offline".to_string()),
-                icon_pixmap: None,
-                icon_width: 0,
-                icon_height: 0,
-                tooltip: Some("No network interface found".to_string()),
-                actions: Vec::new(),
-            }];
+            return vec![Self::no_network_item()];
         };
 
         let connected = self.is_connected(&interface).await;
@@ -193,7 +1246,12 @@ impl NetworkModule {
                 icon_pixmap: None,
                 icon_width: 0,
                 icon_height: 0,
+                attention_icon: None,
+                overlay_icon: None,
+                status: ItemStatus::Active,
+                category: None,
                 tooltip: Some(format!("Interface {} is disconnected", interface)),
+                value: None,
                 actions: Vec::new(),
             }];
         }
@@ -205,21 +1263,20 @@ impl NetworkModule {
         let mut label_parts = Vec::new();
         let mut tooltip_parts = vec![format!("Interface: {}", interface)];
 
-        // IP address
+        // IP addresses
         if show_ip {
-            if let Some(ip) = self.get_ip_address(&interface).await {
-                tooltip_parts.push(format!("IP: {}", ip));
-            }
+            let (ipv4, ipv6) = self.get_ip_addresses(&interface).await;
+            tooltip_parts.extend(ipv4.iter().map(|ip| format!("IP: {}", ip)));
+            tooltip_parts.extend(ipv6.iter().map(|ip| format!("IPv6: {}", ip)));
         }
 
         // Speed
+        let mut value = None;
         if show_speed {
-            if let Some((rx_speed, tx_speed)) = self.get_speed(&interface, interval).await {
-                let rx_str = Self::format_speed(rx_speed);
-                let tx_str = Self::format_speed(tx_speed);
-                label_parts.push(format!("↓{} ↑{}", rx_str, tx_str));
-                tooltip_parts.push(format!("Download: {}", rx_str));
-                tooltip_parts.push(format!("Upload: {}", tx_str));
+            if let Some(stats) = self.speed_stats(&interface).await {
+                label_parts.push(Self::format_speeds(stats.current));
+                tooltip_parts.extend(Self::speed_lines(&stats));
+                value = Some(Self::speed_value(&stats));
             } else {
                 // First reading, show placeholder
                 label_parts.push("↓-- ↑--".to_string());
@@ -240,7 +1297,12 @@ impl NetworkModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip_parts.join("\n")),
+            value,
             actions: Vec::new(),
         });
 
@@ -263,48 +1325,80 @@ impl Module for NetworkModule {
             return;
         }
 
-        // Initial read to populate traffic state
-        if let Some(interface) = self.get_interface().await {
-            let _ = self.get_traffic_bytes(&interface).await;
-        }
-
-        // Wait a moment for first delta (cancellable)
-        let interval = {
-            let config = self.config.read().await;
-            Duration::from_secs(config.interval_seconds)
-        };
-        tokio::select! {
-            _ = ctx.cancelled() => return,
-            _ = tokio::time::sleep(interval) => {}
+        if self.config.read().await.use_networkmanager {
+            if let Some(connection) = Self::connect_network_manager().await {
+                tracing::info!("Network module using NetworkManager");
+                *self.nm_connection.write().await = Some(connection.clone());
+                *self.ctx.write().await = Some(ctx.clone());
+                self.run_network_manager(&ctx, connection).await;
+                return;
+            }
+            tracing::info!("NetworkManager not available, reading /sys/class/net");
         }
 
-        // Send initial items
-        let items = self.create_items().await;
-        ctx.send_items("network", items);
+        self.run_sysfs(&ctx).await;
+    }
 
-        // Poll at configured interval
-        loop {
-            let interval = {
-                let config = self.config.read().await;
-                Duration::from_secs(config.interval_seconds)
-            };
+    async fn stop(&self) {
+        *self.nm_connection.write().await = None;
+        *self.ctx.write().await = None;
+        self.menu_targets.write().await.clear();
+        tracing::info!("Network module stopped");
+    }
 
-            tokio::select! {
-                _ = ctx.cancelled() => break,
-                _ = tokio::time::sleep(interval) => {
-                    let items = self.create_items().await;
-                    ctx.send_items("network", items);
-                }
+    async fn invoke_action(&self, _item_id: &str, action_id: &str, _x: i32, _y: i32) {
+        let target = match action_id {
+            "toggle_wifi" => MenuTarget::ToggleWifi,
+            "toggle_airplane_mode" => MenuTarget::ToggleAirplaneMode,
+            "context_menu" => {
+                // Context menu is handled via get_menu_items/activate_menu_item
+                return;
+            }
+            _ => {
+                tracing::warn!("Unknown action: {}", action_id);
+                return;
             }
+        };
+
+        if let Err(e) = self.toggle_radio(&target).await {
+            tracing::warn!("Network action {} failed: {}", action_id, e);
         }
     }
 
-    async fn stop(&self) {
-        tracing::info!("Network module stopped");
+    async fn get_menu_items(&self, item_id: &str) -> anyhow::Result<Vec<MenuItem>> {
+        anyhow::ensure!(item_id == "network:status", "No menu for item: {}", item_id);
+        let connection = self.require_nm().await?;
+
+        let radio = networkmanager::radio_state(&connection).await?;
+        let networks = if radio.wireless_enabled {
+            networkmanager::list_wifi_networks(&connection).await?
+        } else {
+            Vec::new()
+        };
+        let saved = networkmanager::list_saved_connections(&connection).await?;
+        let snapshot = networkmanager::fetch_snapshot(&connection).await?;
+        let primary = snapshot
+            .primary
+            .as_ref()
+            .map(|p| (p.id.as_str(), p.path.as_str()));
+
+        let (items, targets) = Self::build_menu(radio, &networks, &saved, primary);
+        *self.menu_targets.write().await = targets;
+        Ok(items)
     }
 
-    async fn invoke_action(&self, _item_id: &str, _action_id: &str, _x: i32, _y: i32) {
-        // Network module has no actions
+    async fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> anyhow::Result<()> {
+        anyhow::ensure!(item_id == "network:status", "No menu for item: {}", item_id);
+
+        let target = self
+            .menu_targets
+            .read()
+            .await
+            .get(&menu_item_id)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("Unknown menu item id: {}", menu_item_id))?;
+
+        self.activate_target(target).await
     }
 
     async fn reload_config(&self, config: &crate::config::Config) -> bool {
@@ -318,3 +1412,278 @@ impl Module for NetworkModule {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::networkmanager::{NmConnection, NmState};
+
+    fn connection(id: &str, kind: ConnectionKind) -> NmConnection {
+        NmConnection {
+            path: format!("/org/freedesktop/NetworkManager/ActiveConnection/{}", id),
+            id: id.to_string(),
+            kind,
+            interface: None,
+            ssid: None,
+            strength: None,
+            access_point: None,
+            ipv4: Some("10.0.0.2".to_string()),
+            ipv6: None,
+        }
+    }
+
+    fn module() -> NetworkModule {
+        NetworkModule::new(NetworkModuleConfig {
+            show_speed: false,
+            show_ip: true,
+            ..NetworkModuleConfig::default()
+        })
+    }
+
+    #[test]
+    fn test_wifi_icon_by_strength() {
+        assert_eq!(
+            NetworkModule::get_icon_for_connection(ConnectionKind::Wifi, Some(90)),
+            "network-wireless-signal-excellent"
+        );
+        assert_eq!(
+            NetworkModule::get_icon_for_connection(ConnectionKind::Wifi, Some(40)),
+            "network-wireless-signal-ok"
+        );
+        assert_eq!(
+            NetworkModule::get_icon_for_connection(ConnectionKind::Wifi, None),
+            "network-wireless-signal-none"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wifi_with_vpn_items() {
+        let mut wifi = connection("Home", ConnectionKind::Wifi);
+        wifi.ssid = Some("HomeNet".to_string());
+        wifi.strength = Some(72);
+        let snapshot = NmSnapshot {
+            state: NmState::ConnectedGlobal,
+            connectivity: 4,
+            wireless_enabled: true,
+            primary: Some(wifi),
+            vpns: vec![connection("Work VPN", ConnectionKind::Vpn)],
+            active: Vec::new(),
+        };
+
+        let items = module().create_nm_items(&snapshot).await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].label, "HomeNet 72%");
+        assert_eq!(items[0].icon_name.as_deref(), Some("network-wireless-signal-good"));
+        assert_eq!(
+            items[0].overlay_icon.as_ref().and_then(|i| i.name.as_deref()),
+            Some("network-vpn")
+        );
+        let tooltip = items[0].tooltip.as_deref().unwrap();
+        assert!(tooltip.contains("Connection: Home"));
+        assert!(tooltip.contains("IP: 10.0.0.2"));
+        assert!(tooltip.contains("VPN: Work VPN"));
+    }
+
+    #[tokio::test]
+    async fn test_disconnected_items() {
+        let snapshot = NmSnapshot {
+            state: NmState::Disconnected,
+            connectivity: 1,
+            wireless_enabled: false,
+            primary: None,
+            vpns: Vec::new(),
+            active: Vec::new(),
+        };
+
+        let items = module().create_nm_items(&snapshot).await;
+        assert_eq!(items[0].label, "Disconnected");
+        assert_eq!(items[0].icon_name.as_deref(), Some("network-offline"));
+        assert!(items[0].tooltip.as_deref().unwrap().contains("Wi-Fi is disabled"));
+    }
+
+    #[test]
+    fn test_match_interfaces() {
+        let available = vec![
+            ("enp0s31f6".to_string(), true),
+            ("enx00e04c".to_string(), false),
+            ("lo".to_string(), true),
+            ("wg0".to_string(), true),
+            ("wlan0".to_string(), true),
+        ];
+        let patterns = |list: &[&str]| list.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            NetworkModule::match_interfaces(&patterns(&["*"]), &available),
+            vec!["enp0s31f6", "wg0", "wlan0"]
+        );
+        assert_eq!(
+            NetworkModule::match_interfaces(&patterns(&["en*", "eth1", "wlan0", "wl*"]), &available),
+            vec!["enp0s31f6", "eth1", "wlan0"]
+        );
+    }
+
+    #[test]
+    fn test_is_up() {
+        assert!(NetworkModule::is_up("up\n", ""));
+        assert!(!NetworkModule::is_up("down\n", "0x1003\n"));
+        // WireGuard: operstate unknown, IFF_UP set
+        assert!(NetworkModule::is_up("unknown\n", "0x91\n"));
+        assert!(!NetworkModule::is_up("unknown\n", "0x90\n"));
+    }
+
+    #[test]
+    fn test_parse_ip_addresses() {
+        let output = "\
+3: wlan0: <BROADCAST,MULTICAST,UP,LOWER_UP> mtu 1500 qdisc noqueue state UP group default qlen 1000
+    link/ether 00:11:22:33:44:55 brd ff:ff:ff:ff:ff:ff
+    inet 192.168.1.20/24 brd 192.168.1.255 scope global dynamic noprefixroute wlan0
+       valid_lft 85000sec preferred_lft 85000sec
+    inet6 2001:db8::20/64 scope global dynamic noprefixroute
+       valid_lft 3500sec preferred_lft 3500sec
+    inet6 fe80::1234/64 scope link noprefixroute
+       valid_lft forever preferred_lft forever
+";
+        let (ipv4, ipv6) = NetworkModule::parse_ip_addresses(output);
+        assert_eq!(ipv4, vec!["192.168.1.20"]);
+        assert_eq!(ipv6, vec!["2001:db8::20"]);
+    }
+
+    #[test]
+    fn test_speed_history() {
+        let start = Instant::now();
+        let mut traffic = InterfaceTraffic::new(0, 0, start, false);
+        assert_eq!(traffic.record(0, 0, start, 3), None);
+
+        let samples = [(2_000, 200), (8_000, 400), (10_000, 600), (12_000, 800)];
+        for (i, (rx, tx)) in samples.iter().enumerate() {
+            let now = start + Duration::from_secs(2 * (i as u64 + 1));
+            traffic.record(*rx, *tx, now, 3);
+        }
+
+        // Only the last three samples are kept: 3000, 1000 and 1000 B/s down
+        let stats = SpeedStats::from_history(&traffic.history).unwrap();
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.current, (1_000, 100));
+        assert_eq!(stats.average, (1_666, 100));
+        assert_eq!(stats.peak, (3_000, 100));
+        assert_eq!(
+            NetworkModule::speed_lines(&stats)[0],
+            "Download: 1KB/s (avg 2KB/s, peak 3KB/s)"
+        );
+
+        let value = NetworkModule::speed_value(&stats);
+        assert_eq!(value.fraction(), Some(1.0 / 3.0));
+        assert_eq!(value.spoken(), "1.0 kilobytes per second");
+    }
+
+    #[test]
+    fn test_metrics() {
+        let start = Instant::now();
+        let later = start + Duration::from_secs(1);
+        let mut traffic = HashMap::new();
+        for (name, tunnel) in [("wlan0", false), ("wg0", true), ("eth0", false)] {
+            let mut state = InterfaceTraffic::new(0, 0, start, tunnel);
+            if name != "eth0" {
+                state.record(3_000, 1_000, later, 3);
+            }
+            traffic.insert(name.to_string(), state);
+        }
+
+        let metrics = NetworkModule::create_metrics(true, &traffic);
+        let names: Vec<&str> = metrics.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["network.connected", "network.wg0.rx", "network.wg0.tx", "network.wlan0.rx", "network.wlan0.tx", "network.rx", "network.tx"]
+        );
+        assert_eq!(metrics[0].value, 1.0);
+        // The tunnel isn't counted twice
+        assert_eq!(metrics[5].value, 3_000.0);
+    }
+
+    #[test]
+    fn test_build_menu() {
+        let radio = RadioState {
+            wireless_enabled: true,
+            wwan_enabled: true,
+            has_modem: false,
+        };
+        let networks = vec![WifiNetwork {
+            ssid: "HomeNet".to_string(),
+            strength: 72,
+            security: "WPA2",
+            access_point: "/ap/1".to_string(),
+            device: "/dev/3".to_string(),
+        }];
+        let saved = vec![
+            SavedConnection {
+                path: "/settings/1".to_string(),
+                id: "Home".to_string(),
+                uuid: "uuid-home".to_string(),
+                kind: ConnectionKind::Wifi,
+                ssid: Some("HomeNet".to_string()),
+                active: Some("/active/1".to_string()),
+            },
+            SavedConnection {
+                path: "/settings/2".to_string(),
+                id: "Work VPN".to_string(),
+                uuid: "uuid-vpn".to_string(),
+                kind: ConnectionKind::Vpn,
+                ssid: None,
+                active: None,
+            },
+        ];
+
+        let (items, targets) =
+            NetworkModule::build_menu(radio, &networks, &saved, Some(("Home", "/active/1")));
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec![
+                "Wi-Fi",
+                "Airplane mode",
+                "",
+                "HomeNet, 72%, WPA2",
+                "Scan for networks",
+                "",
+                "Saved connections",
+                "VPN",
+                "Disconnect Home",
+            ]
+        );
+        assert_eq!(items[0].toggle_state, 1);
+        assert_eq!(items[1].toggle_state, 0);
+        assert_eq!(items[3].toggle_state, 1);
+        assert_eq!(items[7].children[0].label, "Work VPN");
+        assert_eq!(items[7].children[0].toggle_state, 0);
+
+        match targets.get(&items[3].id) {
+            Some(MenuTarget::Network { saved_profile, connected, .. }) => {
+                assert_eq!(saved_profile.as_deref(), Some("/settings/1"));
+                assert!(*connected);
+            }
+            other => panic!("unexpected target: {:?}", other),
+        }
+        assert_eq!(
+            targets.get(&items[7].children[0].id),
+            Some(&MenuTarget::Saved(saved[1].clone()))
+        );
+        assert_eq!(
+            targets.get(&MENU_DISCONNECT),
+            Some(&MenuTarget::Disconnect("/active/1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_build_menu_wifi_disabled() {
+        let radio = RadioState {
+            wireless_enabled: false,
+            wwan_enabled: true,
+            has_modem: false,
+        };
+        let (items, targets) = NetworkModule::build_menu(radio, &[], &[], None);
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["Wi-Fi", "Airplane mode"]);
+        assert_eq!(items[1].toggle_state, 1);
+        assert!(!targets.contains_key(&MENU_RESCAN));
+    }
+}
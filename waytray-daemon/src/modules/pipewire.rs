@@ -1,89 +1,123 @@
 //! PipeWire/PulseAudio module - displays volume status and allows control
 //!
-//! Uses pactl commands to communicate with PulseAudio or PipeWire (via pipewire-pulse).
-//! This approach is more reliable than direct libpulse bindings for our use case.
+//! Speaks the PulseAudio native protocol directly, which PipeWire also serves via
+//! pipewire-pulse. A subscription on a second connection tells us when sinks,
+//! sources or the server defaults change, so items are only rebuilt on events
+//! and only sent when something visible actually changed.
 
-use std::process::Command;
 use std::sync::Arc;
 use std::time::Duration;
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use crate::config::PipewireModuleConfig;
-use super::{Module, ModuleContext, ModuleItem, ItemAction};
+use crate::ItemStatus;
+use crate::dbusmenu::MenuItem;
+use crate::pulse::{self, ChannelVolume, Prop, Props, PulseClient, PulseSubscription, SubscriptionMask};
+use super::{Adjustment, Module, ModuleContext, ModuleItem, ItemAction, ItemValue};
+
+/// Client name announced to the audio server
+const CLIENT_NAME: &str = "waytray";
+
+/// Delay before reconnecting after the audio server went away
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Events arrive in bursts (e.g. sink change + server change); wait before refreshing
+const EVENT_DEBOUNCE: Duration = Duration::from_millis(30);
+
+/// Menu ids for output/input devices are this base plus the device index
+const DEVICE_MENU_BASE: i32 = 1;
+
+/// Menu ids for application streams start here; each stream uses a block of 4 ids
+const STREAM_MENU_BASE: i32 = 1_000_000;
+const MENU_SEPARATOR_ID: i32 = STREAM_MENU_BASE - 2;
+const MENU_NO_STREAMS_ID: i32 = STREAM_MENU_BASE - 1;
+const STREAM_MENU_SUBMENU: i32 = 0;
+const STREAM_MENU_MUTE: i32 = 1;
+const STREAM_MENU_VOLUME_UP: i32 = 2;
+const STREAM_MENU_VOLUME_DOWN: i32 = 3;
 
 /// Current audio output (sink) state
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 struct AudioState {
+    index: u32,
+    volume: ChannelVolume,
     volume_percent: u32,
     muted: bool,
     sink_name: String,
 }
 
-impl Default for AudioState {
-    fn default() -> Self {
-        Self {
-            volume_percent: 0,
-            muted: false,
-            sink_name: String::new(),
+/// Current microphone (source) state
+#[derive(Debug, Clone)]
+struct MicState {
+    index: u32,
+    volume: ChannelVolume,
+    volume_percent: u32,
+    muted: bool,
+    source_name: String,
+}
+
+/// Direction of an application stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StreamKind {
+    /// Sink input (an app playing audio)
+    Playback,
+    /// Source output (an app recording audio)
+    Recording,
+}
+
+impl StreamKind {
+    fn item_prefix(&self) -> &'static str {
+        match self {
+            StreamKind::Playback => "pipewire:playback-",
+            StreamKind::Recording => "pipewire:recording-",
         }
     }
 }
 
-/// Current microphone (source) state
-#[derive(Debug, Clone, PartialEq)]
-struct MicState {
+/// Current state of an application stream
+#[derive(Debug, Clone)]
+struct StreamState {
+    kind: StreamKind,
+    index: u32,
+    app_name: String,
+    media_name: Option<String>,
+    volume: ChannelVolume,
     volume_percent: u32,
     muted: bool,
-    source_name: String,
+    corked: bool,
+    /// Whether the stream's volume can be changed (e.g. not for passthrough streams)
+    volume_writable: bool,
+}
+
+/// An output (sink) or input (source) device that can be made the default
+#[derive(Debug, Clone)]
+struct DeviceEntry {
+    index: u32,
+    name: std::ffi::CString,
+    description: String,
+    is_default: bool,
 }
 
 /// PipeWire/PulseAudio module for volume control
 pub struct PipewireModule {
     config: RwLock<PipewireModuleConfig>,
-    ctx: RwLock<Option<Arc<ModuleContext>>>,
+    client: RwLock<Option<PulseClient>>,
+    /// Wakes the event loop when a refresh is needed without a server event
+    refresh: Notify,
 }
 
 impl PipewireModule {
     pub fn new(config: PipewireModuleConfig) -> Self {
         Self {
             config: RwLock::new(config),
-            ctx: RwLock::new(None),
-        }
-    }
-
-    /// Send an immediate update to the client
-    async fn send_update(&self) {
-        let ctx_lock = self.ctx.read().await;
-        if let Some(ctx) = ctx_lock.as_ref() {
-            let config = self.config.read().await;
-            let mut items = Vec::new();
-
-            if let Some(state) = Self::get_audio_state() {
-                drop(config); // Release before awaiting
-                items.push(self.create_module_item(&state).await);
-            } else {
-                drop(config);
-            }
-
-            let config = self.config.read().await;
-            if config.show_microphone {
-                if let Some(mic_state) = Self::get_mic_state() {
-                    drop(config);
-                    items.push(self.create_mic_module_item(&mic_state).await);
-                }
-            }
-
-            if !items.is_empty() {
-                ctx.send_items("pipewire", items);
-            }
+            client: RwLock::new(None),
+            refresh: Notify::new(),
         }
     }
 
     fn get_icon_name(volume_percent: u32, muted: bool) -> &'static str {
-        if muted {
-            "audio-volume-muted"
-        } else if volume_percent == 0 {
+        if muted || volume_percent == 0 {
             "audio-volume-muted"
         } else if volume_percent < 33 {
             "audio-volume-low"
@@ -106,261 +140,434 @@ impl PipewireModule {
         }
     }
 
-    /// Get current audio state using pactl
-    fn get_audio_state() -> Option<AudioState> {
-        // Get default sink name
-        let default_sink = match Command::new("pactl")
-            .args(["get-default-sink"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-default-sink failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
-                }
-                output
-            }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
-            }
+    /// Get the default sink's state
+    async fn get_audio_state(client: &PulseClient) -> anyhow::Result<Option<AudioState>> {
+        let server = client.server_info().await?;
+        let Some(default_sink) = server.default_sink_name else {
+            return Ok(None);
         };
 
-        let sink_name = String::from_utf8_lossy(&default_sink.stdout)
-            .trim()
-            .to_string();
-
-        if sink_name.is_empty() {
-            return None;
-        }
-
-        // Get volume
-        let volume_output = match Command::new("pactl")
-            .args(["get-sink-volume", "@DEFAULT_SINK@"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-sink-volume failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
-                }
-                output
-            }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
+        let sinks = client.sinks().await?;
+        Ok(sinks.into_iter().find(|s| s.name == default_sink).map(|sink| {
+            // Prefer the description for a nicer name
+            let sink_name = sink
+                .description
+                .as_ref()
+                .unwrap_or(&sink.name)
+                .to_string_lossy()
+                .into_owned();
+
+            AudioState {
+                index: sink.index,
+                volume_percent: pulse::volume_percent(&sink.cvolume),
+                volume: sink.cvolume,
+                muted: sink.muted,
+                sink_name,
             }
+        }))
+    }
+
+    /// Get the default source's state
+    async fn get_mic_state(client: &PulseClient) -> anyhow::Result<Option<MicState>> {
+        let server = client.server_info().await?;
+        let Some(default_source) = server.default_source_name else {
+            return Ok(None);
         };
 
-        let volume_str = String::from_utf8_lossy(&volume_output.stdout);
-        let volume_percent = Self::parse_volume(&volume_str).unwrap_or(0);
-
-        // Get mute status
-        let mute_output = match Command::new("pactl")
-            .args(["get-sink-mute", "@DEFAULT_SINK@"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-sink-mute failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
-                }
-                output
-            }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
-            }
+        let sources = client.sources().await?;
+        let Some(source) = sources.into_iter().find(|s| s.name == default_source) else {
+            return Ok(None);
         };
 
-        let mute_str = String::from_utf8_lossy(&mute_output.stdout);
-        let muted = mute_str.contains("yes");
-
-        // Get sink description for a nicer name
-        let sink_description = Self::get_sink_description(&sink_name)
-            .unwrap_or_else(|| sink_name.clone());
-
-        Some(AudioState {
-            volume_percent,
-            muted,
-            sink_name: sink_description,
-        })
-    }
-
-    /// Parse volume percentage from pactl output
-    /// Format: "Volume: front-left: 65536 / 100% / -0.00 dB,   front-right: 65536 / 100% / -0.00 dB"
-    fn parse_volume(output: &str) -> Option<u32> {
-        // Find the first percentage value
-        for part in output.split('/') {
-            let trimmed = part.trim();
-            if let Some(percent_str) = trimmed.strip_suffix('%') {
-                if let Ok(percent) = percent_str.trim().parse::<u32>() {
-                    return Some(percent);
-                }
-            }
+        // Skip monitor sources (they're not real microphones)
+        if source.monitor_of_sink_index.is_some() {
+            tracing::debug!("Default source is a monitor, no microphone available");
+            return Ok(None);
         }
-        None
+
+        let source_name = source
+            .description
+            .as_ref()
+            .unwrap_or(&source.name)
+            .to_string_lossy()
+            .into_owned();
+
+        Ok(Some(MicState {
+            index: source.index,
+            volume_percent: pulse::volume_percent(&source.cvolume),
+            volume: source.cvolume,
+            muted: source.muted,
+            source_name,
+        }))
     }
 
-    /// Get sink description from pactl list-sinks
-    fn get_sink_description(sink_name: &str) -> Option<String> {
-        let output = Command::new("pactl")
-            .args(["list", "sinks"])
-            .output()
-            .ok()?;
+    /// Display name for a stream: the application name, falling back to the binary
+    fn stream_app_name(props: &Props, fallback: &std::ffi::CStr) -> String {
+        pulse::prop_string(props, Prop::ApplicationName)
+            .or_else(|| pulse::prop_string(props, Prop::ApplicationProcessBinary))
+            .unwrap_or_else(|| fallback.to_string_lossy().into_owned())
+    }
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut in_target_sink = false;
+    /// Get all application streams of one kind
+    async fn get_streams(client: &PulseClient, kind: StreamKind) -> anyhow::Result<Vec<StreamState>> {
+        let streams = match kind {
+            StreamKind::Playback => client
+                .sink_inputs()
+                .await?
+                .into_iter()
+                .map(|input| StreamState {
+                    kind,
+                    index: input.index,
+                    app_name: Self::stream_app_name(&input.props, &input.name),
+                    media_name: pulse::prop_string(&input.props, Prop::MediaName),
+                    volume_percent: pulse::volume_percent(&input.cvolume),
+                    volume: input.cvolume,
+                    muted: input.muted,
+                    corked: input.corked,
+                    volume_writable: input.has_volume && input.volume_writable,
+                })
+                .collect(),
+            StreamKind::Recording => client
+                .source_outputs()
+                .await?
+                .into_iter()
+                .map(|output| StreamState {
+                    kind,
+                    index: output.index,
+                    app_name: Self::stream_app_name(&output.props, &output.name),
+                    media_name: pulse::prop_string(&output.props, Prop::MediaName),
+                    volume_percent: pulse::volume_percent(&output.cvolume),
+                    volume: output.cvolume,
+                    muted: output.muted,
+                    corked: output.corked,
+                    volume_writable: output.has_volume && output.volume_writable,
+                })
+                .collect(),
+        };
 
-        for line in output_str.lines() {
-            let trimmed = line.trim();
+        Ok(streams)
+    }
 
-            // Check if this is our sink
-            if trimmed.starts_with("Name:") {
-                let name = trimmed.strip_prefix("Name:")?.trim();
-                in_target_sink = name == sink_name;
-            }
+    /// Parse a stream item id like "pipewire:playback-42"
+    fn parse_stream_item_id(item_id: &str) -> Option<(StreamKind, u32)> {
+        [StreamKind::Playback, StreamKind::Recording]
+            .into_iter()
+            .find_map(|kind| {
+                let index = item_id.strip_prefix(kind.item_prefix())?.parse().ok()?;
+                Some((kind, index))
+            })
+    }
 
-            // Get description if we're in the right sink
-            if in_target_sink && trimmed.starts_with("Description:") {
-                return Some(trimmed.strip_prefix("Description:")?.trim().to_string());
-            }
-        }
+    /// Menu id for a stream entry, or None if the index doesn't fit the id space
+    fn stream_menu_id(stream_index: u32, entry: i32) -> Option<i32> {
+        i32::try_from(stream_index)
+            .ok()?
+            .checked_mul(4)?
+            .checked_add(STREAM_MENU_BASE)?
+            .checked_add(entry)
+    }
 
-        None
+    /// Split a stream menu id back into (stream index, entry)
+    fn parse_stream_menu_id(menu_item_id: i32) -> Option<(u32, i32)> {
+        let offset = menu_item_id - STREAM_MENU_BASE;
+        if offset < 0 {
+            return None;
+        }
+        Some(((offset / 4) as u32, offset % 4))
     }
 
-    /// Get current microphone (source) state using pactl
-    fn get_mic_state() -> Option<MicState> {
-        // Get default source name
-        let default_source = match Command::new("pactl")
-            .args(["get-default-source"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-default-source failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
-                }
-                output
-            }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
-            }
+    /// List the devices streams of the given kind can be routed to
+    async fn get_devices(client: &PulseClient, kind: StreamKind) -> anyhow::Result<Vec<DeviceEntry>> {
+        let server = client.server_info().await?;
+
+        let devices = match kind {
+            StreamKind::Playback => client
+                .sinks()
+                .await?
+                .into_iter()
+                .map(|sink| DeviceEntry {
+                    index: sink.index,
+                    is_default: server.default_sink_name.as_ref() == Some(&sink.name),
+                    description: sink
+                        .description
+                        .as_ref()
+                        .unwrap_or(&sink.name)
+                        .to_string_lossy()
+                        .into_owned(),
+                    name: sink.name,
+                })
+                .collect(),
+            StreamKind::Recording => client
+                .sources()
+                .await?
+                .into_iter()
+                // Monitor sources aren't microphones
+                .filter(|source| source.monitor_of_sink_index.is_none())
+                .map(|source| DeviceEntry {
+                    index: source.index,
+                    is_default: server.default_source_name.as_ref() == Some(&source.name),
+                    description: source
+                        .description
+                        .as_ref()
+                        .unwrap_or(&source.name)
+                        .to_string_lossy()
+                        .into_owned(),
+                    name: source.name,
+                })
+                .collect(),
         };
 
-        let source_name = String::from_utf8_lossy(&default_source.stdout)
-            .trim()
-            .to_string();
+        Ok(devices)
+    }
 
-        if source_name.is_empty() {
-            return None;
-        }
+    /// Menu id for a device entry, or None if the index doesn't fit the device range
+    fn device_menu_id(device_index: u32) -> Option<i32> {
+        let id = DEVICE_MENU_BASE.checked_add(i32::try_from(device_index).ok()?)?;
+        (id < MENU_SEPARATOR_ID).then_some(id)
+    }
 
-        // Skip monitor sources (they're not real microphones)
-        if source_name.contains(".monitor") {
-            tracing::debug!("Default source is a monitor, no microphone available");
-            return None;
+    /// Device index for a device menu id
+    fn parse_device_menu_id(menu_item_id: i32) -> Option<u32> {
+        if (DEVICE_MENU_BASE..MENU_SEPARATOR_ID).contains(&menu_item_id) {
+            Some((menu_item_id - DEVICE_MENU_BASE) as u32)
+        } else {
+            None
         }
+    }
 
-        // Get volume
-        let volume_output = match Command::new("pactl")
-            .args(["get-source-volume", "@DEFAULT_SOURCE@"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-source-volume failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
+    /// Radio items for choosing the default device
+    fn devices_menu(devices: &[DeviceEntry]) -> Vec<MenuItem> {
+        devices
+            .iter()
+            .filter_map(|device| {
+                Some(MenuItem {
+                    id: Self::device_menu_id(device.index)?,
+                    label: device.description.clone(),
+                    toggle_type: Some("radio".to_string()),
+                    toggle_state: if device.is_default { 1 } else { 0 },
+                    ..Default::default()
+                })
+            })
+            .collect()
+    }
+
+    /// Make a device the default and move existing streams over to it
+    async fn switch_device(
+        &self,
+        client: &PulseClient,
+        kind: StreamKind,
+        device_index: u32,
+    ) -> anyhow::Result<()> {
+        let devices = Self::get_devices(client, kind).await?;
+        let device = devices
+            .into_iter()
+            .find(|d| d.index == device_index)
+            .ok_or_else(|| anyhow::anyhow!("Device {} no longer exists", device_index))?;
+
+        match kind {
+            StreamKind::Playback => {
+                client.set_default_sink(device.name.clone()).await?;
+
+                for input in client.sink_inputs().await? {
+                    if input.sink_index == device.index {
+                        continue;
+                    }
+                    // Some streams refuse to move (e.g. DONT_MOVE); that's not fatal
+                    if let Err(e) = client.move_sink_input(input.index, device.index).await {
+                        tracing::debug!("Could not move playback stream {}: {}", input.index, e);
+                    }
                 }
-                output
             }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
+            StreamKind::Recording => {
+                client.set_default_source(device.name.clone()).await?;
+
+                // Leave streams that deliberately record a monitor source alone
+                let monitors: Vec<u32> = client
+                    .sources()
+                    .await?
+                    .into_iter()
+                    .filter(|source| source.monitor_of_sink_index.is_some())
+                    .map(|source| source.index)
+                    .collect();
+
+                for output in client.source_outputs().await? {
+                    if output.source_index == device.index || monitors.contains(&output.source_index) {
+                        continue;
+                    }
+                    if let Err(e) = client.move_source_output(output.index, device.index).await {
+                        tracing::debug!("Could not move recording stream {}: {}", output.index, e);
+                    }
+                }
             }
+        }
+
+        let direction = match kind {
+            StreamKind::Playback => "output",
+            StreamKind::Recording => "input",
         };
+        tracing::info!("Switched default {} to {}", direction, device.description);
+        Ok(())
+    }
 
-        let volume_str = String::from_utf8_lossy(&volume_output.stdout);
-        let volume_percent = Self::parse_volume(&volume_str).unwrap_or(0);
-
-        // Get mute status
-        let mute_output = match Command::new("pactl")
-            .args(["get-source-mute", "@DEFAULT_SOURCE@"])
-            .output()
-        {
-            Ok(output) => {
-                if !output.status.success() {
-                    tracing::debug!(
-                        "pactl get-source-mute failed: {}",
-                        String::from_utf8_lossy(&output.stderr)
-                    );
-                    return None;
-                }
-                output
-            }
-            Err(e) => {
-                tracing::debug!("Failed to execute pactl: {}", e);
-                return None;
-            }
+    /// Short status like "60%", "Muted" or "60%, paused"
+    fn stream_status(stream: &StreamState) -> String {
+        let mut status = if stream.muted {
+            "Muted".to_string()
+        } else {
+            format!("{}%", stream.volume_percent)
         };
+        if stream.corked {
+            status.push_str(", paused");
+        }
+        status
+    }
 
-        let mute_str = String::from_utf8_lossy(&mute_output.stdout);
-        let muted = mute_str.contains("yes");
+    /// Mute toggle and volume entries for one stream
+    /// Entries of a stream's menu, or None if its index is out of the menu id range
+    fn stream_menu_entries(stream: &StreamState) -> Option<Vec<MenuItem>> {
+        Some(vec![
+            MenuItem {
+                id: Self::stream_menu_id(stream.index, STREAM_MENU_MUTE)?,
+                label: "Mute".to_string(),
+                toggle_type: Some("checkmark".to_string()),
+                toggle_state: if stream.muted { 1 } else { 0 },
+                ..Default::default()
+            },
+            MenuItem {
+                id: Self::stream_menu_id(stream.index, STREAM_MENU_VOLUME_UP)?,
+                label: "Volume Up".to_string(),
+                enabled: stream.volume_writable,
+                ..Default::default()
+            },
+            MenuItem {
+                id: Self::stream_menu_id(stream.index, STREAM_MENU_VOLUME_DOWN)?,
+                label: "Volume Down".to_string(),
+                enabled: stream.volume_writable,
+                ..Default::default()
+            },
+        ])
+    }
 
-        // Get source description for a nicer name
-        let source_description = Self::get_source_description(&source_name)
-            .unwrap_or_else(|| source_name.clone());
+    /// One submenu per application stream
+    fn streams_menu(streams: &[StreamState], kind: StreamKind) -> Vec<MenuItem> {
+        if streams.is_empty() {
+            let label = match kind {
+                StreamKind::Playback => "No applications playing audio",
+                StreamKind::Recording => "No applications recording audio",
+            };
+            return vec![MenuItem {
+                id: MENU_NO_STREAMS_ID,
+                label: label.to_string(),
+                enabled: false,
+                ..Default::default()
+            }];
+        }
 
-        Some(MicState {
-            volume_percent,
-            muted,
-            source_name: source_description,
-        })
+        // Streams whose index doesn't fit a menu id are left out
+        streams
+            .iter()
+            .filter_map(|stream| {
+                Some(MenuItem {
+                    id: Self::stream_menu_id(stream.index, STREAM_MENU_SUBMENU)?,
+                    label: format!("{} ({})", stream.app_name, Self::stream_status(stream)),
+                    children: Self::stream_menu_entries(stream)?,
+                    ..Default::default()
+                })
+            })
+            .collect()
     }
 
-    /// Get source description from pactl list-sources
-    fn get_source_description(source_name: &str) -> Option<String> {
-        let output = Command::new("pactl")
-            .args(["list", "sources"])
-            .output()
-            .ok()?;
+    /// Item for an application stream; `max_volume` is the highest volume
+    /// streams of its kind can be raised to
+    fn create_stream_item(stream: &StreamState, max_volume: u32) -> ModuleItem {
+        let (icon_name, direction) = match stream.kind {
+            StreamKind::Playback => (Self::get_icon_name(stream.volume_percent, stream.muted), "Playing"),
+            StreamKind::Recording => (Self::get_mic_icon_name(stream.volume_percent, stream.muted), "Recording"),
+        };
 
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        let mut in_target_source = false;
+        let mut tooltip = format!("{}: {}", stream.app_name, Self::stream_status(stream));
+        if let Some(ref media_name) = stream.media_name {
+            tooltip.push_str(&format!("\n{}: {}", direction, media_name));
+        }
 
-        for line in output_str.lines() {
-            let trimmed = line.trim();
+        let mut actions = vec![
+            ItemAction::default_action("toggle_mute", if stream.muted { "Unmute" } else { "Mute" }),
+        ];
+        if stream.volume_writable {
+            actions.extend(ItemAction::adjust_actions("volume", "Volume"));
+        }
+        actions.push(ItemAction::new("context_menu", "Stream Options"));
 
-            // Check if this is our source
-            if trimmed.starts_with("Name:") {
-                let name = trimmed.strip_prefix("Name:")?.trim();
-                in_target_source = name == source_name;
-            }
+        ModuleItem {
+            id: format!("{}{}", stream.kind.item_prefix(), stream.index),
+            module: "pipewire".to_string(),
+            label: format!("{} {}", stream.app_name, Self::stream_status(stream)),
+            icon_name: Some(icon_name.to_string()),
+            icon_pixmap: None,
+            icon_width: 0,
+            icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
+            tooltip: Some(tooltip),
+            value: Some(
+                ItemValue::percentage(stream.volume_percent as f64)
+                    .with_range(0.0, max_volume.max(100) as f64),
+            ),
+            actions,
+        }
+    }
 
-            // Get description if we're in the right source
-            if in_target_source && trimmed.starts_with("Description:") {
-                return Some(trimmed.strip_prefix("Description:")?.trim().to_string());
-            }
+    /// Volume change in percent for an adjustment, never going past `max`
+    fn volume_delta(adjustment: Adjustment, step: u32, max: u32) -> i32 {
+        match adjustment {
+            Adjustment::Steps(steps) => steps * step as i32,
+            Adjustment::Maximum => max as i32,
+            // Large enough to bring every channel down to zero
+            Adjustment::Minimum => -i32::MAX,
+        }
+    }
+
+    /// Toggle mute or adjust the volume of a single application stream
+    async fn stream_action(
+        &self,
+        client: &PulseClient,
+        kind: StreamKind,
+        index: u32,
+        action: &str,
+    ) -> anyhow::Result<()> {
+        let streams = Self::get_streams(client, kind).await?;
+        let stream = streams
+            .into_iter()
+            .find(|s| s.index == index)
+            .ok_or_else(|| anyhow::anyhow!("Stream {} no longer exists", index))?;
+
+        let config = self.config.read().await.clone();
+        let (step, max) = match kind {
+            StreamKind::Playback => (config.scroll_step, config.max_volume),
+            StreamKind::Recording => (config.mic_scroll_step, config.mic_max_volume),
+        };
+
+        if action == "toggle_mute" {
+            return match kind {
+                StreamKind::Playback => client.set_sink_input_mute(index, !stream.muted).await,
+                StreamKind::Recording => client.set_source_output_mute(index, !stream.muted).await,
+            };
         }
+        let adjustment = Adjustment::parse(action, "volume")
+            .ok_or_else(|| anyhow::anyhow!("Unknown stream action: {}", action))?;
+        let delta = Self::volume_delta(adjustment, step, max);
+        if delta > 0 && stream.volume_percent >= max {
+            return Ok(());
+        }
+        let volume = pulse::adjust_volume(&stream.volume, delta, max);
 
-        None
+        anyhow::ensure!(stream.volume_writable, "Volume of {} cannot be changed", stream.app_name);
+        match kind {
+            StreamKind::Playback => client.set_sink_input_volume(index, volume).await,
+            StreamKind::Recording => client.set_source_output_volume(index, volume).await,
+        }
     }
 
     async fn create_module_item(&self, state: &AudioState) -> ModuleItem {
@@ -392,12 +599,21 @@ impl PipewireModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
-            actions: vec![
-                ItemAction::default_action("toggle_mute", if state.muted { "Unmute" } else { "Mute" }),
-                ItemAction::new("volume_up", "Volume Up"),
-                ItemAction::new("volume_down", "Volume Down"),
-            ],
+            value: Some(
+                ItemValue::percentage(state.volume_percent as f64)
+                    .with_range(0.0, config.max_volume.max(100) as f64),
+            ),
+            actions: [
+                vec![ItemAction::default_action("toggle_mute", if state.muted { "Unmute" } else { "Mute" })],
+                ItemAction::adjust_actions("volume", "Volume"),
+                vec![ItemAction::new("context_menu", "Output Devices and Applications")],
+            ]
+            .concat(),
         }
     }
 
@@ -430,12 +646,103 @@ impl PipewireModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
-            actions: vec![
-                ItemAction::default_action("mic_toggle_mute", if state.muted { "Unmute Microphone" } else { "Mute Microphone" }),
-                ItemAction::new("mic_volume_up", "Microphone Volume Up"),
-                ItemAction::new("mic_volume_down", "Microphone Volume Down"),
-            ],
+            value: Some(
+                ItemValue::percentage(state.volume_percent as f64)
+                    .with_range(0.0, config.mic_max_volume.max(100) as f64),
+            ),
+            actions: [
+                vec![ItemAction::default_action("mic_toggle_mute", if state.muted { "Unmute Microphone" } else { "Mute Microphone" })],
+                ItemAction::adjust_actions("mic_volume", "Microphone Volume"),
+                vec![ItemAction::new("context_menu", "Input Devices and Applications")],
+            ]
+            .concat(),
+        }
+    }
+
+    /// Build items from the current server state
+    async fn build_items(&self, client: &PulseClient) -> anyhow::Result<Vec<ModuleItem>> {
+        let mut items = Vec::new();
+        let (show_microphone, show_streams, max_volume, mic_max_volume) = {
+            let config = self.config.read().await;
+            (
+                config.show_microphone,
+                config.show_streams,
+                config.max_volume,
+                config.mic_max_volume,
+            )
+        };
+
+        if let Some(state) = Self::get_audio_state(client).await? {
+            items.push(self.create_module_item(&state).await);
+        }
+
+        if show_streams {
+            let streams = Self::get_streams(client, StreamKind::Playback).await?;
+            items.extend(streams.iter().map(|stream| Self::create_stream_item(stream, max_volume)));
+        }
+
+        if show_microphone {
+            if let Some(state) = Self::get_mic_state(client).await? {
+                items.push(self.create_mic_module_item(&state).await);
+            }
+        }
+
+        if show_streams {
+            let streams = Self::get_streams(client, StreamKind::Recording).await?;
+            items.extend(
+                streams
+                    .iter()
+                    .map(|stream| Self::create_stream_item(stream, mic_max_volume)),
+            );
+        }
+
+        Ok(items)
+    }
+
+    /// Refresh items on server events until the connection drops or the module stops
+    async fn run_session(
+        &self,
+        ctx: &ModuleContext,
+        client: &PulseClient,
+        events: &mut PulseSubscription,
+    ) {
+        let mut last_items: Option<Vec<ModuleItem>> = None;
+
+        loop {
+            match self.build_items(client).await {
+                Ok(items) => {
+                    if last_items.as_ref() != Some(&items) {
+                        ctx.send_items("pipewire", items.clone());
+                        last_items = Some(items);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to query audio server: {}", e);
+                    return;
+                }
+            }
+
+            tokio::select! {
+                _ = ctx.cancelled() => return,
+                _ = client.broken() => {
+                    tracing::warn!("Audio server connection out of sync, reconnecting");
+                    return;
+                }
+                _ = self.refresh.notified() => {}
+                event = events.next() => {
+                    if event.is_none() {
+                        tracing::info!("Audio server connection closed");
+                        return;
+                    }
+                    tokio::time::sleep(EVENT_DEBOUNCE).await;
+                    events.drain();
+                }
+            }
         }
     }
 }
@@ -455,70 +762,37 @@ impl Module for PipewireModule {
             return;
         }
 
-        // Check if pactl is available
-        if Command::new("pactl").arg("--version").output().is_err() {
-            tracing::error!("pactl not found. Install pulseaudio-utils or pipewire-pulse.");
-            return;
-        }
-
-        // Store context for use in invoke_action
-        *self.ctx.write().await = Some(ctx.clone());
-
-        // Get initial states
-        let mut last_audio_state = Self::get_audio_state().unwrap_or_default();
-        let mut last_mic_state: Option<MicState> = None;
-
-        // Send initial state
-        let mut items = vec![self.create_module_item(&last_audio_state).await];
+        let mask = SubscriptionMask::SINK
+            | SubscriptionMask::SOURCE
+            | SubscriptionMask::SINK_INPUT
+            | SubscriptionMask::SOURCE_OUTPUT
+            | SubscriptionMask::SERVER;
 
-        let config = self.config.read().await;
-        if config.show_microphone {
-            if let Some(mic_state) = Self::get_mic_state() {
-                last_mic_state = Some(mic_state.clone());
-                drop(config);
-                items.push(self.create_mic_module_item(&mic_state).await);
+        loop {
+            let connection = async {
+                let events = PulseSubscription::subscribe(CLIENT_NAME, mask).await?;
+                let client = PulseClient::connect(CLIENT_NAME).await?;
+                anyhow::Ok((client, events))
+            };
+
+            match connection.await {
+                Ok((client, mut events)) => {
+                    tracing::info!("Connected to audio server");
+                    *self.client.write().await = Some(client.clone());
+                    self.run_session(&ctx, &client, &mut events).await;
+                    *self.client.write().await = None;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to connect to audio server: {}. Is PulseAudio or pipewire-pulse running?",
+                        e
+                    );
+                }
             }
-        }
-        ctx.send_items("pipewire", items);
 
-        // Poll for changes
-        let poll_interval = Duration::from_millis(500);
-
-        loop {
             tokio::select! {
                 _ = ctx.cancelled() => break,
-                _ = tokio::time::sleep(poll_interval) => {
-                    let current_audio_state = Self::get_audio_state();
-                    let config = self.config.read().await;
-                    let current_mic_state = if config.show_microphone {
-                        Self::get_mic_state()
-                    } else {
-                        None
-                    };
-                    drop(config);
-
-                    // Check if either state changed
-                    let audio_changed = current_audio_state.as_ref() != Some(&last_audio_state);
-                    let mic_changed = current_mic_state != last_mic_state;
-
-                    if audio_changed || mic_changed {
-                        let mut items = Vec::new();
-
-                        if let Some(ref state) = current_audio_state {
-                            items.push(self.create_module_item(state).await);
-                            last_audio_state = state.clone();
-                        }
-
-                        if let Some(ref state) = current_mic_state {
-                            items.push(self.create_mic_module_item(state).await);
-                        }
-                        last_mic_state = current_mic_state;
-
-                        if !items.is_empty() {
-                            ctx.send_items("pipewire", items);
-                        }
-                    }
-                }
+                _ = tokio::time::sleep(RECONNECT_DELAY) => {}
             }
         }
     }
@@ -527,95 +801,237 @@ impl Module for PipewireModule {
         tracing::info!("PipeWire module stopped");
     }
 
-    async fn invoke_action(&self, _item_id: &str, action_id: &str, _x: i32, _y: i32) {
-        let config = self.config.read().await;
+    async fn invoke_action(&self, item_id: &str, action_id: &str, _x: i32, _y: i32) {
+        // Context menu is handled via get_menu_items/activate_menu_item
+        if action_id == "context_menu" {
+            return;
+        }
 
-        let action_performed = match action_id {
-            // Output (sink) actions
-            "toggle_mute" => {
-                let _ = Command::new("pactl")
-                    .args(["set-sink-mute", "@DEFAULT_SINK@", "toggle"])
-                    .output();
-                true
+        let Some(client) = self.client.read().await.clone() else {
+            tracing::warn!("Audio server not connected, ignoring action: {}", action_id);
+            return;
+        };
+
+        if let Some((kind, index)) = Self::parse_stream_item_id(item_id) {
+            if let Err(e) = self.stream_action(&client, kind, index, action_id).await {
+                tracing::warn!("Stream action {} on {} failed: {}", action_id, item_id, e);
             }
-            "volume_up" => {
-                let step = config.scroll_step;
-                let max = config.max_volume;
-
-                // Check if we're already at max before increasing
-                // Note: There's a small race window here, but using absolute volume would
-                // reset channel balance settings, which is worse than occasional overshoot
-                if let Some(state) = Self::get_audio_state() {
-                    if state.volume_percent >= max {
+            return;
+        }
+
+        let config = self.config.read().await.clone();
+
+        // No explicit refresh needed: the subscription reports the resulting change
+        let result = match action_id {
+            // Output (sink) actions
+            "toggle_mute" | "volume_up" | "volume_down" | "volume_page_up" | "volume_page_down"
+            | "volume_min" | "volume_max" => {
+                let state = match Self::get_audio_state(&client).await {
+                    Ok(Some(state)) => state,
+                    Ok(None) => return,
+                    Err(e) => {
+                        tracing::warn!("Failed to get audio state: {}", e);
                         return;
                     }
+                };
+
+                match Adjustment::parse(action_id, "volume") {
+                    None => client.set_sink_mute(state.index, !state.muted).await,
+                    Some(adjustment) => {
+                        let delta = Self::volume_delta(adjustment, config.scroll_step, config.max_volume);
+                        // Check if we're already at max before increasing
+                        if delta > 0 && state.volume_percent >= config.max_volume {
+                            return;
+                        }
+                        let volume = pulse::adjust_volume(&state.volume, delta, config.max_volume);
+                        client.set_sink_volume(state.index, volume).await
+                    }
                 }
-
-                let _ = Command::new("pactl")
-                    .args(["set-sink-volume", "@DEFAULT_SINK@", &format!("+{}%", step)])
-                    .output();
-                true
-            }
-            "volume_down" => {
-                let step = config.scroll_step;
-                let _ = Command::new("pactl")
-                    .args(["set-sink-volume", "@DEFAULT_SINK@", &format!("-{}%", step)])
-                    .output();
-                true
             }
             // Microphone (source) actions
-            "mic_toggle_mute" => {
-                let _ = Command::new("pactl")
-                    .args(["set-source-mute", "@DEFAULT_SOURCE@", "toggle"])
-                    .output();
-                true
-            }
-            "mic_volume_up" => {
-                let step = config.mic_scroll_step;
-                let max = config.mic_max_volume;
-
-                // Check if we're already at max before increasing
-                // Note: There's a small race window here, but using absolute volume would
-                // reset channel balance settings, which is worse than occasional overshoot
-                if let Some(state) = Self::get_mic_state() {
-                    if state.volume_percent >= max {
+            "mic_toggle_mute" | "mic_volume_up" | "mic_volume_down" | "mic_volume_page_up"
+            | "mic_volume_page_down" | "mic_volume_min" | "mic_volume_max" => {
+                let state = match Self::get_mic_state(&client).await {
+                    Ok(Some(state)) => state,
+                    Ok(None) => return,
+                    Err(e) => {
+                        tracing::warn!("Failed to get microphone state: {}", e);
                         return;
                     }
+                };
+
+                match Adjustment::parse(action_id, "mic_volume") {
+                    None => client.set_source_mute(state.index, !state.muted).await,
+                    Some(adjustment) => {
+                        let delta =
+                            Self::volume_delta(adjustment, config.mic_scroll_step, config.mic_max_volume);
+                        // Check if we're already at max before increasing
+                        if delta > 0 && state.volume_percent >= config.mic_max_volume {
+                            return;
+                        }
+                        let volume = pulse::adjust_volume(&state.volume, delta, config.mic_max_volume);
+                        client.set_source_volume(state.index, volume).await
+                    }
                 }
-
-                let _ = Command::new("pactl")
-                    .args(["set-source-volume", "@DEFAULT_SOURCE@", &format!("+{}%", step)])
-                    .output();
-                true
-            }
-            "mic_volume_down" => {
-                let step = config.mic_scroll_step;
-                let _ = Command::new("pactl")
-                    .args(["set-source-volume", "@DEFAULT_SOURCE@", &format!("-{}%", step)])
-                    .output();
-                true
             }
             _ => {
                 tracing::warn!("Unknown action: {}", action_id);
-                false
+                return;
             }
         };
 
-        // Immediately refresh the display after an action
-        if action_performed {
-            drop(config); // Release the lock before send_update
-            self.send_update().await;
+        if let Err(e) = result {
+            tracing::warn!("Audio action {} failed: {}", action_id, e);
         }
     }
 
+    async fn get_menu_items(&self, item_id: &str) -> anyhow::Result<Vec<MenuItem>> {
+        let client = self
+            .client
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Audio server not connected"))?;
+
+        if let Some((kind, index)) = Self::parse_stream_item_id(item_id) {
+            let streams = Self::get_streams(&client, kind).await?;
+            let stream = streams
+                .iter()
+                .find(|s| s.index == index)
+                .ok_or_else(|| anyhow::anyhow!("Stream {} no longer exists", index))?;
+            return Self::stream_menu_entries(stream)
+                .ok_or_else(|| anyhow::anyhow!("Stream {} has no menu ids", index));
+        }
+
+        let kind = match item_id {
+            "pipewire:volume" => StreamKind::Playback,
+            "pipewire:microphone" => StreamKind::Recording,
+            _ => anyhow::bail!("No menu for item: {}", item_id),
+        };
+        let devices = Self::get_devices(&client, kind).await?;
+        let streams = Self::get_streams(&client, kind).await?;
+
+        let mut items = Self::devices_menu(&devices);
+        if !items.is_empty() {
+            items.push(MenuItem {
+                id: MENU_SEPARATOR_ID,
+                item_type: "separator".to_string(),
+                ..Default::default()
+            });
+        }
+        items.extend(Self::streams_menu(&streams, kind));
+        Ok(items)
+    }
+
+    async fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> anyhow::Result<()> {
+        let client = self
+            .client
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Audio server not connected"))?;
+
+        let kind = match Self::parse_stream_item_id(item_id) {
+            Some((kind, _)) => kind,
+            None if item_id == "pipewire:volume" => StreamKind::Playback,
+            None if item_id == "pipewire:microphone" => StreamKind::Recording,
+            None => anyhow::bail!("No menu for item: {}", item_id),
+        };
+
+        // Device entries only appear in the output/microphone menus
+        if let Some(device_index) = Self::parse_device_menu_id(menu_item_id) {
+            anyhow::ensure!(
+                Self::parse_stream_item_id(item_id).is_none(),
+                "Unknown menu item id: {}",
+                menu_item_id
+            );
+            return self.switch_device(&client, kind, device_index).await;
+        }
+
+        let (index, entry) = Self::parse_stream_menu_id(menu_item_id)
+            .ok_or_else(|| anyhow::anyhow!("Unknown menu item id: {}", menu_item_id))?;
+        let action = match entry {
+            STREAM_MENU_MUTE => "toggle_mute",
+            STREAM_MENU_VOLUME_UP => "volume_up",
+            STREAM_MENU_VOLUME_DOWN => "volume_down",
+            _ => anyhow::bail!("Unknown menu item id: {}", menu_item_id),
+        };
+
+        self.stream_action(&client, kind, index, action).await
+    }
+
     async fn reload_config(&self, config: &crate::config::Config) -> bool {
         if let Some(ref pipewire_config) = config.modules.pipewire {
             let mut current = self.config.write().await;
             *current = pipewire_config.clone();
             tracing::debug!("PipeWire module config reloaded");
+            // Labels and the microphone item depend on config, so rebuild them
+            self.refresh.notify_one();
             true
         } else {
             false
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stream_item_id() {
+        assert_eq!(
+            PipewireModule::parse_stream_item_id("pipewire:playback-42"),
+            Some((StreamKind::Playback, 42))
+        );
+        assert_eq!(
+            PipewireModule::parse_stream_item_id("pipewire:recording-7"),
+            Some((StreamKind::Recording, 7))
+        );
+        assert_eq!(PipewireModule::parse_stream_item_id("pipewire:volume"), None);
+        assert_eq!(PipewireModule::parse_stream_item_id("pipewire:playback-x"), None);
+    }
+
+    #[test]
+    fn test_volume_delta() {
+        let delta = |action: &str| {
+            let adjustment = Adjustment::parse(action, "mic_volume").unwrap();
+            PipewireModule::volume_delta(adjustment, 5, 150)
+        };
+        assert_eq!(delta("mic_volume_up"), 5);
+        assert_eq!(delta("mic_volume_page_down"), -20);
+        assert_eq!(delta("mic_volume_max"), 150);
+
+        // Minimum silences every channel, even ones above the maximum
+        let mut volume = pulse::ChannelVolume::empty();
+        volume.push(pulse::Volume::from_u32_clamped(pulse::Volume::NORM.as_u32() * 2));
+        volume.push(pulse::Volume::from_u32_clamped(pulse::Volume::NORM.as_u32() / 2));
+        let volume = pulse::adjust_volume(&volume, delta("mic_volume_min"), 150);
+        assert_eq!(pulse::volume_percent(&volume), 0);
+    }
+
+    #[test]
+    fn test_stream_menu_id_roundtrip() {
+        for index in [0, 1, 57, 4096] {
+            for entry in [STREAM_MENU_SUBMENU, STREAM_MENU_MUTE, STREAM_MENU_VOLUME_UP, STREAM_MENU_VOLUME_DOWN] {
+                let id = PipewireModule::stream_menu_id(index, entry).unwrap();
+                assert_eq!(PipewireModule::parse_stream_menu_id(id), Some((index, entry)));
+            }
+        }
+        assert_eq!(PipewireModule::parse_stream_menu_id(MENU_NO_STREAMS_ID), None);
+        assert_eq!(PipewireModule::stream_menu_id(u32::MAX, STREAM_MENU_SUBMENU), None);
+        assert_eq!(PipewireModule::stream_menu_id(600_000_000, STREAM_MENU_MUTE), None);
+    }
+
+    #[test]
+    fn test_device_menu_ids_do_not_overlap_streams() {
+        let id = PipewireModule::device_menu_id(57).unwrap();
+        assert_eq!(PipewireModule::parse_device_menu_id(id), Some(57));
+        assert_eq!(PipewireModule::parse_stream_menu_id(id), None);
+
+        let stream_id = PipewireModule::stream_menu_id(0, STREAM_MENU_SUBMENU).unwrap();
+        assert_eq!(PipewireModule::parse_device_menu_id(stream_id), None);
+        assert_eq!(PipewireModule::parse_device_menu_id(MENU_SEPARATOR_ID), None);
+        assert_eq!(PipewireModule::device_menu_id(u32::MAX), None);
+    }
+}
@@ -11,6 +11,7 @@ use zbus::Connection;
 use zbus::proxy;
 
 use crate::config::PowerProfilesModuleConfig;
+use crate::ItemStatus;
 use super::{Module, ModuleContext, ModuleItem, ItemAction};
 
 /// Power profile types
@@ -198,7 +199,12 @@ impl PowerProfilesModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
+            value: None,
             actions: vec![
                 ItemAction::default_action("cycle", "Cycle Profile"),
                 ItemAction::new("context_menu", "Select Profile"),
@@ -1,119 +1,103 @@
 //! Privacy module - shows which apps are using the microphone
 
 use std::collections::HashSet;
-use std::process::Command;
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 
 use crate::config::PrivacyModuleConfig;
+use crate::ItemStatus;
+use crate::pulse::{self, Prop, PulseClient, PulseSubscription, SubscriptionMask};
 use super::{Module, ModuleContext, ModuleItem};
 
-#[derive(Default)]
-struct SourceOutputInfo {
-    app_name: Option<String>,
-    process_binary: Option<String>,
-    corked: Option<bool>,
-}
+/// Client name announced to the audio server
+const CLIENT_NAME: &str = "waytray-privacy";
+
+/// Events arrive in bursts when a stream starts; wait before refreshing
+const EVENT_DEBOUNCE: Duration = Duration::from_millis(30);
 
 /// Privacy module that surfaces active microphone usage
 pub struct PrivacyModule {
     config: RwLock<PrivacyModuleConfig>,
+    /// Wakes the event loop when the items must be rebuilt without a server event
+    refresh: Notify,
 }
 
 impl PrivacyModule {
     pub fn new(config: PrivacyModuleConfig) -> Self {
         Self {
             config: RwLock::new(config),
+            refresh: Notify::new(),
         }
     }
 
-    fn parse_property_value(line: &str, key: &str) -> Option<String> {
-        let prefix = format!("{key} =");
-        let value = line.strip_prefix(&prefix)?.trim();
-        let value = value.trim_matches('"').trim();
-        if value.is_empty() {
-            None
-        } else {
-            Some(value.to_string())
-        }
-    }
-
-    fn push_app(
-        info: &SourceOutputInfo,
-        apps: &mut Vec<String>,
-        seen: &mut HashSet<String>,
-    ) {
-        if info.corked == Some(true) {
-            return;
-        }
+    /// List the apps with an active (uncorked) recording stream
+    async fn get_active_apps(client: &PulseClient) -> anyhow::Result<Vec<String>> {
+        let outputs = client.source_outputs().await?;
 
-        let name = info
-            .app_name
-            .as_ref()
-            .or(info.process_binary.as_ref());
-
-        if let Some(name) = name {
-            if seen.insert(name.clone()) {
-                apps.push(name.clone());
-            }
-        }
-    }
-
-    fn parse_source_outputs(output: &str) -> Vec<String> {
         let mut apps = Vec::new();
         let mut seen = HashSet::new();
-        let mut current = SourceOutputInfo::default();
 
-        for line in output.lines() {
-            let trimmed = line.trim();
+        for output in outputs.iter().filter(|o| !o.corked) {
+            let name = pulse::prop_string(&output.props, Prop::ApplicationName)
+                .or_else(|| pulse::prop_string(&output.props, Prop::ApplicationProcessBinary));
 
-            if trimmed.starts_with("Source Output #") {
-                Self::push_app(&current, &mut apps, &mut seen);
-                current = SourceOutputInfo::default();
-                continue;
+            if let Some(name) = name {
+                if seen.insert(name.clone()) {
+                    apps.push(name);
+                }
             }
+        }
 
-            if let Some(value) = Self::parse_property_value(trimmed, "application.name") {
-                current.app_name = Some(value);
-                continue;
-            }
+        Ok(apps)
+    }
 
-            if let Some(value) =
-                Self::parse_property_value(trimmed, "application.process.binary")
-            {
-                current.process_binary = Some(value);
-                continue;
-            }
+    /// Push updates on source-output events until the connection drops or the module stops
+    async fn run_session(
+        &self,
+        ctx: &ModuleContext,
+        client: &PulseClient,
+        events: &mut PulseSubscription,
+    ) {
+        let mut last_apps: Option<Vec<String>> = None;
 
-            if let Some(value) = trimmed.strip_prefix("Corked:") {
-                let corked = value.trim().eq_ignore_ascii_case("yes");
-                current.corked = Some(corked);
+        loop {
+            match Self::get_active_apps(client).await {
+                Ok(apps) => {
+                    if last_apps.as_ref() != Some(&apps) {
+                        let items = self.build_items(apps.clone()).await;
+                        ctx.send_items("privacy", items);
+                        last_apps = Some(apps);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to list recording streams: {}", e);
+                    return;
+                }
             }
-        }
-
-        Self::push_app(&current, &mut apps, &mut seen);
-        apps
-    }
 
-    fn get_active_apps() -> Option<Vec<String>> {
-        let output = Command::new("pactl")
-            .args(["list", "source-outputs"])
-            .output()
-            .ok()?;
-
-        if !output.status.success() {
-            tracing::debug!(
-                "pactl list source-outputs failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            );
-            return None;
+            tokio::select! {
+                _ = ctx.cancelled() => return,
+                _ = self.refresh.notified() => {
+                    // Config changed: rebuild the items even if no app did
+                    last_apps = None;
+                }
+                _ = client.broken() => {
+                    tracing::warn!("Audio server connection out of sync, reconnecting");
+                    return;
+                }
+                event = events.next() => {
+                    if event.is_none() {
+                        tracing::info!("Audio server connection closed");
+                        return;
+                    }
+                    tokio::time::sleep(EVENT_DEBOUNCE).await;
+                    events.drain();
+                }
+            }
         }
-
-        let output_str = String::from_utf8_lossy(&output.stdout);
-        Some(Self::parse_source_outputs(&output_str))
     }
 
     fn format_label(apps: &[String]) -> String {
@@ -160,7 +144,12 @@ impl PrivacyModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(Self::format_tooltip(&apps)),
+            value: None,
             actions: Vec::new(),
         }]
     }
@@ -181,36 +170,35 @@ impl Module for PrivacyModule {
             return;
         }
 
-        if Command::new("pactl").arg("--version").output().is_err() {
-            tracing::error!("pactl not found. Install pulseaudio-utils or pipewire-pulse.");
-            return;
-        }
-
-        let mut last_apps: Vec<String> = Vec::new();
+        loop {
+            let connection = async {
+                let events =
+                    PulseSubscription::subscribe(CLIENT_NAME, SubscriptionMask::SOURCE_OUTPUT)
+                        .await?;
+                let client = PulseClient::connect(CLIENT_NAME).await?;
+                anyhow::Ok((client, events))
+            };
 
-        if let Some(apps) = Self::get_active_apps() {
-            let items = self.build_items(apps.clone()).await;
-            ctx.send_items("privacy", items);
-            last_apps = apps;
-        }
+            match connection.await {
+                Ok((client, mut events)) => {
+                    self.run_session(&ctx, &client, &mut events).await;
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to connect to audio server: {}. Is PulseAudio or pipewire-pulse running?",
+                        e
+                    );
+                }
+            }
 
-        loop {
-            let interval = {
+            let retry_interval = {
                 let config = self.config.read().await;
                 Duration::from_secs(config.interval_seconds)
             };
 
             tokio::select! {
                 _ = ctx.cancelled() => break,
-                _ = tokio::time::sleep(interval) => {
-                    if let Some(apps) = Self::get_active_apps() {
-                        if apps != last_apps {
-                            let items = self.build_items(apps.clone()).await;
-                            ctx.send_items("privacy", items);
-                            last_apps = apps;
-                        }
-                    }
-                }
+                _ = tokio::time::sleep(retry_interval) => {}
             }
         }
     }
@@ -228,6 +216,8 @@ impl Module for PrivacyModule {
             let mut current = self.config.write().await;
             *current = privacy_config.clone();
             tracing::debug!("Privacy module config reloaded");
+            // show_when_idle decides whether the idle item exists, so rebuild
+            self.refresh.notify_one();
             true
         } else {
             false
@@ -7,7 +7,8 @@ use async_trait::async_trait;
 use tokio::sync::RwLock;
 
 use crate::config::SystemModuleConfig;
-use super::{Module, ModuleContext, ModuleItem};
+use crate::ItemStatus;
+use super::{ItemValue, Metric, Module, ModuleContext, ModuleItem};
 
 /// CPU usage tracking state
 struct CpuState {
@@ -328,7 +329,12 @@ impl SystemModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
+            value: Some(ItemValue::percentage(usage as f64)),
             actions: Vec::new(),
         }
     }
@@ -353,7 +359,12 @@ impl SystemModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
+            value: Some(ItemValue::percentage(percent as f64)),
             actions: Vec::new(),
         }
     }
@@ -374,18 +385,29 @@ impl SystemModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(format!("CPU Temperature: {:.1}°C", temp)),
+            value: Some(ItemValue::temperature(temp as f64)),
             actions: Vec::new(),
         }
     }
 
-    async fn create_items(&self) -> Vec<ModuleItem> {
+    /// Create the items for the enabled readings, and metrics for all of them
+    ///
+    /// Every reading is taken even when its item is hidden, so alert rules
+    /// can use it.
+    async fn create_items(&self) -> (Vec<ModuleItem>, Vec<Metric>) {
         let config = self.config.read().await;
         let mut items = Vec::new();
+        let mut metrics = Vec::new();
 
         // CPU usage and temperature together
-        if config.show_cpu {
-            if let Some(usage) = self.get_cpu_usage().await {
+        if let Some(usage) = self.get_cpu_usage().await {
+            metrics.push(Metric::new("system.cpu", usage as f64));
+            if config.show_cpu {
                 let top_process = if config.show_top_cpu_process {
                     self.get_top_cpu_process().await
                 } else {
@@ -395,15 +417,17 @@ impl SystemModule {
             }
         }
 
-        if config.show_temperature {
-            if let Some(temp) = self.get_temperature().await {
+        if let Some(temp) = self.get_temperature().await {
+            metrics.push(Metric::new("system.temperature", temp as f64));
+            if config.show_temperature {
                 items.push(self.create_temperature_item(temp));
             }
         }
 
         // Memory last
-        if config.show_memory {
-            if let Some((percent, used_gb, total_gb)) = self.get_memory_usage().await {
+        if let Some((percent, used_gb, total_gb)) = self.get_memory_usage().await {
+            metrics.push(Metric::new("system.memory", percent as f64));
+            if config.show_memory {
                 let top_process = if config.show_top_memory_process {
                     self.get_top_memory_process().await
                 } else {
@@ -413,7 +437,7 @@ impl SystemModule {
             }
         }
 
-        items
+        (items, metrics)
     }
 }
 
@@ -442,8 +466,9 @@ impl Module for SystemModule {
         }
 
         // Send initial items
-        let items = self.create_items().await;
+        let (items, metrics) = self.create_items().await;
         ctx.send_items("system", items);
+        ctx.send_metrics("system", metrics);
 
         // Poll at configured interval (re-read each iteration for hot reload)
         loop {
@@ -455,8 +480,9 @@ impl Module for SystemModule {
             tokio::select! {
                 _ = ctx.cancelled() => break,
                 _ = tokio::time::sleep(interval) => {
-                    let items = self.create_items().await;
+                    let (items, metrics) = self.create_items().await;
                     ctx.send_items("system", items);
+                    ctx.send_metrics("system", metrics);
                 }
             }
         }
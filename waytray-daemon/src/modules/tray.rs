@@ -1,6 +1,7 @@
 //! Tray module - wraps the existing StatusNotifierItem functionality as a module
 
 use async_trait::async_trait;
+use regex::Regex;
 use std::collections::VecDeque;
 use std::sync::Arc;
 use std::time::Instant;
@@ -9,15 +10,158 @@ use tokio::time::{self, Duration, MissedTickBehavior};
 use zbus::Connection;
 
 use crate::cache::ItemCache;
-use crate::config::TrayModuleConfig;
+use crate::config::{TrayDefaultAction, TrayModuleConfig, TrayRuleConfig};
 use crate::host::Host;
-use crate::TrayItem;
+use crate::{CacheEvent, ItemStatus, TrayItem};
 
-use super::{ItemAction, Module, ModuleContext, ModuleItem};
+use super::{ItemAction, ItemIcon, Module, ModuleContext, ModuleItem};
+
+/// A compiled `[[modules.tray.rules]]` entry
+struct TrayRule {
+    id: Option<Regex>,
+    title: Option<Regex>,
+    bus_name: Option<Regex>,
+    config: TrayRuleConfig,
+}
+
+impl TrayRule {
+    /// Compile a rule's patterns. Returns None (after logging why) for rules
+    /// with invalid patterns or nothing to match on.
+    fn compile(config: &TrayRuleConfig) -> Option<Self> {
+        if config.id.is_none() && config.title.is_none() && config.bus_name.is_none() {
+            tracing::warn!("Ignoring tray rule without id, title or bus_name: {:?}", config);
+            return None;
+        }
+
+        let compile = |pattern: &Option<String>| -> Result<Option<Regex>, regex::Error> {
+            pattern
+                .as_deref()
+                .map(|p| {
+                    if config.regex {
+                        Regex::new(p)
+                    } else {
+                        Regex::new(&glob_to_regex(p))
+                    }
+                })
+                .transpose()
+        };
+
+        match (
+            compile(&config.id),
+            compile(&config.title),
+            compile(&config.bus_name),
+        ) {
+            (Ok(id), Ok(title), Ok(bus_name)) => Some(Self {
+                id,
+                title,
+                bus_name,
+                config: config.clone(),
+            }),
+            (Err(e), _, _) | (_, Err(e), _) | (_, _, Err(e)) => {
+                tracing::warn!("Ignoring tray rule with invalid pattern: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Check whether every pattern of the rule matches the item
+    fn matches(&self, item: &TrayItem) -> bool {
+        let matches = |pattern: &Option<Regex>, value: &str| {
+            pattern.as_ref().map(|re| re.is_match(value)).unwrap_or(true)
+        };
+        matches(&self.id, &item.sni_id)
+            && matches(&self.title, &item.title)
+            && matches(&self.bus_name, &item.bus_name)
+    }
+
+    /// Apply the rule's label, icon and default action overrides
+    fn apply(&self, module_item: &mut ModuleItem, has_menu: bool) {
+        if let Some(label) = &self.config.label {
+            module_item.label = label.clone();
+        }
+        if let Some(icon) = &self.config.icon {
+            module_item.icon_name = Some(icon.clone());
+            module_item.icon_pixmap = None;
+            module_item.icon_width = 0;
+            module_item.icon_height = 0;
+        }
+        if let Some(action) = self.config.default_action {
+            set_default_action(module_item, action, has_menu);
+        }
+    }
+}
+
+/// Convert a glob (`*` and `?` wildcards) into an anchored regex
+fn glob_to_regex(glob: &str) -> String {
+    let mut pattern = String::from("^");
+    for c in glob.chars() {
+        match c {
+            '*' => pattern.push_str(".*"),
+            '?' => pattern.push('.'),
+            _ => pattern.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    pattern.push('$');
+    pattern
+}
+
+/// Make `action` the default action of an item, adding it if missing
+fn set_default_action(item: &mut ModuleItem, action: TrayDefaultAction, has_menu: bool) {
+    let (action_id, label) = match action {
+        TrayDefaultAction::Activate => ("activate", "Activate"),
+        // Items without a menu have nothing to show
+        TrayDefaultAction::Menu if !has_menu => return,
+        TrayDefaultAction::Menu => ("context_menu", "Show Menu"),
+    };
+
+    if !item.actions.iter().any(|a| a.id == action_id) {
+        item.actions.push(ItemAction::new(action_id, label));
+    }
+    for a in &mut item.actions {
+        a.is_default = a.id == action_id;
+    }
+}
+
+/// Move items pinned by rules to their positions.
+/// Non-negative positions count from the start, negative ones from the end (-1 is last).
+fn apply_pinned_positions(entries: Vec<(ModuleItem, Option<i32>)>) -> Vec<ModuleItem> {
+    let mut items = Vec::with_capacity(entries.len());
+    let mut from_start = Vec::new();
+    let mut from_end = Vec::new();
+
+    for (item, position) in entries {
+        match position {
+            Some(p) if p >= 0 => from_start.push((p as usize, item)),
+            Some(p) => from_end.push((p.unsigned_abs() as usize, item)),
+            None => items.push(item),
+        }
+    }
+
+    // Stable sorts keep items pinned to the same position in their current order
+    from_start.sort_by_key(|(p, _)| *p);
+    let mut previous: Option<(usize, usize)> = None;
+    for (p, item) in from_start {
+        let index = match previous {
+            Some((prev_p, prev_index)) if prev_p == p => prev_index + 1,
+            _ => p.min(items.len()),
+        };
+        items.insert(index, item);
+        previous = Some((p, index));
+    }
+
+    from_end.sort_by_key(|(p, _)| *p);
+    for (p, item) in from_end {
+        let index = (items.len() + 1).saturating_sub(p);
+        items.insert(index, item);
+    }
+
+    items
+}
 
 /// The system tray module - wraps SNI protocol as a module
 pub struct TrayModule {
     config: RwLock<TrayModuleConfig>,
+    rules: RwLock<Vec<TrayRule>>,
     host: RwLock<Option<Arc<Host>>>,
     cache: Arc<ItemCache>,
     connection: Connection,
@@ -25,8 +169,10 @@ pub struct TrayModule {
 
 impl TrayModule {
     pub fn new(config: TrayModuleConfig, connection: Connection) -> Self {
+        let rules = config.rules.iter().filter_map(TrayRule::compile).collect();
         Self {
             config: RwLock::new(config),
+            rules: RwLock::new(rules),
             host: RwLock::new(None),
             cache: ItemCache::new(),
             connection,
@@ -43,7 +189,27 @@ impl TrayModule {
             icon_pixmap: item.icon_pixmap.clone(),
             icon_width: item.icon_width,
             icon_height: item.icon_height,
+            // Only expose the attention icon while the item actually needs attention
+            attention_icon: if item.status == ItemStatus::NeedsAttention {
+                ItemIcon::from_parts(
+                    item.attention_icon_name.clone(),
+                    item.attention_icon_pixmap.clone(),
+                    item.attention_icon_width,
+                    item.attention_icon_height,
+                )
+            } else {
+                None
+            },
+            overlay_icon: ItemIcon::from_parts(
+                item.overlay_icon_name.clone(),
+                item.overlay_icon_pixmap.clone(),
+                item.overlay_icon_width,
+                item.overlay_icon_height,
+            ),
+            status: item.status,
+            category: Some(item.category),
             tooltip: item.tooltip.clone(),
+            value: None,
             actions: Vec::new(),
         };
 
@@ -75,6 +241,42 @@ impl TrayModule {
         module_item
     }
 
+    /// Convert the cached tray items to module items, applying the
+    /// passive filter, category sorting and rules from the config
+    async fn build_items(&self) -> Vec<ModuleItem> {
+        let (hide_passive, sort_by_category) = {
+            let config = self.config.read().await;
+            (config.hide_passive, config.sort_by_category)
+        };
+
+        let mut tray_items = self.cache.get_all().await;
+        if hide_passive {
+            tray_items.retain(|item| item.status != ItemStatus::Passive);
+        }
+        if sort_by_category {
+            tray_items.sort_by(|a, b| a.category.cmp(&b.category).then_with(|| a.id.cmp(&b.id)));
+        }
+
+        let rules = self.rules.read().await;
+        let mut entries = Vec::with_capacity(tray_items.len());
+        for item in &tray_items {
+            let matching: Vec<&TrayRule> = rules.iter().filter(|r| r.matches(item)).collect();
+            if matching.iter().any(|r| r.config.hide) {
+                continue;
+            }
+
+            // Later rules override earlier ones
+            let mut module_item = TrayModule::tray_item_to_module_item(item);
+            let mut position = None;
+            for rule in matching {
+                rule.apply(&mut module_item, item.has_menu);
+                position = rule.config.position.or(position);
+            }
+            entries.push((module_item, position));
+        }
+
+        apply_pinned_positions(entries)
+    }
 
     fn module_items_equal_ignoring_tooltip(a: &[ModuleItem], b: &[ModuleItem]) -> bool {
         if a.len() != b.len() {
@@ -89,6 +291,10 @@ impl TrayModule {
                 && left.icon_pixmap == right.icon_pixmap
                 && left.icon_width == right.icon_width
                 && left.icon_height == right.icon_height
+                && left.attention_icon == right.attention_icon
+                && left.overlay_icon == right.overlay_icon
+                && left.status == right.status
+                && left.category == right.category
                 && left.actions == right.actions
         })
     }
@@ -102,6 +308,27 @@ impl TrayModule {
     pub fn get_cache(&self) -> Arc<ItemCache> {
         self.cache.clone()
     }
+
+    /// Get the menu of a tray item from the host
+    async fn host_menu_items(
+        &self,
+        item_id: &str,
+        about_to_show: bool,
+    ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
+        // Parse the item ID - format is "tray:{original_id}"
+        let original_id = item_id
+            .strip_prefix("tray:")
+            .ok_or_else(|| anyhow::anyhow!("Invalid tray item ID: {}", item_id))?;
+
+        let host = self
+            .host
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| anyhow::anyhow!("Tray host not available"))?;
+
+        host.get_menu_items(original_id, about_to_show).await
+    }
 }
 
 #[async_trait]
@@ -148,11 +375,7 @@ impl Module for TrayModule {
         }
 
         // Send initial items
-        let tray_items = self.cache.get_all().await;
-        let module_items: Vec<ModuleItem> = tray_items
-            .iter()
-            .map(TrayModule::tray_item_to_module_item)
-            .collect();
+        let module_items = self.build_items().await;
         ctx.send_items("tray", module_items.clone());
         let mut last_sent = Some(module_items);
 
@@ -186,6 +409,10 @@ impl Module for TrayModule {
                 }
                 result = receiver.recv() => {
                     match result {
+                        Ok(CacheEvent::MenuUpdated(id)) => {
+                            // Menu changes don't affect the item list
+                            ctx.send_menu_updated(&format!("tray:{}", id));
+                        }
                         Ok(_event) => {
                             refresh_pending = true;
                         }
@@ -201,11 +428,7 @@ impl Module for TrayModule {
                 }
                 _ = refresh_tick.tick() => {
                     if refresh_pending {
-                        let tray_items = self.cache.get_all().await;
-                        let module_items: Vec<ModuleItem> = tray_items
-                            .iter()
-                            .map(TrayModule::tray_item_to_module_item)
-                            .collect();
+                        let module_items = self.build_items().await;
 
                         let now = Instant::now();
                         let mut should_send = false;
@@ -327,6 +550,13 @@ impl Module for TrayModule {
     async fn reload_config(&self, config: &crate::config::Config) -> bool {
         let mut current = self.config.write().await;
         *current = config.modules.tray.clone();
+        drop(current);
+
+        let rules = config.modules.tray.rules.iter().filter_map(TrayRule::compile).collect();
+        *self.rules.write().await = rules;
+
+        // Rebuild the item list with the new filter/sort settings
+        self.cache.notify_changed();
         tracing::debug!("Tray module config reloaded");
         true
     }
@@ -335,19 +565,14 @@ impl Module for TrayModule {
         &self,
         item_id: &str,
     ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
-        // Parse the item ID - format is "tray:{original_id}"
-        let original_id = item_id
-            .strip_prefix("tray:")
-            .ok_or_else(|| anyhow::anyhow!("Invalid tray item ID: {}", item_id))?;
-
-        let host = self
-            .host
-            .read()
-            .await
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("Tray host not available"))?;
+        self.host_menu_items(item_id, true).await
+    }
 
-        host.get_menu_items(original_id).await
+    async fn get_current_menu_items(
+        &self,
+        item_id: &str,
+    ) -> anyhow::Result<Vec<crate::dbusmenu::MenuItem>> {
+        self.host_menu_items(item_id, false).await
     }
 
     async fn activate_menu_item(&self, item_id: &str, menu_item_id: i32) -> anyhow::Result<()> {
@@ -366,3 +591,142 @@ impl Module for TrayModule {
         host.activate_menu_item(original_id, menu_item_id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ItemCategory;
+
+    fn make_item(sni_id: &str, title: &str) -> TrayItem {
+        TrayItem {
+            id: format!(":1.{}/StatusNotifierItem", sni_id.len()),
+            sni_id: sni_id.to_string(),
+            bus_name: format!("org.example.{}", sni_id),
+            object_path: "/StatusNotifierItem".to_string(),
+            title: title.to_string(),
+            icon_name: Some("app".to_string()),
+            icon_pixmap: None,
+            icon_width: 0,
+            icon_height: 0,
+            attention_icon_name: None,
+            attention_icon_pixmap: None,
+            attention_icon_width: 0,
+            attention_icon_height: 0,
+            overlay_icon_name: None,
+            overlay_icon_pixmap: None,
+            overlay_icon_width: 0,
+            overlay_icon_height: 0,
+            tooltip: None,
+            status: ItemStatus::Active,
+            has_menu: true,
+            menu_path: Some("/MenuBar".to_string()),
+            item_is_menu: false,
+            category: ItemCategory::ApplicationStatus,
+        }
+    }
+
+    fn rule(config: TrayRuleConfig) -> TrayRule {
+        TrayRule::compile(&config).expect("rule should compile")
+    }
+
+    #[test]
+    fn test_glob_matching() {
+        let r = rule(TrayRuleConfig {
+            title: Some("chrome_status_icon_?*".to_string()),
+            ..Default::default()
+        });
+        assert!(r.matches(&make_item("chrome", "chrome_status_icon_1")));
+        assert!(!r.matches(&make_item("chrome", "chrome_status_icon_")));
+        assert!(!r.matches(&make_item("chrome", "my chrome_status_icon_1")));
+
+        // Regex metacharacters in globs are literal
+        let r = rule(TrayRuleConfig {
+            bus_name: Some("org.example.a+b".to_string()),
+            ..Default::default()
+        });
+        assert!(r.matches(&make_item("a+b", "x")));
+        assert!(!r.matches(&make_item("aab", "x")));
+    }
+
+    #[test]
+    fn test_regex_and_combined_matching() {
+        let r = rule(TrayRuleConfig {
+            id: Some("^nm-".to_string()),
+            title: Some("(?i)network".to_string()),
+            regex: true,
+            ..Default::default()
+        });
+        assert!(r.matches(&make_item("nm-applet", "Network Manager")));
+        assert!(!r.matches(&make_item("nm-applet", "Wi-Fi")));
+        assert!(!r.matches(&make_item("applet", "Network")));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_ignored() {
+        assert!(TrayRule::compile(&TrayRuleConfig::default()).is_none());
+        assert!(TrayRule::compile(&TrayRuleConfig {
+            id: Some("(".to_string()),
+            regex: true,
+            ..Default::default()
+        })
+        .is_none());
+    }
+
+    #[test]
+    fn test_rule_overrides() {
+        let item = make_item("chrome", "chrome_status_icon_1");
+        let mut module_item = TrayModule::tray_item_to_module_item(&item);
+        let r = rule(TrayRuleConfig {
+            id: Some("chrome".to_string()),
+            label: Some("Chrome".to_string()),
+            icon: Some("google-chrome".to_string()),
+            default_action: Some(TrayDefaultAction::Menu),
+            ..Default::default()
+        });
+        r.apply(&mut module_item, item.has_menu);
+
+        assert_eq!(module_item.label, "Chrome");
+        assert_eq!(module_item.icon_name.as_deref(), Some("google-chrome"));
+        let defaults: Vec<_> = module_item
+            .actions
+            .iter()
+            .filter(|a| a.is_default)
+            .map(|a| a.id.as_str())
+            .collect();
+        assert_eq!(defaults, vec!["context_menu"]);
+    }
+
+    #[test]
+    fn test_force_activate_on_menu_only_item() {
+        let mut item = make_item("menu", "Menu Only");
+        item.item_is_menu = true;
+        let mut module_item = TrayModule::tray_item_to_module_item(&item);
+        set_default_action(&mut module_item, TrayDefaultAction::Activate, item.has_menu);
+
+        let default = module_item.actions.iter().find(|a| a.is_default).unwrap();
+        assert_eq!(default.id, "activate");
+        assert_eq!(module_item.actions.iter().filter(|a| a.is_default).count(), 1);
+    }
+
+    #[test]
+    fn test_pinned_positions() {
+        let entry = |label: &str, position: Option<i32>| {
+            (ModuleItem::new("tray", label, label), position)
+        };
+        let items = apply_pinned_positions(vec![
+            entry("a", None),
+            entry("last", Some(-1)),
+            entry("b", None),
+            entry("first", Some(0)),
+            entry("second", Some(1)),
+            entry("c", None),
+            entry("second-to-last", Some(-2)),
+            entry("far", Some(100)),
+        ]);
+        let labels: Vec<_> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(
+            labels,
+            vec!["first", "second", "a", "b", "c", "far", "second-to-last", "last"]
+        );
+    }
+}
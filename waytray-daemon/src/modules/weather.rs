@@ -7,6 +7,7 @@ use serde::Deserialize;
 use tokio::sync::RwLock;
 
 use crate::config::WeatherModuleConfig;
+use crate::ItemStatus;
 use super::{Module, ModuleContext, ModuleItem};
 
 /// Response from wttr.in JSON API
@@ -128,7 +129,12 @@ impl WeatherModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(format!("Failed to load weather data\n\nError: {}\n\nRetrying shortly...", error)),
+            value: None,
             actions: Vec::new(),
         }
     }
@@ -211,7 +217,12 @@ impl WeatherModule {
             icon_pixmap: None,
             icon_width: 0,
             icon_height: 0,
+            attention_icon: None,
+            overlay_icon: None,
+            status: ItemStatus::Active,
+            category: None,
             tooltip: Some(tooltip),
+            value: None,
             actions: Vec::new(),
         })
     }
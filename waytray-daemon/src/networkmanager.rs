@@ -0,0 +1,1270 @@
+//! NetworkManager D-Bus client
+//!
+//! Reads the active connections from `org.freedesktop.NetworkManager` so the
+//! network module can show connection names, Wi-Fi SSID and signal strength
+//! and VPN state. All functions take the connection to use, which is the system
+//! bus in the daemon and a private peer-to-peer bus in tests.
+
+use std::collections::HashMap;
+
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value};
+use zbus::{proxy, Connection};
+
+pub const NM_BUS_NAME: &str = "org.freedesktop.NetworkManager";
+pub const NM_OBJECT_PATH: &str = "/org/freedesktop/NetworkManager";
+pub const NM_SETTINGS_PATH: &str = "/org/freedesktop/NetworkManager/Settings";
+
+/// NMDeviceType values
+const DEVICE_TYPE_WIFI: u32 = 2;
+const DEVICE_TYPE_MODEM: u32 = 8;
+
+/// Access point security flags (NM80211ApFlags / NM80211ApSecurityFlags)
+const AP_FLAGS_PRIVACY: u32 = 0x1;
+const AP_SEC_KEY_MGMT_PSK: u32 = 0x100;
+const AP_SEC_KEY_MGMT_802_1X: u32 = 0x200;
+const AP_SEC_KEY_MGMT_SAE: u32 = 0x400;
+const AP_SEC_KEY_MGMT_OWE: u32 = 0x800;
+
+/// Settings as returned by GetSettings (a{sa{sv}})
+pub type ConnectionSettings = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// Root NetworkManager object
+#[proxy(
+    interface = "org.freedesktop.NetworkManager",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager"
+)]
+pub trait NetworkManager {
+    /// Get all network devices
+    fn get_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    /// Activate a saved connection, optionally on a device and access point ("/" for any)
+    fn activate_connection(
+        &self,
+        connection: &ObjectPath<'_>,
+        device: &ObjectPath<'_>,
+        specific_object: &ObjectPath<'_>,
+    ) -> zbus::Result<OwnedObjectPath>;
+
+    /// Create a connection from partial settings and activate it
+    fn add_and_activate_connection(
+        &self,
+        connection: HashMap<&str, HashMap<&str, Value<'_>>>,
+        device: &ObjectPath<'_>,
+        specific_object: &ObjectPath<'_>,
+    ) -> zbus::Result<(OwnedObjectPath, OwnedObjectPath)>;
+
+    /// Deactivate an active connection
+    fn deactivate_connection(&self, active_connection: &ObjectPath<'_>) -> zbus::Result<()>;
+
+    /// Emitted when the overall networking state changes
+    #[zbus(signal)]
+    fn state_changed(&self, state: u32) -> zbus::Result<()>;
+
+    /// Named to avoid clashing with the StateChanged signal's receive method
+    #[zbus(property, name = "State")]
+    fn nm_state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn connectivity(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn primary_connection(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn active_connections(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    #[zbus(property)]
+    fn wireless_enabled(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn set_wireless_enabled(&self, enabled: bool) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn wwan_enabled(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn set_wwan_enabled(&self, enabled: bool) -> zbus::Result<()>;
+}
+
+/// Saved connection profiles
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings",
+    default_service = "org.freedesktop.NetworkManager",
+    default_path = "/org/freedesktop/NetworkManager/Settings"
+)]
+pub trait Settings {
+    fn list_connections(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+}
+
+/// A saved connection profile
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Settings.Connection",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait SettingsConnection {
+    fn get_settings(&self) -> zbus::Result<ConnectionSettings>;
+}
+
+/// An active connection (org.freedesktop.NetworkManager.Connection.Active)
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Connection.Active",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait ActiveConnection {
+    #[zbus(property)]
+    fn id(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn uuid(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "Type")]
+    fn connection_type(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn vpn(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn ip4_config(&self) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(property)]
+    fn ip6_config(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// A network device (org.freedesktop.NetworkManager.Device)
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait Device {
+    #[zbus(property)]
+    fn interface(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn ip_interface(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn device_type(&self) -> zbus::Result<u32>;
+}
+
+/// Wi-Fi specific device interface
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.Device.Wireless",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait WirelessDevice {
+    /// Get all visible access points, including hidden ones
+    fn get_all_access_points(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    /// Ask the device to scan for access points
+    fn request_scan(&self, options: HashMap<&str, Value<'_>>) -> zbus::Result<()>;
+
+    #[zbus(property)]
+    fn active_access_point(&self) -> zbus::Result<OwnedObjectPath>;
+}
+
+/// A Wi-Fi access point
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.AccessPoint",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait AccessPoint {
+    #[zbus(property)]
+    fn ssid(&self) -> zbus::Result<Vec<u8>>;
+
+    #[zbus(property)]
+    fn strength(&self) -> zbus::Result<u8>;
+
+    #[zbus(property)]
+    fn flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn wpa_flags(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn rsn_flags(&self) -> zbus::Result<u32>;
+}
+
+/// IPv4/IPv6 configuration of an active connection
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.IP4Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait Ip4Config {
+    #[zbus(property)]
+    fn address_data(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+}
+
+#[proxy(
+    interface = "org.freedesktop.NetworkManager.IP6Config",
+    default_service = "org.freedesktop.NetworkManager"
+)]
+pub trait Ip6Config {
+    #[zbus(property)]
+    fn address_data(&self) -> zbus::Result<Vec<HashMap<String, OwnedValue>>>;
+}
+
+/// Overall networking state (NMState)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NmState {
+    Unknown,
+    Asleep,
+    Disconnected,
+    Disconnecting,
+    Connecting,
+    ConnectedLocal,
+    ConnectedSite,
+    ConnectedGlobal,
+}
+
+impl NmState {
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            10 => NmState::Asleep,
+            20 => NmState::Disconnected,
+            30 => NmState::Disconnecting,
+            40 => NmState::Connecting,
+            50 => NmState::ConnectedLocal,
+            60 => NmState::ConnectedSite,
+            70 => NmState::ConnectedGlobal,
+            _ => NmState::Unknown,
+        }
+    }
+
+    pub fn is_connected(&self) -> bool {
+        matches!(
+            self,
+            NmState::ConnectedLocal | NmState::ConnectedSite | NmState::ConnectedGlobal
+        )
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            NmState::Unknown => "Unknown",
+            NmState::Asleep => "Networking disabled",
+            NmState::Disconnected => "Disconnected",
+            NmState::Disconnecting => "Disconnecting",
+            NmState::Connecting => "Connecting",
+            NmState::ConnectedLocal => "Connected (local only)",
+            NmState::ConnectedSite => "Connected (no internet)",
+            NmState::ConnectedGlobal => "Connected",
+        }
+    }
+}
+
+/// Kind of an active connection, from its connection type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionKind {
+    Wifi,
+    Ethernet,
+    Vpn,
+    Mobile,
+    Other,
+}
+
+impl ConnectionKind {
+    pub fn from_type(connection_type: &str) -> Self {
+        match connection_type {
+            "802-11-wireless" => ConnectionKind::Wifi,
+            "802-3-ethernet" => ConnectionKind::Ethernet,
+            "vpn" | "wireguard" => ConnectionKind::Vpn,
+            "gsm" | "cdma" => ConnectionKind::Mobile,
+            _ => ConnectionKind::Other,
+        }
+    }
+}
+
+/// A snapshot of one active connection
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmConnection {
+    /// Object path of the active connection
+    pub path: String,
+    /// Connection name (e.g. "Home Wi-Fi", "Wired connection 1")
+    pub id: String,
+    pub kind: ConnectionKind,
+    /// IP interface of the first device, used for traffic counters
+    pub interface: Option<String>,
+    pub ssid: Option<String>,
+    /// Wi-Fi signal strength in percent
+    pub strength: Option<u8>,
+    /// Object path of the active access point, for strength updates
+    pub access_point: Option<String>,
+    pub ipv4: Option<String>,
+    pub ipv6: Option<String>,
+}
+
+/// A snapshot of the NetworkManager state
+#[derive(Debug, Clone, PartialEq)]
+pub struct NmSnapshot {
+    pub state: NmState,
+    /// Connectivity check result: 1 none, 2 portal, 3 limited, 4 full, 0 unknown
+    pub connectivity: u32,
+    pub wireless_enabled: bool,
+    /// The connection that owns the default route
+    pub primary: Option<NmConnection>,
+    /// Active VPN connections (which may also be the primary connection)
+    pub vpns: Vec<NmConnection>,
+    /// All active connections, including the primary connection and VPNs
+    pub active: Vec<NmConnection>,
+}
+
+impl NmSnapshot {
+    /// Whether NetworkManager reports a captive portal
+    pub fn is_portal(&self) -> bool {
+        self.connectivity == 2
+    }
+}
+
+/// Check whether NetworkManager is running on this connection
+pub async fn is_available(connection: &Connection) -> bool {
+    let Ok(dbus) = zbus::fdo::DBusProxy::new(connection).await else {
+        return false;
+    };
+    match NM_BUS_NAME.try_into() {
+        Ok(name) => dbus.name_has_owner(name).await.unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+/// Fetch the current networking state and active connections
+pub async fn fetch_snapshot(connection: &Connection) -> zbus::Result<NmSnapshot> {
+    let nm = NetworkManagerProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    let state = NmState::from_u32(nm.nm_state().await?);
+    let connectivity = nm.connectivity().await.unwrap_or(0);
+    let wireless_enabled = nm.wireless_enabled().await.unwrap_or(false);
+    let primary_path = nm.primary_connection().await?;
+
+    let mut primary = None;
+    let mut vpns = Vec::new();
+    let mut active = Vec::new();
+    for path in nm.active_connections().await? {
+        let conn = match fetch_connection(connection, &path).await {
+            Ok(conn) => conn,
+            Err(e) => {
+                // Connections can disappear while we're reading them
+                tracing::debug!("Failed to read active connection {}: {}", path.as_str(), e);
+                continue;
+            }
+        };
+
+        if conn.kind == ConnectionKind::Vpn {
+            vpns.push(conn.clone());
+        }
+        if path == primary_path {
+            primary = Some(conn.clone());
+        }
+        active.push(conn);
+    }
+
+    Ok(NmSnapshot {
+        state,
+        connectivity,
+        wireless_enabled,
+        primary,
+        vpns,
+        active,
+    })
+}
+
+/// Read a single active connection with its device, access point and addresses
+async fn fetch_connection(
+    connection: &Connection,
+    path: &OwnedObjectPath,
+) -> zbus::Result<NmConnection> {
+    let active = ActiveConnectionProxy::builder(connection)
+        .path(path.clone())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    let id = active.id().await?;
+    let connection_type = active.connection_type().await?;
+    let mut kind = ConnectionKind::from_type(&connection_type);
+    if kind == ConnectionKind::Other && active.vpn().await.unwrap_or(false) {
+        kind = ConnectionKind::Vpn;
+    }
+
+    let mut interface = None;
+    let mut ssid = None;
+    let mut strength = None;
+    let mut access_point = None;
+
+    if let Some(device_path) = active.devices().await?.into_iter().next() {
+        let device = DeviceProxy::builder(connection)
+            .path(device_path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let ip_interface = device.ip_interface().await.unwrap_or_default();
+        interface = if ip_interface.is_empty() {
+            device.interface().await.ok()
+        } else {
+            Some(ip_interface)
+        };
+
+        if kind == ConnectionKind::Wifi {
+            let wireless = WirelessDeviceProxy::builder(connection)
+                .path(device_path)?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+            if let Some(ap_path) = wireless.active_access_point().await.ok().and_then(non_root) {
+                let ap = AccessPointProxy::builder(connection)
+                    .path(ap_path.clone())?
+                    .cache_properties(CacheProperties::No)
+                    .build()
+                    .await?;
+                ssid = ap.ssid().await.ok().map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+                strength = ap.strength().await.ok();
+                access_point = Some(ap_path.to_string());
+            }
+        }
+    }
+
+    let ipv4 = match active.ip4_config().await.ok().and_then(non_root) {
+        Some(config_path) => {
+            let config = Ip4ConfigProxy::builder(connection)
+                .path(config_path)?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+            config.address_data().await.ok().and_then(|data| first_address(&data))
+        }
+        None => None,
+    };
+
+    let ipv6 = match active.ip6_config().await.ok().and_then(non_root) {
+        Some(config_path) => {
+            let config = Ip6ConfigProxy::builder(connection)
+                .path(config_path)?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+            config.address_data().await.ok().and_then(|data| first_address(&data))
+        }
+        None => None,
+    };
+
+    Ok(NmConnection {
+        path: path.to_string(),
+        id,
+        kind,
+        interface,
+        ssid,
+        strength,
+        access_point,
+        ipv4,
+        ipv6,
+    })
+}
+
+/// A visible Wi-Fi network (the strongest access point for its SSID)
+#[derive(Debug, Clone, PartialEq)]
+pub struct WifiNetwork {
+    pub ssid: String,
+    pub strength: u8,
+    /// Human readable security, e.g. "WPA2" or "Open"
+    pub security: &'static str,
+    pub access_point: String,
+    pub device: String,
+}
+
+/// A saved connection profile
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedConnection {
+    pub path: String,
+    pub id: String,
+    pub uuid: String,
+    pub kind: ConnectionKind,
+    /// SSID for Wi-Fi profiles
+    pub ssid: Option<String>,
+    /// Path of the active connection if this profile is currently active
+    pub active: Option<String>,
+}
+
+/// Describe the security of an access point from its flags
+pub fn security_label(flags: u32, wpa_flags: u32, rsn_flags: u32) -> &'static str {
+    let key_mgmt = wpa_flags | rsn_flags;
+    if key_mgmt & AP_SEC_KEY_MGMT_802_1X != 0 {
+        "Enterprise"
+    } else if key_mgmt & AP_SEC_KEY_MGMT_SAE != 0 {
+        "WPA3"
+    } else if key_mgmt & AP_SEC_KEY_MGMT_OWE != 0 {
+        "Enhanced Open"
+    } else if rsn_flags & AP_SEC_KEY_MGMT_PSK != 0 {
+        "WPA2"
+    } else if wpa_flags & AP_SEC_KEY_MGMT_PSK != 0 {
+        "WPA"
+    } else if flags & AP_FLAGS_PRIVACY != 0 {
+        "WEP"
+    } else {
+        "Open"
+    }
+}
+
+/// Object paths of all devices of the given NMDeviceType
+async fn devices_of_type(connection: &Connection, device_type: u32) -> zbus::Result<Vec<OwnedObjectPath>> {
+    let nm = NetworkManagerProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    let mut result = Vec::new();
+    for path in nm.get_devices().await? {
+        let device = DeviceProxy::builder(connection)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        if device.device_type().await.unwrap_or(0) == device_type {
+            result.push(path);
+        }
+    }
+    Ok(result)
+}
+
+/// Object paths of all Wi-Fi devices
+pub async fn wifi_devices(connection: &Connection) -> zbus::Result<Vec<OwnedObjectPath>> {
+    devices_of_type(connection, DEVICE_TYPE_WIFI).await
+}
+
+/// List visible Wi-Fi networks, strongest first, one entry per SSID
+pub async fn list_wifi_networks(connection: &Connection) -> zbus::Result<Vec<WifiNetwork>> {
+    let mut networks: Vec<WifiNetwork> = Vec::new();
+
+    for device_path in wifi_devices(connection).await? {
+        let wireless = WirelessDeviceProxy::builder(connection)
+            .path(device_path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+
+        for ap_path in wireless.get_all_access_points().await? {
+            let ap = AccessPointProxy::builder(connection)
+                .path(ap_path.clone())?
+                .cache_properties(CacheProperties::No)
+                .build()
+                .await?;
+
+            // Access points can vanish between listing and reading them
+            let Ok(ssid) = ap.ssid().await else {
+                continue;
+            };
+            if ssid.is_empty() {
+                continue; // Hidden network
+            }
+            let ssid = String::from_utf8_lossy(&ssid).into_owned();
+            let strength = ap.strength().await.unwrap_or(0);
+            let security = security_label(
+                ap.flags().await.unwrap_or(0),
+                ap.wpa_flags().await.unwrap_or(0),
+                ap.rsn_flags().await.unwrap_or(0),
+            );
+
+            if let Some(existing) = networks.iter_mut().find(|n| n.ssid == ssid) {
+                if strength <= existing.strength {
+                    continue;
+                }
+                *existing = WifiNetwork {
+                    ssid,
+                    strength,
+                    security,
+                    access_point: ap_path.to_string(),
+                    device: device_path.to_string(),
+                };
+            } else {
+                networks.push(WifiNetwork {
+                    ssid,
+                    strength,
+                    security,
+                    access_point: ap_path.to_string(),
+                    device: device_path.to_string(),
+                });
+            }
+        }
+    }
+
+    networks.sort_by(|a, b| b.strength.cmp(&a.strength).then_with(|| a.ssid.cmp(&b.ssid)));
+    Ok(networks)
+}
+
+/// Ask all Wi-Fi devices to rescan
+pub async fn request_scan(connection: &Connection) -> zbus::Result<()> {
+    for device_path in wifi_devices(connection).await? {
+        let wireless = WirelessDeviceProxy::builder(connection)
+            .path(device_path)?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        wireless.request_scan(HashMap::new()).await?;
+    }
+    Ok(())
+}
+
+/// List saved connection profiles, marking the ones that are active
+pub async fn list_saved_connections(connection: &Connection) -> zbus::Result<Vec<SavedConnection>> {
+    let nm = NetworkManagerProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    // Map active connections by profile UUID
+    let mut active = HashMap::new();
+    for path in nm.active_connections().await? {
+        let proxy = ActiveConnectionProxy::builder(connection)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        if let Ok(uuid) = proxy.uuid().await {
+            active.insert(uuid, path.to_string());
+        }
+    }
+
+    let settings = SettingsProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+
+    let mut result = Vec::new();
+    for path in settings.list_connections().await? {
+        let profile = SettingsConnectionProxy::builder(connection)
+            .path(path.clone())?
+            .cache_properties(CacheProperties::No)
+            .build()
+            .await?;
+        let Ok(settings) = profile.get_settings().await else {
+            continue;
+        };
+        if let Some(saved) = parse_saved_connection(path.as_str(), &settings, &active) {
+            result.push(saved);
+        }
+    }
+
+    result.sort_by_key(|c| c.id.to_lowercase());
+    Ok(result)
+}
+
+/// Build a SavedConnection from GetSettings output, skipping loopback and bridge ports
+fn parse_saved_connection(
+    path: &str,
+    settings: &ConnectionSettings,
+    active: &HashMap<String, String>,
+) -> Option<SavedConnection> {
+    let section = settings.get("connection")?;
+    let string = |key: &str| section.get(key).and_then(|v| String::try_from(v.clone()).ok());
+
+    let id = string("id")?;
+    let uuid = string("uuid")?;
+    let connection_type = string("type")?;
+    if connection_type == "loopback" {
+        return None;
+    }
+
+    let ssid = settings
+        .get("802-11-wireless")
+        .and_then(|wireless| wireless.get("ssid"))
+        .and_then(|v| Vec::<u8>::try_from(v.clone()).ok())
+        .map(|bytes| String::from_utf8_lossy(&bytes).into_owned());
+
+    Some(SavedConnection {
+        path: path.to_string(),
+        active: active.get(&uuid).cloned(),
+        id,
+        uuid,
+        kind: ConnectionKind::from_type(&connection_type),
+        ssid,
+    })
+}
+
+/// Activate a saved profile (on any suitable device)
+pub async fn activate_saved(connection: &Connection, profile: &str) -> zbus::Result<()> {
+    let nm = NetworkManagerProxy::new(connection).await?;
+    let root = ObjectPath::from_static_str_unchecked("/");
+    nm.activate_connection(&ObjectPath::try_from(profile)?, &root, &root)
+        .await?;
+    Ok(())
+}
+
+/// Connect to a Wi-Fi network, reusing a saved profile for its SSID when there is one
+///
+/// New secured networks need a secret agent (e.g. the desktop's keyring prompt)
+/// to ask for the password.
+pub async fn connect_wifi(
+    connection: &Connection,
+    network: &WifiNetwork,
+    saved_profile: Option<&str>,
+) -> zbus::Result<()> {
+    let nm = NetworkManagerProxy::new(connection).await?;
+    let device = ObjectPath::try_from(network.device.as_str())?;
+    let ap = ObjectPath::try_from(network.access_point.as_str())?;
+
+    match saved_profile {
+        Some(profile) => {
+            nm.activate_connection(&ObjectPath::try_from(profile)?, &device, &ap)
+                .await?;
+        }
+        None => {
+            nm.add_and_activate_connection(HashMap::new(), &device, &ap)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Deactivate an active connection
+pub async fn deactivate(connection: &Connection, active_connection: &str) -> zbus::Result<()> {
+    let nm = NetworkManagerProxy::new(connection).await?;
+    nm.deactivate_connection(&ObjectPath::try_from(active_connection)?)
+        .await
+}
+
+/// Radio switch state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RadioState {
+    pub wireless_enabled: bool,
+    pub wwan_enabled: bool,
+    /// Whether a mobile broadband modem is present
+    pub has_modem: bool,
+}
+
+impl RadioState {
+    /// Airplane mode means every radio NetworkManager controls is off
+    ///
+    /// WwanEnabled stays true on machines without a modem, so it only counts
+    /// when one is present.
+    pub fn airplane_mode(&self) -> bool {
+        let wwan_on = self.has_modem && self.wwan_enabled;
+        !(self.wireless_enabled || wwan_on)
+    }
+}
+
+/// Read the Wi-Fi and mobile broadband switches
+pub async fn radio_state(connection: &Connection) -> zbus::Result<RadioState> {
+    let nm = NetworkManagerProxy::builder(connection)
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    Ok(RadioState {
+        wireless_enabled: nm.wireless_enabled().await?,
+        wwan_enabled: nm.wwan_enabled().await.unwrap_or(false),
+        has_modem: !devices_of_type(connection, DEVICE_TYPE_MODEM).await?.is_empty(),
+    })
+}
+
+/// Turn Wi-Fi on or off
+pub async fn set_wireless_enabled(connection: &Connection, enabled: bool) -> zbus::Result<()> {
+    let nm = NetworkManagerProxy::new(connection).await?;
+    nm.set_wireless_enabled(enabled).await
+}
+
+/// Turn airplane mode on (all radios off) or off (Wi-Fi and mobile broadband back on)
+pub async fn set_airplane_mode(connection: &Connection, enabled: bool) -> zbus::Result<()> {
+    let nm = NetworkManagerProxy::new(connection).await?;
+    nm.set_wireless_enabled(!enabled).await?;
+    if !devices_of_type(connection, DEVICE_TYPE_MODEM).await?.is_empty() {
+        nm.set_wwan_enabled(!enabled).await?;
+    }
+    Ok(())
+}
+
+/// NetworkManager uses "/" for unset object path properties
+fn non_root(path: OwnedObjectPath) -> Option<OwnedObjectPath> {
+    if path.as_str() == "/" {
+        None
+    } else {
+        Some(path)
+    }
+}
+
+/// Get the first "address" entry from AddressData
+fn first_address(data: &[HashMap<String, OwnedValue>]) -> Option<String> {
+    data.iter()
+        .filter_map(|entry| entry.get("address"))
+        .find_map(|value| String::try_from(value.clone()).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use std::sync::{Arc, Mutex};
+    use zbus::object_server::SignalEmitter;
+    use zbus::{connection, interface};
+
+    struct MockManager {
+        state: u32,
+        active: Vec<OwnedObjectPath>,
+        primary: OwnedObjectPath,
+        devices: Vec<OwnedObjectPath>,
+        /// Records of ActivateConnection/DeactivateConnection calls
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager")]
+    impl MockManager {
+        fn get_devices(&self) -> Vec<OwnedObjectPath> {
+            self.devices.clone()
+        }
+
+        fn activate_connection(
+            &self,
+            connection: OwnedObjectPath,
+            device: OwnedObjectPath,
+            specific_object: OwnedObjectPath,
+        ) -> OwnedObjectPath {
+            self.calls.lock().unwrap().push(format!(
+                "activate {} {} {}",
+                connection.as_str(),
+                device.as_str(),
+                specific_object.as_str()
+            ));
+            path("/org/freedesktop/NetworkManager/ActiveConnection/9")
+        }
+
+        fn deactivate_connection(&self, active_connection: OwnedObjectPath) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("deactivate {}", active_connection.as_str()));
+        }
+
+        #[zbus(property, name = "State")]
+        fn nm_state(&self) -> u32 {
+            self.state
+        }
+
+        #[zbus(property)]
+        fn connectivity(&self) -> u32 {
+            4
+        }
+
+        #[zbus(property)]
+        fn primary_connection(&self) -> OwnedObjectPath {
+            self.primary.clone()
+        }
+
+        #[zbus(property)]
+        fn active_connections(&self) -> Vec<OwnedObjectPath> {
+            self.active.clone()
+        }
+
+        #[zbus(property)]
+        fn wireless_enabled(&self) -> bool {
+            true
+        }
+
+        #[zbus(signal, name = "StateChanged")]
+        async fn emit_state_changed(emitter: &SignalEmitter<'_>, state: u32) -> zbus::Result<()>;
+    }
+
+    struct MockActive {
+        id: String,
+        uuid: String,
+        connection_type: String,
+        device: OwnedObjectPath,
+        ip4: OwnedObjectPath,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager.Connection.Active")]
+    impl MockActive {
+        #[zbus(property)]
+        fn id(&self) -> String {
+            self.id.clone()
+        }
+
+        #[zbus(property)]
+        fn uuid(&self) -> String {
+            self.uuid.clone()
+        }
+
+        #[zbus(property, name = "Type")]
+        fn connection_type(&self) -> String {
+            self.connection_type.clone()
+        }
+
+        #[zbus(property)]
+        fn devices(&self) -> Vec<OwnedObjectPath> {
+            vec![self.device.clone()]
+        }
+
+        #[zbus(property)]
+        fn state(&self) -> u32 {
+            2
+        }
+
+        #[zbus(property)]
+        fn vpn(&self) -> bool {
+            self.connection_type == "vpn"
+        }
+
+        #[zbus(property)]
+        fn ip4_config(&self) -> OwnedObjectPath {
+            self.ip4.clone()
+        }
+
+        #[zbus(property)]
+        fn ip6_config(&self) -> OwnedObjectPath {
+            path("/")
+        }
+    }
+
+    struct MockDevice {
+        interface: String,
+        device_type: u32,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager.Device")]
+    impl MockDevice {
+        #[zbus(property)]
+        fn interface(&self) -> String {
+            self.interface.clone()
+        }
+
+        #[zbus(property)]
+        fn ip_interface(&self) -> String {
+            self.interface.clone()
+        }
+
+        #[zbus(property)]
+        fn device_type(&self) -> u32 {
+            self.device_type
+        }
+    }
+
+    struct MockWireless;
+
+    #[interface(name = "org.freedesktop.NetworkManager.Device.Wireless")]
+    impl MockWireless {
+        fn get_all_access_points(&self) -> Vec<OwnedObjectPath> {
+            vec![
+                path("/org/freedesktop/NetworkManager/AccessPoint/1"),
+                path("/org/freedesktop/NetworkManager/AccessPoint/2"),
+                path("/org/freedesktop/NetworkManager/AccessPoint/3"),
+                path("/org/freedesktop/NetworkManager/AccessPoint/4"),
+            ]
+        }
+
+        fn request_scan(&self, _options: HashMap<String, OwnedValue>) {}
+
+        #[zbus(property)]
+        fn active_access_point(&self) -> OwnedObjectPath {
+            path("/org/freedesktop/NetworkManager/AccessPoint/1")
+        }
+    }
+
+    struct MockAccessPoint {
+        ssid: &'static str,
+        strength: u8,
+        rsn_flags: u32,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager.AccessPoint")]
+    impl MockAccessPoint {
+        #[zbus(property)]
+        fn ssid(&self) -> Vec<u8> {
+            self.ssid.as_bytes().to_vec()
+        }
+
+        #[zbus(property)]
+        fn strength(&self) -> u8 {
+            self.strength
+        }
+
+        #[zbus(property)]
+        fn flags(&self) -> u32 {
+            if self.rsn_flags != 0 { AP_FLAGS_PRIVACY } else { 0 }
+        }
+
+        #[zbus(property)]
+        fn wpa_flags(&self) -> u32 {
+            0
+        }
+
+        #[zbus(property)]
+        fn rsn_flags(&self) -> u32 {
+            self.rsn_flags
+        }
+    }
+
+    struct MockSettings;
+
+    #[interface(name = "org.freedesktop.NetworkManager.Settings")]
+    impl MockSettings {
+        fn list_connections(&self) -> Vec<OwnedObjectPath> {
+            vec![
+                path("/org/freedesktop/NetworkManager/Settings/1"),
+                path("/org/freedesktop/NetworkManager/Settings/2"),
+                path("/org/freedesktop/NetworkManager/Settings/3"),
+            ]
+        }
+    }
+
+    struct MockProfile {
+        id: &'static str,
+        uuid: &'static str,
+        connection_type: &'static str,
+        ssid: Option<&'static str>,
+    }
+
+    #[interface(name = "org.freedesktop.NetworkManager.Settings.Connection")]
+    impl MockProfile {
+        fn get_settings(&self) -> HashMap<String, HashMap<String, OwnedValue>> {
+            let value = |v: Value<'_>| OwnedValue::try_from(v).unwrap();
+            let mut settings = HashMap::new();
+
+            let mut section = HashMap::new();
+            section.insert("id".to_string(), value(Value::from(self.id)));
+            section.insert("uuid".to_string(), value(Value::from(self.uuid)));
+            section.insert("type".to_string(), value(Value::from(self.connection_type)));
+            settings.insert("connection".to_string(), section);
+
+            if let Some(ssid) = self.ssid {
+                let mut wireless = HashMap::new();
+                wireless.insert(
+                    "ssid".to_string(),
+                    value(Value::from(ssid.as_bytes().to_vec())),
+                );
+                settings.insert("802-11-wireless".to_string(), wireless);
+            }
+            settings
+        }
+    }
+
+    struct MockIp4Config;
+
+    #[interface(name = "org.freedesktop.NetworkManager.IP4Config")]
+    impl MockIp4Config {
+        #[zbus(property)]
+        fn address_data(&self) -> Vec<HashMap<String, OwnedValue>> {
+            let mut entry = HashMap::new();
+            entry.insert(
+                "address".to_string(),
+                OwnedValue::try_from(Value::from("192.168.1.20")).unwrap(),
+            );
+            vec![entry]
+        }
+    }
+
+    fn path(p: &str) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(p).unwrap()
+    }
+
+    /// Serve a mock NetworkManager with a Wi-Fi connection and a VPN on a private bus
+    async fn mock_network_manager() -> (Connection, Connection, Arc<Mutex<Vec<String>>>) {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let (server_stream, client_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+
+        let wifi = "/org/freedesktop/NetworkManager/ActiveConnection/1";
+        let vpn = "/org/freedesktop/NetworkManager/ActiveConnection/2";
+        let wlan = "/org/freedesktop/NetworkManager/Devices/3";
+        let tun = "/org/freedesktop/NetworkManager/Devices/4";
+        let ip4 = "/org/freedesktop/NetworkManager/IP4Config/1";
+
+        let server = connection::Builder::async_io_unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(
+                NM_OBJECT_PATH,
+                MockManager {
+                    state: 70,
+                    active: vec![path(wifi), path(vpn)],
+                    primary: path(wifi),
+                    devices: vec![path(wlan), path(tun)],
+                    calls: calls.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                wifi,
+                MockActive {
+                    id: "Home".to_string(),
+                    uuid: "uuid-home".to_string(),
+                    connection_type: "802-11-wireless".to_string(),
+                    device: path(wlan),
+                    ip4: path(ip4),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                vpn,
+                MockActive {
+                    id: "Work VPN".to_string(),
+                    uuid: "uuid-vpn".to_string(),
+                    connection_type: "vpn".to_string(),
+                    device: path(tun),
+                    ip4: path("/"),
+                },
+            )
+            .unwrap()
+            .serve_at(wlan, MockDevice { interface: "wlan0".to_string(), device_type: 2 })
+            .unwrap()
+            .serve_at(wlan, MockWireless)
+            .unwrap()
+            .serve_at(tun, MockDevice { interface: "tun0".to_string(), device_type: 29 })
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/AccessPoint/1",
+                MockAccessPoint { ssid: "HomeNet", strength: 72, rsn_flags: AP_SEC_KEY_MGMT_PSK },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/AccessPoint/2",
+                MockAccessPoint { ssid: "Cafe", strength: 40, rsn_flags: 0 },
+            )
+            .unwrap()
+            .serve_at(
+                // Second access point of the same network, stronger
+                "/org/freedesktop/NetworkManager/AccessPoint/3",
+                MockAccessPoint { ssid: "Cafe", strength: 85, rsn_flags: 0 },
+            )
+            .unwrap()
+            .serve_at(
+                // Hidden network
+                "/org/freedesktop/NetworkManager/AccessPoint/4",
+                MockAccessPoint { ssid: "", strength: 90, rsn_flags: AP_SEC_KEY_MGMT_PSK },
+            )
+            .unwrap()
+            .serve_at(NM_SETTINGS_PATH, MockSettings)
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Settings/1",
+                MockProfile { id: "Home", uuid: "uuid-home", connection_type: "802-11-wireless", ssid: Some("HomeNet") },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Settings/2",
+                MockProfile { id: "Work VPN", uuid: "uuid-vpn", connection_type: "vpn", ssid: None },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Settings/3",
+                MockProfile { id: "lo", uuid: "uuid-lo", connection_type: "loopback", ssid: None },
+            )
+            .unwrap()
+            .serve_at(ip4, MockIp4Config)
+            .unwrap()
+            .build();
+        let client = connection::Builder::async_io_unix_stream(client_stream).p2p().build();
+
+        let (server, client) = futures::try_join!(server, client).unwrap();
+        (server, client, calls)
+    }
+
+    #[test]
+    fn test_state_mapping() {
+        assert_eq!(NmState::from_u32(70), NmState::ConnectedGlobal);
+        assert!(NmState::from_u32(50).is_connected());
+        assert!(!NmState::from_u32(40).is_connected());
+        assert_eq!(NmState::from_u32(5), NmState::Unknown);
+        assert_eq!(ConnectionKind::from_type("wireguard"), ConnectionKind::Vpn);
+        assert_eq!(ConnectionKind::from_type("802-3-ethernet"), ConnectionKind::Ethernet);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_snapshot_from_mock() {
+        let (_server, client, _calls) = mock_network_manager().await;
+
+        let snapshot = fetch_snapshot(&client).await.unwrap();
+        assert_eq!(snapshot.state, NmState::ConnectedGlobal);
+        assert!(snapshot.wireless_enabled);
+
+        let primary = snapshot.primary.unwrap();
+        assert_eq!(primary.id, "Home");
+        assert_eq!(primary.kind, ConnectionKind::Wifi);
+        assert_eq!(primary.interface.as_deref(), Some("wlan0"));
+        assert_eq!(primary.ssid.as_deref(), Some("HomeNet"));
+        assert_eq!(primary.strength, Some(72));
+        assert_eq!(primary.ipv4.as_deref(), Some("192.168.1.20"));
+        assert_eq!(primary.ipv6, None);
+
+        assert_eq!(snapshot.vpns.len(), 1);
+        assert_eq!(snapshot.vpns[0].id, "Work VPN");
+        assert_eq!(snapshot.vpns[0].interface.as_deref(), Some("tun0"));
+        assert_eq!(snapshot.active.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_state_changed_signal_from_mock() {
+        let (server, client, _calls) = mock_network_manager().await;
+
+        let nm = NetworkManagerProxy::new(&client).await.unwrap();
+        let mut stream = nm.receive_state_changed().await.unwrap();
+
+        let emitter = SignalEmitter::new(&server, NM_OBJECT_PATH).unwrap();
+        MockManager::emit_state_changed(&emitter, 20).await.unwrap();
+
+        let signal = stream.next().await.unwrap();
+        assert_eq!(signal.args().unwrap().state, 20);
+    }
+
+    #[test]
+    fn test_security_label() {
+        assert_eq!(security_label(0, 0, 0), "Open");
+        assert_eq!(security_label(AP_FLAGS_PRIVACY, 0, 0), "WEP");
+        assert_eq!(security_label(AP_FLAGS_PRIVACY, AP_SEC_KEY_MGMT_PSK, 0), "WPA");
+        assert_eq!(security_label(AP_FLAGS_PRIVACY, 0, AP_SEC_KEY_MGMT_PSK), "WPA2");
+        assert_eq!(
+            security_label(AP_FLAGS_PRIVACY, 0, AP_SEC_KEY_MGMT_PSK | AP_SEC_KEY_MGMT_SAE),
+            "WPA3"
+        );
+        assert_eq!(security_label(AP_FLAGS_PRIVACY, 0, AP_SEC_KEY_MGMT_802_1X), "Enterprise");
+    }
+
+    #[tokio::test]
+    async fn test_list_wifi_networks_from_mock() {
+        let (_server, client, _calls) = mock_network_manager().await;
+
+        let networks = list_wifi_networks(&client).await.unwrap();
+        let summary: Vec<(&str, u8, &str)> = networks
+            .iter()
+            .map(|n| (n.ssid.as_str(), n.strength, n.security))
+            .collect();
+        assert_eq!(summary, vec![("Cafe", 85, "Open"), ("HomeNet", 72, "WPA2")]);
+        assert_eq!(networks[0].access_point, "/org/freedesktop/NetworkManager/AccessPoint/3");
+        assert_eq!(networks[0].device, "/org/freedesktop/NetworkManager/Devices/3");
+    }
+
+    #[tokio::test]
+    async fn test_saved_connections_and_activation_from_mock() {
+        let (_server, client, calls) = mock_network_manager().await;
+
+        let saved = list_saved_connections(&client).await.unwrap();
+        assert_eq!(saved.len(), 2);
+        assert_eq!(saved[0].id, "Home");
+        assert_eq!(saved[0].ssid.as_deref(), Some("HomeNet"));
+        assert_eq!(
+            saved[0].active.as_deref(),
+            Some("/org/freedesktop/NetworkManager/ActiveConnection/1")
+        );
+        assert_eq!(saved[1].kind, ConnectionKind::Vpn);
+
+        let networks = list_wifi_networks(&client).await.unwrap();
+        connect_wifi(&client, &networks[1], Some(&saved[0].path)).await.unwrap();
+        deactivate(&client, saved[1].active.as_deref().unwrap()).await.unwrap();
+
+        let calls = calls.lock().unwrap();
+        assert_eq!(
+            calls.as_slice(),
+            [
+                "activate /org/freedesktop/NetworkManager/Settings/1 \
+                 /org/freedesktop/NetworkManager/Devices/3 \
+                 /org/freedesktop/NetworkManager/AccessPoint/1",
+                "deactivate /org/freedesktop/NetworkManager/ActiveConnection/2",
+            ]
+        );
+    }
+}
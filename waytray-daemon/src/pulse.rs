@@ -0,0 +1,449 @@
+//! Native PulseAudio protocol client
+//!
+//! Talks the PulseAudio native protocol directly over the server's unix socket.
+//! This works with both PulseAudio and PipeWire (via pipewire-pulse) without
+//! spawning `pactl`. The protocol implementation is blocking, so every request
+//! runs on tokio's blocking pool, and subscriptions are read on a dedicated
+//! thread that forwards events into an async channel.
+
+use std::ffi::CString;
+use std::io::BufReader;
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::Context;
+use pulseaudio::protocol::{self, Command, CommandReply, ProtocolError};
+use tokio::sync::{mpsc, Notify};
+
+pub use pulseaudio::protocol::{
+    ChannelVolume, Prop, Props, ServerInfo, SinkInfo, SinkInputInfo, SourceInfo, SourceOutputInfo,
+    SubscriptionEvent, SubscriptionEventFacility, SubscriptionEventType, SubscriptionMask, Volume,
+};
+
+/// How long to wait for a reply before giving up on a request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// An authenticated connection to the audio server
+struct Connection {
+    sock: BufReader<UnixStream>,
+    version: u16,
+    seq: u32,
+    /// Set once a reply was lost or misread; the stream can't be trusted after that
+    broken: bool,
+}
+
+impl Connection {
+    /// Connect to the server socket, authenticate and announce the client name
+    fn open(client_name: &str) -> anyhow::Result<Self> {
+        let socket_path = pulseaudio::socket_path_from_env()
+            .context("No PulseAudio/PipeWire socket found")?;
+        let stream = UnixStream::connect(&socket_path)
+            .with_context(|| format!("Failed to connect to {}", socket_path.display()))?;
+
+        // Missing cookie is fine: pipewire-pulse and local PulseAudio don't require one
+        let cookie = pulseaudio::cookie_path_from_env()
+            .and_then(|path| std::fs::read(path).ok())
+            .unwrap_or_default();
+
+        let mut conn = Self {
+            sock: BufReader::new(stream),
+            version: protocol::MAX_VERSION,
+            seq: 0,
+            broken: false,
+        };
+
+        let auth = protocol::AuthParams {
+            version: protocol::MAX_VERSION,
+            supports_shm: false,
+            supports_memfd: false,
+            cookie,
+        };
+        let auth_reply: protocol::AuthReply = conn.request(Command::Auth(auth))?;
+        conn.version = protocol::MAX_VERSION.min(auth_reply.version);
+
+        let mut props = Props::new();
+        props.set(Prop::ApplicationName, CString::new(client_name)?);
+        let _: protocol::SetClientNameReply = conn.request(Command::SetClientName(props))?;
+
+        Ok(conn)
+    }
+
+    fn next_seq(&mut self) -> u32 {
+        let seq = self.seq;
+        self.seq = self.seq.wrapping_add(1);
+        seq
+    }
+
+    /// Send a command and read its reply payload
+    fn request<T: CommandReply>(&mut self, command: Command) -> anyhow::Result<T> {
+        anyhow::ensure!(!self.broken, "Audio server connection is out of sync");
+        let seq = self.next_seq();
+        let result = protocol::write_command_message(self.sock.get_mut(), seq, &command, self.version)
+            .and_then(|_| protocol::read_reply_message::<T>(&mut self.sock, self.version));
+        match result {
+            Ok((reply_seq, reply)) => {
+                self.check_seq(reply_seq, seq)?;
+                Ok(reply)
+            }
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+
+    /// Send a command that is answered with an empty reply
+    fn request_ack(&mut self, command: Command) -> anyhow::Result<()> {
+        anyhow::ensure!(!self.broken, "Audio server connection is out of sync");
+        let seq = self.next_seq();
+        let result = protocol::write_command_message(self.sock.get_mut(), seq, &command, self.version)
+            .and_then(|_| protocol::read_ack_message(&mut self.sock));
+        match result {
+            Ok(reply_seq) => self.check_seq(reply_seq, seq),
+            Err(e) => Err(self.fail(e)),
+        }
+    }
+
+    fn check_seq(&mut self, reply_seq: u32, seq: u32) -> anyhow::Result<()> {
+        if reply_seq != seq {
+            self.broken = true;
+            anyhow::bail!("Reply out of sequence ({} != {})", reply_seq, seq);
+        }
+        Ok(())
+    }
+
+    /// Record a failed exchange. Server errors are complete messages, so the
+    /// stream stays usable; anything else (timeouts, short reads) leaves an
+    /// unknown amount of the reply unread.
+    fn fail(&mut self, error: ProtocolError) -> anyhow::Error {
+        if !matches!(error, ProtocolError::ServerError(_)) {
+            self.broken = true;
+        }
+        error.into()
+    }
+}
+
+/// Long-lived connection used for queries and control commands
+///
+/// Requests are serialized over a single socket; cloning is cheap and shares it.
+/// Once a request leaves the socket out of sync every later request fails, and
+/// [`PulseClient::broken`] resolves so the owner can reconnect.
+#[derive(Clone)]
+pub struct PulseClient {
+    conn: Arc<Mutex<Connection>>,
+    broken: Arc<Notify>,
+}
+
+impl PulseClient {
+    /// Connect to the audio server
+    pub async fn connect(client_name: &str) -> anyhow::Result<Self> {
+        let client_name = client_name.to_string();
+        let conn = tokio::task::spawn_blocking(move || {
+            let conn = Connection::open(&client_name)?;
+            conn.sock.get_ref().set_read_timeout(Some(REQUEST_TIMEOUT))?;
+            anyhow::Ok(conn)
+        })
+        .await??;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+            broken: Arc::new(Notify::new()),
+        })
+    }
+
+    /// Wait until a request has left the connection unusable
+    pub async fn broken(&self) {
+        self.broken.notified().await
+    }
+
+    /// Run a request on the blocking pool, flagging the client if it broke the connection
+    async fn with_conn<T, F>(&self, f: F) -> anyhow::Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> anyhow::Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        let (result, broken) = tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().map_err(|_| anyhow::anyhow!("Connection poisoned"))?;
+            let result = f(&mut conn);
+            anyhow::Ok((result, conn.broken))
+        })
+        .await??;
+
+        if broken {
+            self.broken.notify_one();
+        }
+        result
+    }
+
+    async fn request<T>(&self, command: Command) -> anyhow::Result<T>
+    where
+        T: CommandReply + Send + 'static,
+    {
+        self.with_conn(move |conn| conn.request(command)).await
+    }
+
+    async fn request_ack(&self, command: Command) -> anyhow::Result<()> {
+        self.with_conn(move |conn| conn.request_ack(command)).await
+    }
+
+    pub async fn server_info(&self) -> anyhow::Result<ServerInfo> {
+        self.request(Command::GetServerInfo).await
+    }
+
+    pub async fn sinks(&self) -> anyhow::Result<Vec<SinkInfo>> {
+        self.request(Command::GetSinkInfoList).await
+    }
+
+    pub async fn sources(&self) -> anyhow::Result<Vec<SourceInfo>> {
+        self.request(Command::GetSourceInfoList).await
+    }
+
+    pub async fn source_outputs(&self) -> anyhow::Result<Vec<SourceOutputInfo>> {
+        self.request(Command::GetSourceOutputInfoList).await
+    }
+
+    pub async fn sink_inputs(&self) -> anyhow::Result<Vec<SinkInputInfo>> {
+        self.request(Command::GetSinkInputInfoList).await
+    }
+
+    pub async fn set_sink_volume(&self, index: u32, volume: ChannelVolume) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSinkVolume(protocol::SetDeviceVolumeParams {
+            device_index: Some(index),
+            device_name: None,
+            volume,
+        }))
+        .await
+    }
+
+    pub async fn set_source_volume(&self, index: u32, volume: ChannelVolume) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSourceVolume(protocol::SetDeviceVolumeParams {
+            device_index: Some(index),
+            device_name: None,
+            volume,
+        }))
+        .await
+    }
+
+    pub async fn set_sink_input_volume(&self, index: u32, volume: ChannelVolume) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSinkInputVolume(protocol::SetStreamVolumeParams {
+            index,
+            volume,
+        }))
+        .await
+    }
+
+    pub async fn set_source_output_volume(&self, index: u32, volume: ChannelVolume) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSourceOutputVolume(protocol::SetStreamVolumeParams {
+            index,
+            volume,
+        }))
+        .await
+    }
+
+    pub async fn set_sink_mute(&self, index: u32, mute: bool) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSinkMute(protocol::SetDeviceMuteParams {
+            device_index: Some(index),
+            device_name: None,
+            mute,
+        }))
+        .await
+    }
+
+    pub async fn set_source_mute(&self, index: u32, mute: bool) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSourceMute(protocol::SetDeviceMuteParams {
+            device_index: Some(index),
+            device_name: None,
+            mute,
+        }))
+        .await
+    }
+
+    pub async fn set_default_sink(&self, name: CString) -> anyhow::Result<()> {
+        self.request_ack(Command::SetDefaultSink(name)).await
+    }
+
+    pub async fn set_default_source(&self, name: CString) -> anyhow::Result<()> {
+        self.request_ack(Command::SetDefaultSource(name)).await
+    }
+
+    pub async fn move_sink_input(&self, index: u32, sink_index: u32) -> anyhow::Result<()> {
+        self.request_ack(Command::MoveSinkInput(protocol::MoveStreamParams {
+            index: Some(index),
+            device_index: Some(sink_index),
+            device_name: None,
+        }))
+        .await
+    }
+
+    pub async fn move_source_output(&self, index: u32, source_index: u32) -> anyhow::Result<()> {
+        self.request_ack(Command::MoveSourceOutput(protocol::MoveStreamParams {
+            index: Some(index),
+            device_index: Some(source_index),
+            device_name: None,
+        }))
+        .await
+    }
+
+    pub async fn set_sink_input_mute(&self, index: u32, mute: bool) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSinkInputMute(protocol::SetStreamMuteParams { index, mute }))
+            .await
+    }
+
+    pub async fn set_source_output_mute(&self, index: u32, mute: bool) -> anyhow::Result<()> {
+        self.request_ack(Command::SetSourceOutputMute(protocol::SetStreamMuteParams { index, mute }))
+            .await
+    }
+}
+
+/// Stream of server events on a dedicated connection
+///
+/// Dropping the subscription closes the socket, which ends the reader thread.
+pub struct PulseSubscription {
+    events: mpsc::UnboundedReceiver<SubscriptionEvent>,
+    stream: UnixStream,
+}
+
+impl PulseSubscription {
+    /// Open a new connection and subscribe to the given event facilities
+    pub async fn subscribe(client_name: &str, mask: SubscriptionMask) -> anyhow::Result<Self> {
+        let client_name = client_name.to_string();
+        let mut conn = tokio::task::spawn_blocking(move || {
+            let mut conn = Connection::open(&client_name)?;
+            conn.request_ack(Command::Subscribe(mask))?;
+            anyhow::Ok(conn)
+        })
+        .await??;
+
+        let stream = conn.sock.get_ref().try_clone()?;
+        let (tx, events) = mpsc::unbounded_channel();
+
+        std::thread::Builder::new()
+            .name("pulse-events".to_string())
+            .spawn(move || loop {
+                match protocol::read_command_message(&mut conn.sock, conn.version) {
+                    Ok((_, Command::SubscribeEvent(event))) => {
+                        if tx.send(event).is_err() {
+                            break;
+                        }
+                    }
+                    Ok((_, other)) => {
+                        tracing::trace!("Ignoring unexpected audio server message: {:?}", other);
+                    }
+                    Err(e) => {
+                        tracing::debug!("Audio server subscription ended: {}", e);
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self { events, stream })
+    }
+
+    /// Wait for the next event. Returns None once the server connection is gone.
+    pub async fn next(&mut self) -> Option<SubscriptionEvent> {
+        self.events.recv().await
+    }
+
+    /// Discard events that are already queued, so a burst triggers a single refresh
+    pub fn drain(&mut self) {
+        while self.events.try_recv().is_ok() {}
+    }
+}
+
+impl Drop for PulseSubscription {
+    fn drop(&mut self) {
+        let _ = self.stream.shutdown(std::net::Shutdown::Both);
+    }
+}
+
+/// Read a string property, stripping the trailing NUL the protocol includes
+pub fn prop_string(props: &Props, prop: Prop) -> Option<String> {
+    let bytes = props.get(prop)?;
+    let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+    let value = String::from_utf8_lossy(bytes).trim().to_string();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Overall volume as a percentage (the loudest channel, like pactl/pavucontrol)
+pub fn volume_percent(volume: &ChannelVolume) -> u32 {
+    let max = volume
+        .channels()
+        .iter()
+        .map(|v| v.as_u32())
+        .max()
+        .unwrap_or(0) as u64;
+    let norm = Volume::NORM.as_u32() as u64;
+    ((max * 100 + norm / 2) / norm) as u32
+}
+
+/// Shift every channel by `delta_percent`, keeping the channel balance intact
+///
+/// The loudest channel is capped at `max_percent`; channels never go below zero.
+pub fn adjust_volume(volume: &ChannelVolume, delta_percent: i32, max_percent: u32) -> ChannelVolume {
+    let norm = Volume::NORM.as_u32() as i64;
+    let delta = delta_percent as i64 * norm / 100;
+    let ceiling = max_percent as i64 * norm / 100;
+
+    let loudest = volume
+        .channels()
+        .iter()
+        .map(|v| v.as_u32() as i64)
+        .max()
+        .unwrap_or(0);
+    // Never push the loudest channel past the ceiling, but don't pull it down either
+    let delta = if delta > 0 {
+        delta.min((ceiling - loudest).max(0))
+    } else {
+        delta
+    };
+
+    let mut adjusted = ChannelVolume::empty();
+    for channel in volume.channels() {
+        let raw = (channel.as_u32() as i64 + delta).max(0);
+        adjusted.push(Volume::from_u32_clamped(raw as u32));
+    }
+    adjusted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stereo(left: u32, right: u32) -> ChannelVolume {
+        let norm = Volume::NORM.as_u32();
+        let mut volume = ChannelVolume::empty();
+        volume.push(Volume::from_u32_clamped(norm * left / 100));
+        volume.push(Volume::from_u32_clamped(norm * right / 100));
+        volume
+    }
+
+    #[test]
+    fn test_volume_percent_uses_loudest_channel() {
+        assert_eq!(volume_percent(&stereo(40, 60)), 60);
+        assert_eq!(volume_percent(&stereo(100, 100)), 100);
+        assert_eq!(volume_percent(&ChannelVolume::empty()), 0);
+    }
+
+    #[test]
+    fn test_adjust_volume_keeps_balance() {
+        let adjusted = adjust_volume(&stereo(40, 60), 5, 150);
+        let channels: Vec<u32> = adjusted.channels().iter().map(|v| volume_percent_of(*v)).collect();
+        assert_eq!(channels, vec![45, 65]);
+    }
+
+    #[test]
+    fn test_adjust_volume_caps_and_floors() {
+        assert_eq!(volume_percent(&adjust_volume(&stereo(98, 98), 5, 100)), 100);
+        assert_eq!(volume_percent(&adjust_volume(&stereo(120, 120), 5, 100)), 120);
+        assert_eq!(volume_percent(&adjust_volume(&stereo(3, 3), -5, 100)), 0);
+    }
+
+    fn volume_percent_of(volume: Volume) -> u32 {
+        let mut single = ChannelVolume::empty();
+        single.push(volume);
+        volume_percent(&single)
+    }
+}
@@ -0,0 +1,116 @@
+//! Sound playback for notifications
+//!
+//! Plays user-configured sound files through GStreamer. Used by the battery
+//! module and alert rules.
+
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use gstreamer as gst;
+use gstreamer::prelude::*;
+
+/// Global flag to track if GStreamer has been initialized
+static GST_INITIALIZED: AtomicBool = AtomicBool::new(false);
+
+/// Initialize GStreamer if not already done
+fn ensure_gst_init() {
+    if !GST_INITIALIZED.swap(true, Ordering::SeqCst) {
+        if let Err(e) = gst::init() {
+            tracing::error!("Failed to initialize GStreamer: {}", e);
+            GST_INITIALIZED.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Validate and expand a sound file path
+/// Returns None if the path is invalid or not a regular file
+fn validate_sound_path(path: &str) -> Option<String> {
+    // Expand ~ to home directory
+    let expanded = if path.starts_with("~/") {
+        let home = dirs::home_dir()?;
+        home.join(&path[2..])
+    } else {
+        Path::new(path).to_path_buf()
+    };
+
+    // Canonicalize to resolve any .. or symlinks (also verifies file exists)
+    let canonical = match expanded.canonicalize() {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::warn!("Cannot resolve sound path '{}': {}", path, e);
+            return None;
+        }
+    };
+
+    // Ensure it's a regular file, not a directory or special file
+    if !canonical.is_file() {
+        tracing::warn!("Sound path '{}' is not a regular file", path);
+        return None;
+    }
+
+    Some(canonical.to_string_lossy().to_string())
+}
+
+/// Play a sound file using GStreamer (fire and forget)
+pub fn play_sound(path: &str) {
+    ensure_gst_init();
+
+    // Validate and expand the path (also handles path traversal prevention)
+    let expanded_path = match validate_sound_path(path) {
+        Some(p) => p,
+        None => return,
+    };
+
+    // Create playbin element - GStreamer will handle file not found errors
+    let uri = format!("file://{}", expanded_path);
+    let playbin = match gst::ElementFactory::make("playbin")
+        .property("uri", &uri)
+        .build()
+    {
+        Ok(p) => p,
+        Err(e) => {
+            tracing::error!("Failed to create playbin: {}", e);
+            return;
+        }
+    };
+
+    // Start playback
+    if let Err(e) = playbin.set_state(gst::State::Playing) {
+        tracing::error!("Failed to start sound playback: {}", e);
+        return;
+    }
+
+    // Spawn a task to wait for playback to finish and clean up
+    let playbin_weak = playbin.downgrade();
+    std::thread::spawn(move || {
+        let Some(playbin) = playbin_weak.upgrade() else {
+            return;
+        };
+
+        // Get the bus and wait for EOS or error
+        let bus = match playbin.bus() {
+            Some(b) => b,
+            None => {
+                let _ = playbin.set_state(gst::State::Null);
+                return;
+            }
+        };
+
+        for msg in bus.iter_timed(gst::ClockTime::from_seconds(30)) {
+            match msg.view() {
+                gst::MessageView::Eos(_) => break,
+                gst::MessageView::Error(err) => {
+                    tracing::error!(
+                        "Sound playback error: {} ({:?})",
+                        err.error(),
+                        err.debug()
+                    );
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        // Clean up
+        let _ = playbin.set_state(gst::State::Null);
+    });
+}
@@ -0,0 +1,763 @@
+//! UPower D-Bus client
+//!
+//! Enumerates the power devices known to `org.freedesktop.UPower`: laptop
+//! batteries and UPSes as well as wireless mice, keyboards and headsets. All
+//! functions take the connection to use, which is the system bus in the daemon
+//! and a private peer-to-peer bus in tests.
+
+use std::collections::HashMap;
+
+use zbus::names::InterfaceName;
+use zbus::proxy::CacheProperties;
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+use zbus::{proxy, Connection};
+
+pub const UPOWER_OBJECT_PATH: &str = "/org/freedesktop/UPower";
+
+/// Interface of the individual power devices
+const DEVICE_INTERFACE: &str = "org.freedesktop.UPower.Device";
+
+/// Root UPower object
+#[proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+pub trait UPower {
+    /// Get the object paths of all power devices
+    fn enumerate_devices(&self) -> zbus::Result<Vec<OwnedObjectPath>>;
+
+    /// Emitted when a device is plugged in or paired
+    #[zbus(signal)]
+    fn device_added(&self, device: OwnedObjectPath) -> zbus::Result<()>;
+
+    /// Emitted when a device is unplugged or disconnects
+    #[zbus(signal)]
+    fn device_removed(&self, device: OwnedObjectPath) -> zbus::Result<()>;
+
+    /// Whether the system runs on battery power
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+/// A UPower power device
+#[proxy(
+    interface = "org.freedesktop.UPower.Device",
+    default_service = "org.freedesktop.UPower"
+)]
+pub trait Device {
+    #[zbus(property)]
+    fn native_path(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn vendor(&self) -> zbus::Result<String>;
+
+    #[zbus(property)]
+    fn model(&self) -> zbus::Result<String>;
+
+    #[zbus(property, name = "Type")]
+    fn device_type(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn power_supply(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn is_present(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn percentage(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn state(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn time_to_empty(&self) -> zbus::Result<i64>;
+
+    #[zbus(property)]
+    fn time_to_full(&self) -> zbus::Result<i64>;
+
+    #[zbus(property)]
+    fn energy_rate(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn energy(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn energy_full(&self) -> zbus::Result<f64>;
+
+    #[zbus(property)]
+    fn energy_full_design(&self) -> zbus::Result<f64>;
+
+    /// Full-charge capacity relative to the design capacity, in percent
+    #[zbus(property)]
+    fn capacity(&self) -> zbus::Result<f64>;
+
+    /// Charge cycle count, or -1 if unknown
+    #[zbus(property)]
+    fn charge_cycles(&self) -> zbus::Result<i32>;
+
+    /// Get "rate" or "charge" history as (time, value, state) over the last
+    /// `timespan` seconds, with at most `resolution` points
+    fn get_history(&self, kind: &str, timespan: u32, resolution: u32) -> zbus::Result<Vec<(u32, f64, u32)>>;
+
+    /// Get the "charging" or "discharging" profile as (value, accuracy) per percent
+    fn get_statistics(&self, kind: &str) -> zbus::Result<Vec<(f64, f64)>>;
+
+    /// Charge threshold properties, available since UPower 1.90.5
+    #[zbus(property)]
+    fn charge_threshold_supported(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn charge_threshold_enabled(&self) -> zbus::Result<bool>;
+
+    #[zbus(property)]
+    fn charge_start_threshold(&self) -> zbus::Result<u32>;
+
+    #[zbus(property)]
+    fn charge_end_threshold(&self) -> zbus::Result<u32>;
+}
+
+/// UPower device type (UpDeviceKind)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeviceKind {
+    LinePower,
+    Battery,
+    Ups,
+    Monitor,
+    Mouse,
+    Keyboard,
+    Phone,
+    MediaPlayer,
+    Tablet,
+    Computer,
+    GamingInput,
+    Pen,
+    Touchpad,
+    Headset,
+    Speakers,
+    Headphones,
+    OtherAudio,
+    RemoteControl,
+    Other,
+}
+
+impl DeviceKind {
+    pub fn from_u32(value: u32) -> Self {
+        match value {
+            1 => DeviceKind::LinePower,
+            2 => DeviceKind::Battery,
+            3 => DeviceKind::Ups,
+            4 => DeviceKind::Monitor,
+            5 => DeviceKind::Mouse,
+            6 => DeviceKind::Keyboard,
+            8 => DeviceKind::Phone,
+            9 => DeviceKind::MediaPlayer,
+            10 => DeviceKind::Tablet,
+            11 => DeviceKind::Computer,
+            12 => DeviceKind::GamingInput,
+            13 => DeviceKind::Pen,
+            14 => DeviceKind::Touchpad,
+            17 => DeviceKind::Headset,
+            18 => DeviceKind::Speakers,
+            19 => DeviceKind::Headphones,
+            21 => DeviceKind::OtherAudio,
+            22 => DeviceKind::RemoteControl,
+            _ => DeviceKind::Other,
+        }
+    }
+
+    /// Name used in the `include_kinds`/`exclude_kinds` config options
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            DeviceKind::LinePower => "line-power",
+            DeviceKind::Battery => "battery",
+            DeviceKind::Ups => "ups",
+            DeviceKind::Monitor => "monitor",
+            DeviceKind::Mouse => "mouse",
+            DeviceKind::Keyboard => "keyboard",
+            DeviceKind::Phone => "phone",
+            DeviceKind::MediaPlayer => "media-player",
+            DeviceKind::Tablet => "tablet",
+            DeviceKind::Computer => "computer",
+            DeviceKind::GamingInput => "gaming-input",
+            DeviceKind::Pen => "pen",
+            DeviceKind::Touchpad => "touchpad",
+            DeviceKind::Headset => "headset",
+            DeviceKind::Speakers => "speakers",
+            DeviceKind::Headphones => "headphones",
+            DeviceKind::OtherAudio => "other-audio",
+            DeviceKind::RemoteControl => "remote-control",
+            DeviceKind::Other => "other",
+        }
+    }
+
+    /// Human readable device type
+    pub fn description(&self) -> &'static str {
+        match self {
+            DeviceKind::LinePower => "Line power",
+            DeviceKind::Battery => "Battery",
+            DeviceKind::Ups => "UPS",
+            DeviceKind::Monitor => "Monitor",
+            DeviceKind::Mouse => "Mouse",
+            DeviceKind::Keyboard => "Keyboard",
+            DeviceKind::Phone => "Phone",
+            DeviceKind::MediaPlayer => "Media player",
+            DeviceKind::Tablet => "Tablet",
+            DeviceKind::Computer => "Computer",
+            DeviceKind::GamingInput => "Game controller",
+            DeviceKind::Pen => "Pen",
+            DeviceKind::Touchpad => "Touchpad",
+            DeviceKind::Headset => "Headset",
+            DeviceKind::Speakers => "Speakers",
+            DeviceKind::Headphones => "Headphones",
+            DeviceKind::OtherAudio => "Audio device",
+            DeviceKind::RemoteControl => "Remote control",
+            DeviceKind::Other => "Device",
+        }
+    }
+
+    /// Themed icon for peripherals, or None for batteries that use the charge icon
+    pub fn icon_name(&self) -> Option<&'static str> {
+        match self {
+            DeviceKind::Mouse => Some("input-mouse"),
+            DeviceKind::Keyboard => Some("input-keyboard"),
+            DeviceKind::Phone => Some("phone"),
+            DeviceKind::MediaPlayer => Some("multimedia-player"),
+            DeviceKind::Tablet | DeviceKind::Pen => Some("input-tablet"),
+            DeviceKind::Computer => Some("computer"),
+            DeviceKind::GamingInput => Some("input-gaming"),
+            DeviceKind::Touchpad => Some("input-touchpad"),
+            DeviceKind::Headset => Some("audio-headset"),
+            DeviceKind::Speakers => Some("audio-speakers"),
+            DeviceKind::Headphones => Some("audio-headphones"),
+            DeviceKind::OtherAudio => Some("audio-card"),
+            DeviceKind::RemoteControl => Some("input-remote"),
+            DeviceKind::Monitor => Some("video-display"),
+            DeviceKind::LinePower | DeviceKind::Battery | DeviceKind::Ups | DeviceKind::Other => None,
+        }
+    }
+}
+
+/// Battery charge limiting, which keeps a laptop battery between two
+/// percentages while on AC to slow its wear
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChargeThreshold {
+    pub enabled: bool,
+    /// Charging starts below this percentage
+    pub start: u32,
+    /// Charging stops at this percentage
+    pub end: u32,
+}
+
+/// Wear of a rechargeable battery
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryHealth {
+    /// Full-charge capacity relative to the design capacity, in percent
+    pub capacity: f64,
+    /// Energy when full, in Wh
+    pub energy_full: f64,
+    /// Energy when full as designed, in Wh
+    pub energy_full_design: f64,
+    pub charge_cycles: Option<u32>,
+}
+
+/// One point of UPower's device history
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HistoryPoint {
+    /// Unix time in seconds
+    pub time: u32,
+    /// Watts for "rate" history, percent for "charge" history
+    pub value: f64,
+    /// UpDeviceState value
+    pub state: u32,
+}
+
+/// A snapshot of one power device
+#[derive(Debug, Clone, PartialEq)]
+pub struct PowerDevice {
+    /// Object path of the device
+    pub path: String,
+    /// Kernel name, e.g. "BAT0" or "hidpp_battery_0"
+    pub native_path: String,
+    pub kind: DeviceKind,
+    pub vendor: String,
+    pub model: String,
+    /// Whether the device powers the computer (laptop battery, UPS) rather
+    /// than being a peripheral with its own battery
+    pub power_supply: bool,
+    pub is_present: bool,
+    pub percentage: f64,
+    /// UpDeviceState value
+    pub state: u32,
+    pub time_to_empty: i64,
+    pub time_to_full: i64,
+    /// Charge or discharge rate in watts (0 if unknown)
+    pub energy_rate: f64,
+    /// Charge threshold state, if the device supports one
+    pub charge_threshold: Option<ChargeThreshold>,
+    /// Current energy in Wh (0 if unknown)
+    pub energy: f64,
+    /// Battery wear, for batteries that report their design capacity
+    pub health: Option<BatteryHealth>,
+}
+
+impl PowerDevice {
+    /// Name for display: vendor and model when known, else the device type
+    pub fn display_name(&self) -> String {
+        let model = self.model.trim();
+        let vendor = self.vendor.trim();
+        if model.is_empty() {
+            self.kind.description().to_string()
+        } else if vendor.is_empty() || model.starts_with(vendor) {
+            model.to_string()
+        } else {
+            format!("{} {}", vendor, model)
+        }
+    }
+
+    /// Last segment of the object path, e.g. "battery_BAT0", used in item ids
+    pub fn short_id(&self) -> &str {
+        self.path.rsplit('/').next().unwrap_or(&self.path)
+    }
+}
+
+/// Get the object paths of all power devices
+pub async fn enumerate_devices(connection: &Connection) -> zbus::Result<Vec<OwnedObjectPath>> {
+    UPowerProxy::new(connection).await?.enumerate_devices().await
+}
+
+/// Read the properties of one device
+///
+/// All properties come from a single GetAll call, since devices are reread
+/// whenever they report a change.
+pub async fn fetch_device(connection: &Connection, path: &OwnedObjectPath) -> zbus::Result<PowerDevice> {
+    let properties = zbus::fdo::PropertiesProxy::builder(connection)
+        .destination("org.freedesktop.UPower")?
+        .path(path.clone())?
+        .cache_properties(CacheProperties::No)
+        .build()
+        .await?;
+    let props = properties
+        .get_all(InterfaceName::from_static_str_unchecked(DEVICE_INTERFACE))
+        .await?;
+
+    let charge_threshold = if prop(&props, "ChargeThresholdSupported").unwrap_or(false) {
+        Some(ChargeThreshold {
+            enabled: prop(&props, "ChargeThresholdEnabled").unwrap_or(false),
+            start: prop(&props, "ChargeStartThreshold").unwrap_or(0),
+            end: prop(&props, "ChargeEndThreshold").unwrap_or(100),
+        })
+    } else {
+        None
+    };
+
+    let energy_full_design = prop(&props, "EnergyFullDesign").unwrap_or(0.0);
+    let health = if energy_full_design > 0.0 {
+        let energy_full = prop(&props, "EnergyFull").unwrap_or(0.0);
+        let capacity = match prop::<f64>(&props, "Capacity") {
+            Some(capacity) if capacity > 0.0 => capacity,
+            _ => energy_full / energy_full_design * 100.0,
+        };
+        Some(BatteryHealth {
+            capacity,
+            energy_full,
+            energy_full_design,
+            charge_cycles: prop::<i32>(&props, "ChargeCycles").and_then(|c| u32::try_from(c).ok()),
+        })
+    } else {
+        None
+    };
+
+    let kind = prop(&props, "Type")
+        .ok_or_else(|| zbus::Error::Failure(format!("{} has no device type", path.as_str())))?;
+
+    Ok(PowerDevice {
+        path: path.to_string(),
+        native_path: prop(&props, "NativePath").unwrap_or_default(),
+        kind: DeviceKind::from_u32(kind),
+        vendor: prop(&props, "Vendor").unwrap_or_default(),
+        model: prop(&props, "Model").unwrap_or_default(),
+        power_supply: prop(&props, "PowerSupply").unwrap_or(false),
+        is_present: prop(&props, "IsPresent").unwrap_or(false),
+        percentage: prop(&props, "Percentage").unwrap_or(0.0),
+        state: prop(&props, "State").unwrap_or(0),
+        time_to_empty: prop(&props, "TimeToEmpty").unwrap_or(0),
+        time_to_full: prop(&props, "TimeToFull").unwrap_or(0),
+        energy_rate: prop(&props, "EnergyRate").unwrap_or(0.0),
+        charge_threshold,
+        energy: prop(&props, "Energy").unwrap_or(0.0),
+        health,
+    })
+}
+
+/// A property from a GetAll reply, if present and of the expected type
+fn prop<'a, T>(props: &'a HashMap<String, OwnedValue>, name: &str) -> Option<T>
+where
+    T: TryFrom<&'a Value<'a>>,
+    <T as TryFrom<&'a Value<'a>>>::Error: Into<zbus::zvariant::Error>,
+{
+    props.get(name)?.downcast_ref().ok()
+}
+
+/// Whether the system runs on battery power
+pub async fn on_battery(connection: &Connection) -> zbus::Result<bool> {
+    UPowerProxy::new(connection).await?.on_battery().await
+}
+
+/// Subscribe to property changes of a device
+pub async fn receive_device_changes(
+    connection: &Connection,
+    path: &str,
+) -> zbus::Result<zbus::fdo::PropertiesChangedStream> {
+    let properties = zbus::fdo::PropertiesProxy::builder(connection)
+        .destination("org.freedesktop.UPower")?
+        .path(path.to_string())?
+        .build()
+        .await?;
+    properties.receive_properties_changed().await
+}
+
+/// Get UPower's recorded "rate" or "charge" history of a device
+pub async fn get_history(
+    connection: &Connection,
+    path: &str,
+    kind: &str,
+    timespan: u32,
+    resolution: u32,
+) -> zbus::Result<Vec<HistoryPoint>> {
+    let device = DeviceProxy::builder(connection).path(path.to_string())?.build().await?;
+    let points = device.get_history(kind, timespan, resolution).await?;
+    Ok(points
+        .into_iter()
+        .map(|(time, value, state)| HistoryPoint { time, value, state })
+        .collect())
+}
+
+/// Get UPower's "charging" or "discharging" profile of a device
+pub async fn get_statistics(connection: &Connection, path: &str, kind: &str) -> zbus::Result<Vec<(f64, f64)>> {
+    let device = DeviceProxy::builder(connection).path(path.to_string())?.build().await?;
+    device.get_statistics(kind).await
+}
+
+/// Read all power devices, skipping any that disappear while being read
+pub async fn list_devices(connection: &Connection) -> zbus::Result<Vec<PowerDevice>> {
+    let mut devices = Vec::new();
+    for path in enumerate_devices(connection).await? {
+        match fetch_device(connection, &path).await {
+            Ok(device) => devices.push(device),
+            Err(e) => tracing::debug!("Failed to read UPower device {}: {}", path.as_str(), e),
+        }
+    }
+    Ok(devices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use zbus::object_server::SignalEmitter;
+    use zbus::{connection, interface};
+
+    struct MockUPower {
+        devices: Vec<OwnedObjectPath>,
+    }
+
+    #[interface(name = "org.freedesktop.UPower")]
+    impl MockUPower {
+        fn enumerate_devices(&self) -> Vec<OwnedObjectPath> {
+            self.devices.clone()
+        }
+
+        #[zbus(property)]
+        fn on_battery(&self) -> bool {
+            true
+        }
+
+        #[zbus(signal, name = "DeviceAdded")]
+        async fn emit_device_added(emitter: &SignalEmitter<'_>, device: OwnedObjectPath) -> zbus::Result<()>;
+    }
+
+    struct MockDevice {
+        native_path: &'static str,
+        kind: u32,
+        model: &'static str,
+        power_supply: bool,
+        percentage: f64,
+        state: u32,
+    }
+
+    #[interface(name = "org.freedesktop.UPower.Device")]
+    impl MockDevice {
+        #[zbus(property)]
+        fn native_path(&self) -> String {
+            self.native_path.to_string()
+        }
+
+        #[zbus(property)]
+        fn vendor(&self) -> String {
+            "Logitech".to_string()
+        }
+
+        #[zbus(property)]
+        fn model(&self) -> String {
+            self.model.to_string()
+        }
+
+        #[zbus(property, name = "Type")]
+        fn device_type(&self) -> u32 {
+            self.kind
+        }
+
+        #[zbus(property)]
+        fn power_supply(&self) -> bool {
+            self.power_supply
+        }
+
+        #[zbus(property)]
+        fn is_present(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn percentage(&self) -> f64 {
+            self.percentage
+        }
+
+        #[zbus(property)]
+        fn state(&self) -> u32 {
+            self.state
+        }
+
+        #[zbus(property)]
+        fn time_to_empty(&self) -> i64 {
+            5400
+        }
+
+        #[zbus(property)]
+        fn time_to_full(&self) -> i64 {
+            0
+        }
+
+        fn get_history(&self, kind: &str, _timespan: u32, _resolution: u32) -> Vec<(u32, f64, u32)> {
+            match kind {
+                "rate" => vec![(1000, 12.0, 2), (2000, 0.0, 1), (3000, 6.0, 2)],
+                _ => Vec::new(),
+            }
+        }
+
+        fn get_statistics(&self, _kind: &str) -> Vec<(f64, f64)> {
+            vec![(1.0, 100.0), (1.1, 50.0)]
+        }
+
+        #[zbus(property)]
+        fn energy(&self) -> f64 {
+            self.percentage / 2.0
+        }
+
+        #[zbus(property)]
+        fn energy_full(&self) -> f64 {
+            if self.kind == 2 { 50.0 } else { 0.0 }
+        }
+
+        #[zbus(property)]
+        fn energy_full_design(&self) -> f64 {
+            if self.kind == 2 { 57.0 } else { 0.0 }
+        }
+
+        #[zbus(property)]
+        fn capacity(&self) -> f64 {
+            0.0
+        }
+
+        #[zbus(property)]
+        fn charge_cycles(&self) -> i32 {
+            if self.kind == 2 { 213 } else { -1 }
+        }
+
+        #[zbus(property)]
+        fn energy_rate(&self) -> f64 {
+            if self.power_supply { 8.25 } else { 0.0 }
+        }
+
+        #[zbus(property)]
+        fn charge_threshold_supported(&self) -> bool {
+            self.kind == 2
+        }
+
+        #[zbus(property)]
+        fn charge_threshold_enabled(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn charge_start_threshold(&self) -> u32 {
+            75
+        }
+
+        #[zbus(property)]
+        fn charge_end_threshold(&self) -> u32 {
+            80
+        }
+    }
+
+    fn path(p: &str) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(p).unwrap()
+    }
+
+    /// Serve a mock UPower with a laptop battery, a mouse and the AC adapter
+    /// on a private bus
+    async fn mock_upower() -> (Connection, Connection) {
+        let (server_stream, client_stream) = std::os::unix::net::UnixStream::pair().unwrap();
+        let guid = zbus::Guid::generate();
+
+        let battery = "/org/freedesktop/UPower/devices/battery_BAT0";
+        let mouse = "/org/freedesktop/UPower/devices/mouse_hidpp_battery_0";
+        let ac = "/org/freedesktop/UPower/devices/line_power_AC";
+
+        let server = connection::Builder::async_io_unix_stream(server_stream)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(
+                UPOWER_OBJECT_PATH,
+                MockUPower { devices: vec![path(ac), path(battery), path(mouse)] },
+            )
+            .unwrap()
+            .serve_at(
+                battery,
+                MockDevice {
+                    native_path: "BAT0",
+                    kind: 2,
+                    model: "",
+                    power_supply: true,
+                    percentage: 81.0,
+                    state: 2,
+                },
+            )
+            .unwrap()
+            .serve_at(
+                mouse,
+                MockDevice {
+                    native_path: "hidpp_battery_0",
+                    kind: 5,
+                    model: "MX Master 3",
+                    power_supply: false,
+                    percentage: 8.0,
+                    state: 0,
+                },
+            )
+            .unwrap()
+            .serve_at(
+                ac,
+                MockDevice {
+                    native_path: "AC",
+                    kind: 1,
+                    model: "",
+                    power_supply: true,
+                    percentage: 0.0,
+                    state: 0,
+                },
+            )
+            .unwrap()
+            .build();
+        let client = connection::Builder::async_io_unix_stream(client_stream).p2p().build();
+
+        futures::try_join!(server, client).unwrap()
+    }
+
+    #[test]
+    fn test_kind_mapping() {
+        assert_eq!(DeviceKind::from_u32(5), DeviceKind::Mouse);
+        assert_eq!(DeviceKind::from_u32(17).config_name(), "headset");
+        assert_eq!(DeviceKind::from_u32(12).description(), "Game controller");
+        assert_eq!(DeviceKind::from_u32(99), DeviceKind::Other);
+        assert_eq!(DeviceKind::Battery.icon_name(), None);
+    }
+
+    #[tokio::test]
+    async fn test_list_devices_from_mock() {
+        let (_server, client) = mock_upower().await;
+
+        let devices = list_devices(&client).await.unwrap();
+        assert_eq!(devices.len(), 3);
+        assert_eq!(devices[0].kind, DeviceKind::LinePower);
+
+        let battery = &devices[1];
+        assert_eq!(battery.native_path, "BAT0");
+        assert!(battery.power_supply);
+        assert_eq!(battery.percentage, 81.0);
+        assert_eq!(battery.short_id(), "battery_BAT0");
+        assert_eq!(battery.display_name(), "Battery");
+        assert_eq!(battery.energy_rate, 8.25);
+        assert_eq!(
+            battery.charge_threshold,
+            Some(ChargeThreshold { enabled: true, start: 75, end: 80 })
+        );
+
+        let mouse = &devices[2];
+        assert_eq!(mouse.kind, DeviceKind::Mouse);
+        assert!(!mouse.power_supply);
+        assert_eq!(mouse.display_name(), "Logitech MX Master 3");
+        assert_eq!(mouse.charge_threshold, None);
+        assert_eq!(mouse.health, None);
+
+        let health = battery.health.unwrap();
+        assert_eq!(health.charge_cycles, Some(213));
+        // Computed from the energies when Capacity is unset
+        assert!((health.capacity - 87.7).abs() < 0.1);
+        assert_eq!(battery.energy, 40.5);
+
+        let history = get_history(&client, &battery.path, "rate", 86400, 100).await.unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(history[0], HistoryPoint { time: 1000, value: 12.0, state: 2 });
+        assert_eq!(get_statistics(&client, &battery.path, "discharging").await.unwrap().len(), 2);
+
+        assert!(on_battery(&client).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_device_changes_from_mock() {
+        let (server, client) = mock_upower().await;
+        let battery = "/org/freedesktop/UPower/devices/battery_BAT0";
+
+        let mut changes = receive_device_changes(&client, battery).await.unwrap();
+
+        let iface = server
+            .object_server()
+            .interface::<_, MockDevice>(battery)
+            .await
+            .unwrap();
+        iface.get_mut().await.percentage = 79.0;
+        iface
+            .get()
+            .await
+            .percentage_changed(iface.signal_emitter())
+            .await
+            .unwrap();
+
+        let signal = changes.next().await.unwrap();
+        let args = signal.args().unwrap();
+        assert_eq!(args.interface_name.as_str(), "org.freedesktop.UPower.Device");
+        assert!(args.changed_properties.contains_key("Percentage"));
+        assert_eq!(fetch_device(&client, &path(battery)).await.unwrap().percentage, 79.0);
+    }
+
+    #[tokio::test]
+    async fn test_device_added_signal_from_mock() {
+        let (server, client) = mock_upower().await;
+
+        let upower = UPowerProxy::new(&client).await.unwrap();
+        let mut stream = upower.receive_device_added().await.unwrap();
+
+        let emitter = SignalEmitter::new(&server, UPOWER_OBJECT_PATH).unwrap();
+        MockUPower::emit_device_added(&emitter, path("/org/freedesktop/UPower/devices/keyboard_0"))
+            .await
+            .unwrap();
+
+        let signal = stream.next().await.unwrap();
+        assert_eq!(
+            signal.args().unwrap().device.as_str(),
+            "/org/freedesktop/UPower/devices/keyboard_0"
+        );
+    }
+}